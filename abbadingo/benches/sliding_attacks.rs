@@ -0,0 +1,72 @@
+//! Benchmark of the sliding attacks backends: the kindergarten bitboards
+//! lookups against the magic bitboards (and the other backends as reference).
+//!
+//! Run with `cargo bench --bench sliding_attacks`: all the backends compute
+//! the bishop and rook attacks from the same cells with the same occupancies.
+
+use abbadingo::bbdefines::*;
+use abbadingo::magics::{init_magics, MagicAttacks};
+use abbadingo::slidingattacks::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Number of occupancies the attacks are computed with, from each cell
+const NUM_OCCUPANCIES: usize = 1_000;
+// Number of times the whole set of lookups is repeated
+const NUM_ROUNDS: usize = 20;
+
+// ------------------------------------------------------------
+fn main() {
+    let occupancies = random_occupancies(NUM_OCCUPANCIES);
+    let cells: Vec<Cell> = (0..NUM_CELLS)
+        .map(|ndx| num::FromPrimitive::from_usize(ndx).unwrap())
+        .collect();
+    // The magic tables are generated outside of the measure
+    init_magics();
+
+    let reference = run::<ClassicalAttacks>("Classical", &cells, &occupancies);
+    let kogge_stone = run::<KoggeStoneAttacks>("Kogge-Stone", &cells, &occupancies);
+    let kindergarten = run::<KindergartenAttacks>("Kindergarten", &cells, &occupancies);
+    let magic = run::<MagicAttacks>("Magic", &cells, &occupancies);
+    assert_eq!(kogge_stone, reference);
+    assert_eq!(kindergarten, reference);
+    assert_eq!(magic, reference);
+}
+
+// Computes the bishop and rook attacks from all the cells with all the
+// occupancies, printing the mean time per lookup; returns a checksum of
+// the attacks to compare the backends
+fn run<T: SlidingAttacks>(name: &str, cells: &[Cell], occupancies: &[BitBoardState]) -> u64 {
+    let mut checksum = 0;
+    let start = Instant::now();
+    for _ in 0..NUM_ROUNDS {
+        checksum = 0;
+        for &c in cells {
+            for &occupancy in occupancies {
+                checksum ^= T::bishop_attacks(black_box(c), black_box(occupancy));
+                checksum =
+                    checksum.rotate_left(1) ^ T::rook_attacks(black_box(c), black_box(occupancy));
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    let lookups = (2 * NUM_ROUNDS * cells.len() * occupancies.len()) as f64;
+    println!(
+        "{:>12}: {:6.2} ns/lookup",
+        name,
+        elapsed.as_nanos() as f64 / lookups
+    );
+    checksum
+}
+
+// Returns the given number of pseudo-random sparse occupancies (xorshift64)
+fn random_occupancies(count: usize) -> Vec<BitBoardState> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count).map(|_| next() & next() & next()).collect()
+}
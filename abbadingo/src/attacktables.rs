@@ -0,0 +1,512 @@
+//! Precomputed attack tables for an 8x8 board.
+//!
+//! All the tables in this module are generated at compile time using `const fn`s,
+//! so there is no initialization cost at runtime and the tables can also be used
+//! in `const` contexts. Each table is indexed using the [Cell] value as `usize`
+//! (A1 = 0, ..., H8 = 63); the bit masks are the same [BitBoardState] used
+//! in the [BitBoard](crate::bitboard::BitBoard) structure.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::attacktables::*;
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::bitboard::BitBoard;
+//! assert_eq!(
+//!     BitBoard::from(KNIGHT_ATTACKS[Cell::A1 as usize]),
+//!     BitBoard::from_cells(&[Cell::B3, Cell::C2])
+//! );
+//! ```
+//!
+//! Other tables of the library (e.g. the [KPK bitbase](crate::kpk)) are instead
+//! generated at runtime, when first used: the [init_tables] function can be used
+//! to build all of them in advance, and [tables_status] to know how each table
+//! is built.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::bbdefines::*;
+use crate::bitboard::BitBoard;
+use crate::chessdefines::{ArmyColour, ChessPiece};
+use crate::slidingattacks::{bishop_attacks, queen_attacks, rook_attacks};
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+const KNIGHT_STEPS: [(i32, i32); 8] = [
+    (2, 1),
+    (1, 2),
+    (-1, 2),
+    (-2, 1),
+    (-2, -1),
+    (-1, -2),
+    (1, -2),
+    (2, -1),
+];
+const KING_STEPS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const WHITE_PAWN_STEPS: [(i32, i32); 2] = [(1, -1), (1, 1)];
+const BLACK_PAWN_STEPS: [(i32, i32); 2] = [(-1, -1), (-1, 1)];
+
+const ALL_DIRECTIONS: [Direction; NUM_DIRECTIONS] = [
+    Direction::North,
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::South,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest,
+];
+
+/// The cells attacked by a Knight placed in a given [Cell].
+pub const KNIGHT_ATTACKS: [BitBoardState; NUM_CELLS] = gen_step_attacks(&KNIGHT_STEPS);
+
+/// The cells attacked by a King placed in a given [Cell].
+pub const KING_ATTACKS: [BitBoardState; NUM_CELLS] = gen_step_attacks(&KING_STEPS);
+
+/// The cells attacked by a pawn placed in a given [Cell].
+///
+/// The table is indexed first by the [ArmyColour] of the pawn, then by the [Cell].
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessdefines::ArmyColour;
+/// assert_eq!(
+///     PAWN_ATTACKS[ArmyColour::White as usize][Cell::E4 as usize],
+///     single_cell(Cell::D5) | single_cell(Cell::F5)
+/// );
+/// assert_eq!(
+///     PAWN_ATTACKS[ArmyColour::Black as usize][Cell::A7 as usize],
+///     single_cell(Cell::B6)
+/// );
+/// ```
+pub const PAWN_ATTACKS: [[BitBoardState; NUM_CELLS]; 2] = [
+    gen_step_attacks(&WHITE_PAWN_STEPS),
+    gen_step_attacks(&BLACK_PAWN_STEPS),
+];
+
+/// The rays starting from a given [Cell] (excluded) in a given [Direction],
+/// up to the edge of the board.
+///
+/// The table is indexed first by the [Direction], then by the [Cell].
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// assert_eq!(
+///     RAYS[Direction::NorthEast as usize][Cell::E5 as usize],
+///     single_cell(Cell::F6) | single_cell(Cell::G7) | single_cell(Cell::H8)
+/// );
+/// assert_eq!(RAYS[Direction::West as usize][Cell::A3 as usize], EMPTY_STATE);
+/// ```
+pub const RAYS: [[BitBoardState; NUM_CELLS]; NUM_DIRECTIONS] = gen_rays();
+
+/// The cells strictly between two [Cell]s, if they are on the same
+/// [Rank], [File], [Diagonal] or [AntiDiagonal], the empty state otherwise.
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// assert_eq!(
+///     BETWEEN[Cell::B2 as usize][Cell::E5 as usize],
+///     single_cell(Cell::C3) | single_cell(Cell::D4)
+/// );
+/// assert_eq!(BETWEEN[Cell::B2 as usize][Cell::C4 as usize], EMPTY_STATE);
+/// ```
+///
+/// Given its size (32 KiB) this table is a `static` instead of a `const`, to avoid
+/// copies of it to be inlined where used; it is anyway computed at compile time.
+pub static BETWEEN: [[BitBoardState; NUM_CELLS]; NUM_CELLS] = gen_between();
+
+/// The attacks of a sliding piece along the first [Rank], used by the
+/// [kindergarten](crate::slidingattacks::KindergartenAttacks) sliding attacks.
+///
+/// The table is indexed first by the [File] of the sliding piece, then by the
+/// occupancy of the inner cells of the rank (the 6 cells from B1 to G1, bit 0
+/// for B1): the occupancy of the outer cells does not change the attacks. The
+/// attacked cells are returned as an 8-bit value, with bit 0 for A1.
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// // Rook in C1, with blockers in E1 (bit 3 of the inner cells) and G1 (bit 5)
+/// assert_eq!(FIRST_RANK_ATTACKS[File::FileC as usize][0b10_1000], 0b0001_1011);
+/// ```
+pub const FIRST_RANK_ATTACKS: [[u8; 64]; NUM_FILES] = gen_first_rank_attacks();
+
+/// How a lookup table of the library is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableInit {
+    /// The table is computed at compile time, so it is always available.
+    CompileTime,
+    /// The table is computed at runtime when first used (or by [init_tables]);
+    /// `ready` is true if the table has already been computed.
+    Lazy { ready: bool },
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Returns the cells attacked by a pawn of the given [ArmyColour] placed in the given [Cell].
+///
+/// Convenience wrapper around the [PAWN_ATTACKS] table.
+pub const fn pawn_attacks(c: Cell, ac: ArmyColour) -> BitBoardState {
+    PAWN_ATTACKS[ac as usize][c as usize]
+}
+
+/// Returns the cells attacked by a piece of the given type placed in the given
+/// [Cell], with the given occupancy of the board.
+///
+/// The occupancy limits the view of the sliding pieces (Bishops, Rooks and Queens):
+/// the first occupied cell found in each direction is the last attacked one,
+/// whatever the colour of the piece occupying it. Leaper pieces (King and Knights)
+/// are not affected by the occupancy.
+///
+/// The cells attacked by a pawn depend on its colour, that is not known here:
+/// for [ChessPiece::Pawn] the empty [BitBoard] is returned, use [pawn_attacks] instead.
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::bitboard::BitBoard;
+/// # use abbadingo::chessdefines::ChessPiece;
+/// // What would a rook on d4 see with pieces in d6 and b4?
+/// let occupancy = BitBoard::from_cells(&[Cell::D6, Cell::B4, Cell::D4]);
+/// assert_eq!(
+///     attacks_from(ChessPiece::Rook, Cell::D4, occupancy),
+///     BitBoard::from_cells(&[
+///         Cell::D1, Cell::D2, Cell::D3, Cell::D5, Cell::D6,
+///         Cell::B4, Cell::C4, Cell::E4, Cell::F4, Cell::G4, Cell::H4
+///     ])
+/// );
+/// assert_eq!(
+///     attacks_from(ChessPiece::Knight, Cell::A1, occupancy),
+///     BitBoard::from_cells(&[Cell::B3, Cell::C2])
+/// );
+/// ```
+pub fn attacks_from(piece: ChessPiece, cell: Cell, occupancy: BitBoard) -> BitBoard {
+    BitBoard::from(match piece {
+        ChessPiece::King => KING_ATTACKS[cell as usize],
+        ChessPiece::Queen => queen_attacks(cell, occupancy.state),
+        ChessPiece::Bishop => bishop_attacks(cell, occupancy.state),
+        ChessPiece::Knight => KNIGHT_ATTACKS[cell as usize],
+        ChessPiece::Rook => rook_attacks(cell, occupancy.state),
+        ChessPiece::Pawn => EMPTY_STATE,
+    })
+}
+
+/// Rotates an 8-bit rank occupancy (bit 0 for the [File] A) onto the [File] A,
+/// with bit 0 moved to A1 and bit 7 moved to A8.
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// # use abbadingo::bbdefines::*;
+/// assert_eq!(rank_bits_to_file_a(0b1000_0010), single_cell(Cell::A2) | single_cell(Cell::A8));
+/// ```
+pub const fn rank_bits_to_file_a(bits: u8) -> BitBoardState {
+    let mut bbs = EMPTY_STATE;
+    let mut r = 0;
+    while r < NUM_RANKS {
+        bbs |= (((bits >> r) & 1) as BitBoardState) << (r * NUM_FILES);
+        r += 1;
+    }
+    bbs
+}
+
+/// Builds all the lookup tables of the library that are computed at runtime,
+/// if not already done.
+///
+/// Latency sensitive applications (e.g. an engine answering to the UCI `isready`
+/// command) can call this function at startup, instead of paying the
+/// initialization cost at the first use of each table. Calling it more than
+/// once is harmless.
+///
+pub fn init_tables() {
+    #[cfg(feature = "std")]
+    crate::kpk::init_kpk();
+    #[cfg(feature = "magic")]
+    crate::magics::init_magics();
+}
+
+/// Returns the name and the [TableInit] status of each lookup table of the library.
+///
+/// # Example
+/// ```
+/// # use abbadingo::attacktables::*;
+/// init_tables();
+/// assert!(tables_status()
+///     .iter()
+///     .all(|(_, s)| matches!(s, TableInit::CompileTime | TableInit::Lazy { ready: true })));
+/// ```
+pub fn tables_status() -> Vec<(&'static str, TableInit)> {
+    #[allow(unused_mut)] // Without std there are no lazily built tables
+    let mut status = vec![
+        ("KNIGHT_ATTACKS", TableInit::CompileTime),
+        ("KING_ATTACKS", TableInit::CompileTime),
+        ("PAWN_ATTACKS", TableInit::CompileTime),
+        ("RAYS", TableInit::CompileTime),
+        ("BETWEEN", TableInit::CompileTime),
+        ("FIRST_RANK_ATTACKS", TableInit::CompileTime),
+    ];
+    #[cfg(feature = "std")]
+    status.push((
+        "KPK bitbase",
+        TableInit::Lazy {
+            ready: crate::kpk::kpk_is_initialized(),
+        },
+    ));
+    #[cfg(feature = "magic")]
+    status.push((
+        "Magic bitboards",
+        TableInit::Lazy {
+            ready: crate::magics::magics_are_initialized(),
+        },
+    ));
+    status
+}
+
+// ---------------------------------------------------------------------------
+// PRIVATE FUNCTIONS (tables generation)
+// ---------------------------------------------------------------------------
+
+/// Computes the index of the cell reached starting from the cell with index
+/// `ndx` and performing the given steps (as in [calc_cell_after_steps]),
+/// returning `None` if the cell is outside the board.
+///
+const fn ndx_after_steps(ndx: usize, step_north: i32, step_east: i32) -> Option<usize> {
+    let r = (ndx / NUM_FILES) as i32 + step_north;
+    let f = (ndx % NUM_FILES) as i32 + step_east;
+    if r < 0 || r >= NUM_RANKS as i32 || f < 0 || f >= NUM_FILES as i32 {
+        None
+    } else {
+        Some((r as usize) * NUM_FILES + f as usize)
+    }
+}
+
+/// Generates the attack table of a "leaper" piece, given its steps.
+///
+const fn gen_step_attacks(steps: &[(i32, i32)]) -> [BitBoardState; NUM_CELLS] {
+    let mut table = [EMPTY_STATE; NUM_CELLS];
+    let mut ndx = 0;
+    while ndx < NUM_CELLS {
+        let mut s = 0;
+        while s < steps.len() {
+            if let Some(dest) = ndx_after_steps(ndx, steps[s].0, steps[s].1) {
+                table[ndx] |= 1 << dest;
+            }
+            s += 1;
+        }
+        ndx += 1;
+    }
+    table
+}
+
+/// Generates the rays table.
+///
+const fn gen_rays() -> [[BitBoardState; NUM_CELLS]; NUM_DIRECTIONS] {
+    let mut table = [[EMPTY_STATE; NUM_CELLS]; NUM_DIRECTIONS];
+    let mut d = 0;
+    while d < NUM_DIRECTIONS {
+        let (step_north, step_east) = ALL_DIRECTIONS[d].steps();
+        let mut ndx = 0;
+        while ndx < NUM_CELLS {
+            let mut cur = ndx_after_steps(ndx, step_north, step_east);
+            while let Some(c) = cur {
+                table[d][ndx] |= 1 << c;
+                cur = ndx_after_steps(c, step_north, step_east);
+            }
+            ndx += 1;
+        }
+        d += 1;
+    }
+    table
+}
+
+/// Generates the "between" table.
+///
+const fn gen_between() -> [[BitBoardState; NUM_CELLS]; NUM_CELLS] {
+    let mut table = [[EMPTY_STATE; NUM_CELLS]; NUM_CELLS];
+    let mut from = 0;
+    while from < NUM_CELLS {
+        let mut d = 0;
+        while d < NUM_DIRECTIONS {
+            let (step_north, step_east) = ALL_DIRECTIONS[d].steps();
+            let mut in_between = EMPTY_STATE;
+            let mut cur = ndx_after_steps(from, step_north, step_east);
+            while let Some(c) = cur {
+                table[from][c] = in_between;
+                in_between |= 1 << c;
+                cur = ndx_after_steps(c, step_north, step_east);
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    table
+}
+
+/// Generates the first rank attacks table.
+///
+const fn gen_first_rank_attacks() -> [[u8; 64]; NUM_FILES] {
+    let mut table = [[0; 64]; NUM_FILES];
+    let mut f = 0;
+    while f < NUM_FILES {
+        let mut inner = 0;
+        while inner < 64 {
+            let occupancy = inner << 1;
+            let mut east = f + 1;
+            while east < NUM_FILES {
+                table[f][inner] |= 1 << east;
+                if occupancy & (1 << east) != 0 {
+                    break;
+                }
+                east += 1;
+            }
+            let mut west = f;
+            while west > 0 {
+                west -= 1;
+                table[f][inner] |= 1 << west;
+                if occupancy & (1 << west) != 0 {
+                    break;
+                }
+            }
+            inner += 1;
+        }
+        f += 1;
+    }
+    table
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn king_attacks_are_the_neighbour_cells() {
+        for (ndx, king_attacks) in KING_ATTACKS.iter().enumerate() {
+            let c: Cell = num::FromPrimitive::from_usize(ndx).unwrap();
+            assert_eq!(*king_attacks, neighbour(c));
+        }
+    }
+
+    #[test]
+    fn knight_attacks_test() {
+        assert_eq!(
+            KNIGHT_ATTACKS[Cell::D4 as usize],
+            0x00_00_14_22_00_22_14_00_u64
+        );
+        assert_eq!(
+            KNIGHT_ATTACKS[Cell::H8 as usize],
+            0x00_20_40_00_00_00_00_00_u64
+        );
+        assert_eq!(
+            KNIGHT_ATTACKS[Cell::G1 as usize],
+            0x00_00_00_00_00_A0_10_00_u64
+        );
+    }
+
+    #[test]
+    fn pawn_attacks_test() {
+        assert_eq!(
+            pawn_attacks(Cell::A2, ArmyColour::White),
+            single_cell(Cell::B3)
+        );
+        assert_eq!(
+            pawn_attacks(Cell::H7, ArmyColour::Black),
+            single_cell(Cell::G6)
+        );
+        assert_eq!(pawn_attacks(Cell::E8, ArmyColour::White), EMPTY_STATE);
+        assert_eq!(pawn_attacks(Cell::E1, ArmyColour::Black), EMPTY_STATE);
+    }
+
+    #[test]
+    fn rays_are_consistent_with_the_board_masks() {
+        for c in (0..NUM_CELLS).map(|ndx| -> Cell { num::FromPrimitive::from_usize(ndx).unwrap() })
+        {
+            let ndx = c as usize;
+            assert_eq!(
+                RAYS[Direction::North as usize][ndx] | RAYS[Direction::South as usize][ndx],
+                file_mask(c) ^ single_cell(c)
+            );
+            assert_eq!(
+                RAYS[Direction::East as usize][ndx] | RAYS[Direction::West as usize][ndx],
+                rank_mask(c) ^ single_cell(c)
+            );
+            assert_eq!(
+                RAYS[Direction::NorthEast as usize][ndx] | RAYS[Direction::SouthWest as usize][ndx],
+                diag_mask(c) ^ single_cell(c)
+            );
+            assert_eq!(
+                RAYS[Direction::NorthWest as usize][ndx] | RAYS[Direction::SouthEast as usize][ndx],
+                antidiag_mask(c) ^ single_cell(c)
+            );
+        }
+    }
+
+    #[test]
+    fn between_test() {
+        assert_eq!(
+            BETWEEN[Cell::A1 as usize][Cell::H8 as usize],
+            0x00_40_20_10_08_04_02_00_u64
+        );
+        assert_eq!(
+            BETWEEN[Cell::H8 as usize][Cell::A1 as usize],
+            BETWEEN[Cell::A1 as usize][Cell::H8 as usize]
+        );
+        assert_eq!(
+            BETWEEN[Cell::E1 as usize][Cell::E4 as usize],
+            single_cell(Cell::E2) | single_cell(Cell::E3)
+        );
+        assert_eq!(BETWEEN[Cell::E1 as usize][Cell::E2 as usize], EMPTY_STATE);
+        assert_eq!(BETWEEN[Cell::E1 as usize][Cell::E1 as usize], EMPTY_STATE);
+        assert_eq!(BETWEEN[Cell::A1 as usize][Cell::B3 as usize], EMPTY_STATE);
+    }
+
+    #[test]
+    fn attacks_from_sliding_pieces() {
+        let occupancy = BitBoard::from_cells(&[Cell::C3, Cell::G7, Cell::E6]);
+        let bishop = attacks_from(ChessPiece::Bishop, Cell::E5, occupancy);
+        assert_eq!(bishop.pop_count(), 10);
+        assert!(bishop.cell_is_active(Cell::C3));
+        assert!(!bishop.cell_is_active(Cell::B2));
+        assert!(bishop.cell_is_active(Cell::G7));
+        assert!(!bishop.cell_is_active(Cell::H8));
+        let rook = attacks_from(ChessPiece::Rook, Cell::E5, occupancy);
+        assert_eq!(rook.pop_count(), 12);
+        assert_eq!(
+            attacks_from(ChessPiece::Queen, Cell::E5, occupancy),
+            bishop | rook
+        );
+        assert!(attacks_from(ChessPiece::Pawn, Cell::E5, occupancy).is_empty());
+        assert_eq!(
+            attacks_from(ChessPiece::King, Cell::E5, occupancy).state,
+            neighbour(Cell::E5)
+        );
+    }
+}
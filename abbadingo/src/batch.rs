@@ -0,0 +1,365 @@
+//! Batch analysis of files of chess positions.
+//!
+//! The [analyze_fens] function streams a text with one FEN string per line,
+//! applies a caller-provided function to each position (an evaluation, a perft
+//! count, the detection of a motif, ...) and writes the results, in the same
+//! order of the input, in CSV or JSON format. The positions can be processed by
+//! several threads in parallel.
+//!
+//! Empty lines and lines starting with `#` are skipped.
+//!
+//! The [analyse_positions] function applies a function to the [ChessBoard]s
+//! of a sequence of FEN strings in memory, in parallel, collecting the results.
+//!
+//! The module requires the `std` feature.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::batch::*;
+//! let input = "# Two positions\n\
+//!              rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+//!              4k3/8/8/8/8/8/8/4K2R w K - 0 1\n";
+//! let mut output = Vec::new();
+//! let n = analyze_fens(input.as_bytes(), &mut output, OutputFormat::Csv, 2, |fen| {
+//!     fen.split(' ').next().unwrap().len().to_string()
+//! })
+//! .unwrap();
+//! assert_eq!(n, 2);
+//! assert_eq!(
+//!     String::from_utf8(output).unwrap(),
+//!     "line,fen,result\n\
+//!      2,rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1,43\n\
+//!      3,4k3/8/8/8/8/8/8/4K2R w K - 0 1,20\n"
+//! );
+//! ```
+
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use std::thread;
+
+use crate::chessboard::ChessBoard;
+use crate::error::AbbaDingoError;
+use crate::fenrecord::FenRecord;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// Number of positions read from the input and processed (in parallel) before
+// writing their results.
+const BATCH_SIZE: usize = 1024;
+
+/// The format of the results written by [analyze_fens].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// CSV with header `line,fen,result`; fields are quoted when needed.
+    Csv,
+    /// A JSON array of objects with the `line`, `fen` and `result` members.
+    Json,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Applies `analyze` to each position of `input` (one FEN string per line) and
+/// writes the results to `output` in the given [OutputFormat].
+///
+/// Each result is reported with the (1-based) number of the input line and the
+/// FEN string. The positions are processed by `threads` threads (a single one
+/// if `threads` is 0 or 1); the results are written in the input order anyway.
+///
+/// Returns the number of positions processed or the first I/O error met.
+///
+pub fn analyze_fens<R, W, F>(
+    input: R,
+    mut output: W,
+    format: OutputFormat,
+    threads: usize,
+    analyze: F,
+) -> io::Result<usize>
+where
+    R: BufRead,
+    W: Write,
+    F: Fn(&str) -> String + Sync,
+{
+    let threads = threads.max(1);
+    let mut lines = input.lines().enumerate();
+    let mut count = 0;
+
+    match format {
+        OutputFormat::Csv => writeln!(output, "line,fen,result")?,
+        OutputFormat::Json => write!(output, "[")?,
+    }
+    loop {
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for (n, line) in lines.by_ref() {
+            let line = line?;
+            let fen = line.trim();
+            if !fen.is_empty() && !fen.starts_with('#') {
+                batch.push((n + 1, String::from(fen)));
+                if batch.len() == BATCH_SIZE {
+                    break;
+                }
+            }
+        }
+        if batch.is_empty() {
+            break;
+        }
+
+        let results = if threads == 1 {
+            batch.iter().map(|(_, fen)| analyze(fen)).collect()
+        } else {
+            analyze_in_parallel(&batch, threads, &analyze)
+        };
+
+        for ((n, fen), result) in batch.iter().zip(results) {
+            match format {
+                OutputFormat::Csv => {
+                    writeln!(output, "{},{},{}", n, csv_field(fen), csv_field(&result))?
+                }
+                OutputFormat::Json => write!(
+                    output,
+                    "{}\n  {{\"line\": {}, \"fen\": {}, \"result\": {}}}",
+                    if count == 0 { "" } else { "," },
+                    n,
+                    json_string(fen),
+                    json_string(&result)
+                )?,
+            }
+            count += 1;
+        }
+    }
+    if format == OutputFormat::Json {
+        writeln!(output, "{}]", if count == 0 { "" } else { "\n" })?;
+    }
+    output.flush()?;
+    Ok(count)
+}
+
+/// Applies `analyze` to the [ChessBoard] of each FEN string of `fens`,
+/// splitting the positions among `threads` threads (a single one if `threads`
+/// is 0 or 1), and returns the results in the order of the FEN strings.
+///
+/// The FEN strings that cannot be parsed are reported with the error of the
+/// FEN parsing, without stopping the analysis of the other positions.
+///
+/// # Example
+/// ```
+/// # use abbadingo::batch::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// # use abbadingo::perft::perft;
+/// let fens = [
+///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+///     "8/8/8",
+///     "4k3/8/8/8/8/8/8/4K2R w K - 0 1",
+/// ];
+/// let results = analyse_positions(fens.iter(), 2, |board| perft(board, 2));
+/// assert_eq!(
+///     results,
+///     vec![Ok(400), Err(AbbaDingoError::IllegalFenRecord), Ok(66)]
+/// );
+/// ```
+pub fn analyse_positions<I, S, F, T>(
+    fens: I,
+    threads: usize,
+    analyze: F,
+) -> Vec<Result<T, AbbaDingoError>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    F: Fn(&ChessBoard) -> T + Sync,
+    T: Send,
+{
+    let fens: Vec<String> = fens
+        .into_iter()
+        .map(|fen| String::from(fen.as_ref()))
+        .collect();
+    map_in_parallel(&fens, threads.max(1), |fen| {
+        FenRecord::try_from(fen.as_str()).map(|record| analyze(&record.board()))
+    })
+}
+
+/// Applies `analyze` to the positions of a batch splitting them among
+/// `threads` threads, returning the results in the batch order.
+///
+fn analyze_in_parallel<F>(batch: &[(usize, String)], threads: usize, analyze: &F) -> Vec<String>
+where
+    F: Fn(&str) -> String + Sync,
+{
+    map_in_parallel(batch, threads, |(_, fen)| analyze(fen))
+}
+
+/// Applies `f` to the items of a slice splitting them among `threads`
+/// threads, returning the results in the order of the items.
+///
+fn map_in_parallel<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| s.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("analysis thread panicked"))
+            .collect()
+    })
+}
+
+/// Quotes a CSV field, if it contains commas, quotes or line breaks.
+///
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// Converts a string to a JSON string literal.
+///
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const FENS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+                        \n\
+                        8/8/8/8/8/8/8/K6k b - - 10 60\n";
+
+    fn side_to_move(fen: &str) -> String {
+        String::from(fen.split(' ').nth(1).unwrap())
+    }
+
+    #[test]
+    fn json_output() {
+        let mut output = Vec::new();
+        let n = analyze_fens(
+            FENS.as_bytes(),
+            &mut output,
+            OutputFormat::Json,
+            1,
+            side_to_move,
+        )
+        .unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "[\n  {\"line\": 1, \"fen\": \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\", \
+             \"result\": \"w\"},\n  \
+             {\"line\": 3, \"fen\": \"8/8/8/8/8/8/8/K6k b - - 10 60\", \"result\": \"b\"}\n]\n"
+        );
+
+        let mut output = Vec::new();
+        assert_eq!(
+            analyze_fens(
+                "".as_bytes(),
+                &mut output,
+                OutputFormat::Json,
+                1,
+                side_to_move
+            )
+            .unwrap(),
+            0
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        let mut output = Vec::new();
+        analyze_fens(FENS.as_bytes(), &mut output, OutputFormat::Csv, 1, |_| {
+            String::from("a, \"quoted\" result")
+        })
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("60,\"a, \"\"quoted\"\" result\"\n"));
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+
+    #[test]
+    fn parallel_analysis_keeps_the_input_order() {
+        let input: String = (0..3000)
+            .map(|i| format!("8/8/8/8/8/8/8/K6k w - - 0 {}\n", i + 1))
+            .collect();
+        let fullmove = |fen: &str| String::from(fen.rsplit(' ').next().unwrap());
+        let mut sequential = Vec::new();
+        let mut parallel = Vec::new();
+        analyze_fens(
+            input.as_bytes(),
+            &mut sequential,
+            OutputFormat::Csv,
+            1,
+            fullmove,
+        )
+        .unwrap();
+        let n = analyze_fens(
+            input.as_bytes(),
+            &mut parallel,
+            OutputFormat::Csv,
+            7,
+            fullmove,
+        )
+        .unwrap();
+        assert_eq!(n, 3000);
+        assert_eq!(sequential, parallel);
+        assert!(String::from_utf8(parallel)
+            .unwrap()
+            .ends_with("3000,8/8/8/8/8/8/8/K6k w - - 0 3000,3000\n"));
+    }
+
+    #[test]
+    fn positions_are_analysed_in_order() {
+        let fens: Vec<String> = (0..100)
+            .map(|i| format!("8/8/8/8/8/8/8/K6k w - - 0 {}", i + 1))
+            .collect();
+        for threads in [0, 1, 8, 200] {
+            let results = analyse_positions(&fens, threads, |board| board.full_move_number);
+            assert_eq!(results.len(), 100);
+            assert!(results
+                .iter()
+                .enumerate()
+                .all(|(i, r)| *r == Ok(i as u32 + 1)));
+        }
+        assert!(analyse_positions(Vec::<String>::new(), 4, |_| 0).is_empty());
+        assert_eq!(
+            analyse_positions(["8/8/8/8/8/8/8/K7 w - - 0 1"], 4, |_| 0),
+            vec![Err(AbbaDingoError::IllegalFenKings)]
+        );
+    }
+}
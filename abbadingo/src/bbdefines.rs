@@ -3,8 +3,10 @@
 //! In this module there are definition for Cells, Files, Ranks and other
 //! concepts used in definition and manipulation of [BitBoard](crate::bitboard::BitBoard).)s.
 
-use std::convert::TryFrom;
-use std::fmt;
+use alloc::format;
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt;
 
 use crate::error::AbbaDingoError;
 
@@ -24,7 +26,9 @@ pub const EMPTY_STATE: BitBoardState = 0;
 /// Traditionally, in square board games the vertical files are represented
 /// from left to right using the letters from 'A' to 'H', so the "File A"
 /// is the leftmost column, whereas the "File H" is the rightmost one.
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, PartialEq, Eq)]
+///
+/// Files are ordered from left to right (File A < File B < ... < File H).
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum File {
     FileA, FileB, FileC, FileD, FileE, FileF, FileG, FileH,
 }
@@ -61,7 +65,9 @@ pub const FILES_BBS: [BitBoardState; 8] = [
 /// Traditionally, in square board games the horizontal files are represented
 /// from bottom to top using the numbers from '1' to '8', so the "Rank 1"
 /// is the bottom row, whereas the "Rank 8" is the top one.
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+///
+/// Ranks are ordered from bottom to top (Rank 1 < Rank 2 < ... < Rank 8).
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum Rank {
     Rank1, Rank2, Rank3, Rank4, Rank5, Rank6, Rank7, Rank8,
 }
@@ -97,8 +103,9 @@ pub const RANKS_BBS: [BitBoardState; 8] = [
 /// square board.
 ///
 /// The cells starts from A1 on bottom left (bit index 0) to H8 on top right (bit index 63).
+/// Cells are ordered using their bit index (A1 < B1 < ... < H1 < A2 < ... < H8).
 ///
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum Cell {
     A1, B1, C1, D1, E1, F1, G1, H1,
     A2, B2, C2, D2, E2, F2, G2, H2,
@@ -233,6 +240,58 @@ pub const ANTIDIAGS_BBS: [BitBoardState; 15] = [
     0x8000000000000000_u64, // 14
 ];
 
+/// The mask of the four center cells (D4, E4, D5 and E5)
+pub const CENTER_BBS: BitBoardState = 0x00_00_00_18_18_00_00_00;
+
+/// The eight compass directions inside an 8x8 board.
+///
+/// North is towards the Rank 8, east is towards the File H.
+///
+///```text
+///   NW   N   NE
+///     \  |  /
+///   W -- o -- E
+///     /  |  \
+///   SW   S   SE
+///```
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+/// The number of [Direction]s
+pub const NUM_DIRECTIONS: usize = 8;
+
+impl Direction {
+    /// Returns the (north, east) steps performed moving by one cell in the [Direction].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// assert_eq!(Direction::North.steps(), (1, 0));
+    /// assert_eq!(Direction::SouthWest.steps(), (-1, -1));
+    /// ```
+    pub const fn steps(self) -> (i32, i32) {
+        match self {
+            Direction::North => (1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (-1, 1),
+            Direction::South => (-1, 0),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (1, -1),
+        }
+    }
+}
+
 // ********************************************************************************
 // ********************************************************************************
 // METHODS
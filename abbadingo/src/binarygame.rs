@@ -0,0 +1,526 @@
+//! Compact binary serialization of chess games.
+//!
+//! A [GameRecord] (initial position, list of moves and result) is encoded as:
+//!
+//!  - the magic bytes `ADG` followed by the format version (currently [BINARY_GAME_VERSION]);
+//!  - a header byte: bits `0..1` are the result (0 = `*`, 1 = `1-0`, 2 = `0-1`,
+//!    3 = `1/2-1/2`), bit `7` is set if the game starts from a position other than
+//!    the initial standard position;
+//!  - only if bit `7` of the header is set, the FEN string of the initial position,
+//!    as a variable length (LEB128) byte count followed by the ASCII characters;
+//!  - the number of moves, as a variable length (LEB128) integer;
+//!  - the moves, each one as the 32-bits [ChessMove] representation in little endian
+//!    order.
+//!
+//! The moves are stored with their full 32-bits representation (moved and taken
+//! pieces included) so that the game can be decoded without replaying it on a board.
+//! Several records can be stored one after the other in the same buffer, see
+//! [GameRecord::decode_all].
+//!
+//! # Legal move index compression
+//!
+//! When the position of the moves is known, [compress_moves] stores each move
+//! as its index in the [legal_moves()](ChessBoard::legal_moves) of the
+//! position, with the minimum number of bits needed to represent all the
+//! indexes (no bits at all for forced moves): a whole game usually takes
+//! less than a byte per move. [decompress_moves] replays the moves with the
+//! move generator, so a successful round trip also checks that the legal
+//! moves are generated in a deterministic order.
+//!
+//! The compressed data is the number of moves, as a variable length (LEB128)
+//! integer, followed by the indexes, packed starting from the least
+//! significant bit of each byte; the last byte is padded with zero bits.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::chessmove::*;
+//! # use abbadingo::binarygame::*;
+//! # use abbadingo::fenrecord::INITIAL_STANDARD_POSITION;
+//! let game = GameRecord {
+//!     initial_fen: String::from(INITIAL_STANDARD_POSITION),
+//!     moves: vec![
+//!         ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None),
+//!         ChessMove::new(ChessPiece::Pawn, Cell::E7, Cell::E5, None, None),
+//!     ],
+//!     result: GameResult::Draw,
+//! };
+//! let bytes = game.encode();
+//! assert_eq!(bytes.len(), 14);
+//! assert_eq!(GameRecord::decode(&bytes), Ok(game));
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::chessboard::ChessBoard;
+use crate::chessdefines::{ChessPiece, GameResult};
+use crate::chessmove::ChessMove;
+use crate::error::AbbaDingoError;
+use crate::fenrecord::INITIAL_STANDARD_POSITION;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// The current version of the binary format.
+pub const BINARY_GAME_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 3] = b"ADG";
+const RESULTS: [GameResult; 4] = [
+    GameResult::Ongoing,
+    GameResult::WhiteWins,
+    GameResult::BlackWins,
+    GameResult::Draw,
+];
+const RESULT_MASK: u8 = 0x03;
+const CUSTOM_POSITION_FLAG: u8 = 0x80;
+
+/// A chess game to be stored in the compact binary format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    /// The initial position of the game, as a FEN string.
+    pub initial_fen: String,
+    /// The moves of the game.
+    pub moves: Vec<ChessMove>,
+    /// The result of the game.
+    pub result: GameResult,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl GameRecord {
+    /// Encodes the game in the compact binary format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.encode_into(&mut bytes);
+        bytes
+    }
+
+    /// Encodes the game in the compact binary format, appending it to `bytes`.
+    pub fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(BINARY_GAME_VERSION);
+        let mut header = RESULTS.iter().position(|r| *r == self.result).unwrap() as u8;
+        let custom_position = self.initial_fen != INITIAL_STANDARD_POSITION;
+        if custom_position {
+            header |= CUSTOM_POSITION_FLAG;
+        }
+        bytes.push(header);
+        if custom_position {
+            push_varint(bytes, self.initial_fen.len());
+            bytes.extend_from_slice(self.initial_fen.as_bytes());
+        }
+        push_varint(bytes, self.moves.len());
+        for m in &self.moves {
+            bytes.extend_from_slice(&m.m.to_le_bytes());
+        }
+    }
+
+    /// Decodes a game encoded in the compact binary format.
+    ///
+    /// Err(AbbaDingoError::IllegalBinaryGame) is returned if the data is
+    /// truncated or corrupted, if it has been written by an unsupported version
+    /// of the format or if there are trailing bytes after the game.
+    ///
+    pub fn decode(bytes: &[u8]) -> Result<GameRecord, AbbaDingoError> {
+        let (game, len) = GameRecord::decode_prefix(bytes)?;
+        if len != bytes.len() {
+            return Err(AbbaDingoError::IllegalBinaryGame);
+        }
+        Ok(game)
+    }
+
+    /// Decodes a sequence of games encoded one after the other in the compact
+    /// binary format.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::binarygame::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut bytes = Vec::new();
+    /// for result in [GameResult::WhiteWins, GameResult::BlackWins, GameResult::Ongoing] {
+    ///     GameRecord {
+    ///         initial_fen: String::from("4k3/8/8/8/8/8/8/4K2R w K - 0 1"),
+    ///         moves: vec![],
+    ///         result,
+    ///     }
+    ///     .encode_into(&mut bytes);
+    /// }
+    /// let games = GameRecord::decode_all(&bytes).unwrap();
+    /// assert_eq!(games.len(), 3);
+    /// assert_eq!(games[1].result, GameResult::BlackWins);
+    /// ```
+    pub fn decode_all(mut bytes: &[u8]) -> Result<Vec<GameRecord>, AbbaDingoError> {
+        let mut games = Vec::new();
+        while !bytes.is_empty() {
+            let (game, len) = GameRecord::decode_prefix(bytes)?;
+            games.push(game);
+            bytes = &bytes[len..];
+        }
+        Ok(games)
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Decodes the game at the beginning of `bytes`, returning it with the
+    /// number of bytes used.
+    ///
+    fn decode_prefix(bytes: &[u8]) -> Result<(GameRecord, usize), AbbaDingoError> {
+        let err = AbbaDingoError::IllegalBinaryGame;
+        if bytes.len() < 5 || &bytes[0..3] != MAGIC || bytes[3] != BINARY_GAME_VERSION {
+            return Err(err);
+        }
+        let header = bytes[4];
+        if header & !(RESULT_MASK | CUSTOM_POSITION_FLAG) != 0 {
+            return Err(err);
+        }
+        let mut pos = 5;
+
+        let initial_fen = if header & CUSTOM_POSITION_FLAG != 0 {
+            let len = read_varint(bytes, &mut pos)?;
+            let end = pos
+                .checked_add(len)
+                .ok_or(AbbaDingoError::IllegalBinaryGame)?;
+            let fen = bytes
+                .get(pos..end)
+                .ok_or(AbbaDingoError::IllegalBinaryGame)?;
+            pos = end;
+            String::from(core::str::from_utf8(fen).map_err(|_| AbbaDingoError::IllegalBinaryGame)?)
+        } else {
+            String::from(INITIAL_STANDARD_POSITION)
+        };
+
+        let num_moves = read_varint(bytes, &mut pos)?;
+        if num_moves > (bytes.len() - pos) / 4 {
+            return Err(err);
+        }
+        let mut moves = Vec::with_capacity(num_moves);
+        for _ in 0..num_moves {
+            let mut m = [0; 4];
+            m.copy_from_slice(&bytes[pos..pos + 4]);
+            pos += 4;
+            let m = u32::from_le_bytes(m);
+            // The moved piece is always present in a valid move
+            if m & 0x07 > ChessPiece::Pawn as u32 {
+                return Err(err);
+            }
+            moves.push(ChessMove { m });
+        }
+
+        Ok((
+            GameRecord {
+                initial_fen,
+                moves,
+                result: RESULTS[(header & RESULT_MASK) as usize],
+            },
+            pos,
+        ))
+    }
+}
+
+/// Compresses a sequence of moves played from the given position, storing
+/// each move as its index in the legal moves of its position (see the
+/// [module documentation](self)).
+///
+/// Err(AbbaDingoError::IllegalBinaryGame) is returned if a move is not legal
+/// in its position, since it cannot be represented.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::binarygame::*;
+/// # use abbadingo::chessboard::ChessBoard;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::chessmove::*;
+/// let board = ChessBoard::initial();
+/// let moves = [
+///     ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None),
+///     ChessMove::new(ChessPiece::Pawn, Cell::E7, Cell::E5, None, None),
+///     ChessMove::new(ChessPiece::Knight, Cell::G1, Cell::F3, None, None),
+/// ];
+/// let bytes = compress_moves(&board, &moves).unwrap();
+/// // The moves count and 3 indexes of 5 bits each
+/// assert_eq!(bytes.len(), 1 + 2);
+/// assert_eq!(decompress_moves(&board, &bytes), Ok(moves.to_vec()));
+/// ```
+pub fn compress_moves(board: &ChessBoard, moves: &[ChessMove]) -> Result<Vec<u8>, AbbaDingoError> {
+    let mut bytes = Vec::new();
+    push_varint(&mut bytes, moves.len());
+    let mut writer = BitWriter { bytes, used: 0 };
+    let mut board = *board;
+    for m in moves {
+        let legal = board.legal_moves();
+        let index = legal
+            .iter()
+            .position(|l| l == m)
+            .ok_or(AbbaDingoError::IllegalBinaryGame)?;
+        writer.write(index, index_bits(legal.len()));
+        board.make_move(*m);
+    }
+    Ok(writer.bytes)
+}
+
+/// Decompresses a sequence of moves compressed by [compress_moves] from the
+/// same position, replaying them on the board.
+///
+/// Err(AbbaDingoError::IllegalBinaryGame) is returned if the data is
+/// truncated or corrupted (an index out of the legal moves, a move after the
+/// end of the game, trailing bytes or a padding with bits set).
+///
+pub fn decompress_moves(
+    board: &ChessBoard,
+    bytes: &[u8],
+) -> Result<Vec<ChessMove>, AbbaDingoError> {
+    let mut pos = 0;
+    let num_moves = read_varint(bytes, &mut pos)?;
+    // Each move takes at least one bit unless it is forced, so the count
+    // cannot be used to preallocate the moves safely
+    let mut moves = Vec::new();
+    let mut reader = BitReader {
+        bytes: &bytes[pos..],
+        pos: 0,
+    };
+    let mut board = *board;
+    for _ in 0..num_moves {
+        let legal = board.legal_moves();
+        let index = reader
+            .read(index_bits(legal.len()))
+            .ok_or(AbbaDingoError::IllegalBinaryGame)?;
+        let m = *legal.get(index).ok_or(AbbaDingoError::IllegalBinaryGame)?;
+        moves.push(m);
+        board.make_move(m);
+    }
+    if !reader.is_at_padding() {
+        return Err(AbbaDingoError::IllegalBinaryGame);
+    }
+    Ok(moves)
+}
+
+/// Returns the number of bits needed to store an index into a list of `len`
+/// elements.
+///
+fn index_bits(len: usize) -> u32 {
+    if len <= 1 {
+        0
+    } else {
+        usize::BITS - (len - 1).leading_zeros()
+    }
+}
+
+// Appends bit fields to a byte buffer, starting from the least significant bit
+// of each byte.
+struct BitWriter {
+    bytes: Vec<u8>,
+    // The number of bits used in the last byte, 0 if it is full (or missing)
+    used: u32,
+}
+
+impl BitWriter {
+    /// Appends the `bits` least significant bits of `value`.
+    ///
+    fn write(&mut self, value: usize, bits: u32) {
+        for b in 0..bits {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            // We can unwrap safely here... a byte has just been pushed if needed
+            *self.bytes.last_mut().unwrap() |= (((value >> b) & 1) as u8) << self.used;
+            self.used = (self.used + 1) % 8;
+        }
+    }
+}
+
+// Reads the bit fields written by a BitWriter.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    // The number of bits already read
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    /// Reads a field of `bits` bits, `None` if the data is over.
+    ///
+    fn read(&mut self, bits: u32) -> Option<usize> {
+        let mut value = 0;
+        for b in 0..bits {
+            let byte = self.bytes.get(self.pos / 8)?;
+            value |= (((byte >> (self.pos % 8)) & 1) as usize) << b;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    /// Returns true if only the zero bits padding the last byte are left.
+    ///
+    fn is_at_padding(&self) -> bool {
+        let len = self.pos.div_ceil(8);
+        len == self.bytes.len()
+            && (self.pos.is_multiple_of(8) || self.bytes[len - 1] >> (self.pos % 8) == 0)
+    }
+}
+
+/// Appends an unsigned integer to `bytes` in the LEB128 variable length format.
+///
+fn push_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads an unsigned integer in the LEB128 variable length format from `bytes`,
+/// starting at `pos` (that is moved after the integer).
+///
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, AbbaDingoError> {
+    let mut value: usize = 0;
+    let mut shift = 0;
+    loop {
+        let b = *bytes.get(*pos).ok_or(AbbaDingoError::IllegalBinaryGame)?;
+        *pos += 1;
+        if shift >= usize::BITS {
+            return Err(AbbaDingoError::IllegalBinaryGame);
+        }
+        value |= ((b & 0x7F) as usize) << shift;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bbdefines::Cell;
+    use crate::fenrecord::FenRecord;
+    use alloc::vec;
+    use core::convert::TryFrom;
+
+    fn sample_game() -> GameRecord {
+        GameRecord {
+            initial_fen: String::from("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1"),
+            moves: vec![
+                ChessMove::new(
+                    ChessPiece::Pawn,
+                    Cell::B7,
+                    Cell::B8,
+                    None,
+                    Some(ChessPiece::Queen),
+                ),
+                ChessMove::new(ChessPiece::King, Cell::E8, Cell::E7, None, None),
+            ],
+            result: GameResult::WhiteWins,
+        }
+    }
+
+    #[test]
+    fn encode_and_decode_a_game_from_a_custom_position() {
+        let game = sample_game();
+        let bytes = game.encode();
+        assert_eq!(&bytes[0..4], b"ADG\x01");
+        assert_eq!(bytes[4], 0x81);
+        assert_eq!(bytes[5] as usize, game.initial_fen.len());
+        assert_eq!(bytes.len(), 5 + 1 + game.initial_fen.len() + 1 + 2 * 4);
+        assert_eq!(GameRecord::decode(&bytes), Ok(game));
+    }
+
+    #[test]
+    fn compressed_moves_round_trip() {
+        // A deterministic walk through the moves of a few positions
+        for fen in [
+            INITIAL_STANDARD_POSITION,
+            crate::testpositions::KIWIPETE.fen,
+            "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let initial = FenRecord::try_from(fen).unwrap().board();
+            let mut board = initial;
+            let mut moves = Vec::new();
+            for i in 0..120 {
+                let legal = board.legal_moves();
+                if legal.is_empty() {
+                    break;
+                }
+                let m = legal[(i * 7 + 3) % legal.len()];
+                moves.push(m);
+                board.make_move(m);
+            }
+            let bytes = compress_moves(&initial, &moves).unwrap();
+            assert!(bytes.len() < 2 + moves.len());
+            assert_eq!(decompress_moves(&initial, &bytes), Ok(moves));
+        }
+        let board = ChessBoard::initial();
+        assert_eq!(compress_moves(&board, &[]), Ok(vec![0]));
+        assert_eq!(decompress_moves(&board, &[0]), Ok(vec![]));
+    }
+
+    #[test]
+    fn corrupted_compressed_moves_are_rejected() {
+        let board = ChessBoard::initial();
+        let err = Some(AbbaDingoError::IllegalBinaryGame);
+        let illegal = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E5, None, None);
+        assert_eq!(compress_moves(&board, &[illegal]).err(), err);
+        // 20 legal moves, indexes of 5 bits: 31 is out of range
+        assert_eq!(decompress_moves(&board, &[1, 0x1F]).err(), err);
+        // Truncated data, padding bits set and trailing bytes
+        assert_eq!(decompress_moves(&board, &[2, 0x00]).err(), err);
+        assert_eq!(decompress_moves(&board, &[1, 0x20]).err(), err);
+        assert_eq!(decompress_moves(&board, &[1, 0x00, 0x00]).err(), err);
+        assert_eq!(decompress_moves(&board, &[]).err(), err);
+        // No moves after the end of the game
+        let mate = FenRecord::try_from(crate::testpositions::CHECKMATE.fen)
+            .unwrap()
+            .board();
+        assert_eq!(decompress_moves(&mate, &[1]).err(), err);
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        for v in [0, 1, 127, 128, 300, 16384, usize::MAX] {
+            let mut bytes = Vec::new();
+            push_varint(&mut bytes, v);
+            let mut pos = 0;
+            assert_eq!(read_varint(&bytes, &mut pos), Ok(v));
+            assert_eq!(pos, bytes.len());
+        }
+    }
+
+    #[test]
+    fn corrupted_data_is_rejected() {
+        let bytes = sample_game().encode();
+        let err = Err(AbbaDingoError::IllegalBinaryGame);
+        // Truncated data
+        for len in 0..bytes.len() {
+            assert_eq!(GameRecord::decode(&bytes[..len]), err);
+        }
+        // Trailing bytes
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(GameRecord::decode(&longer), err);
+        // Unsupported version
+        let mut wrong = bytes.clone();
+        wrong[3] = 2;
+        assert_eq!(GameRecord::decode(&wrong), err);
+        // Invalid moved piece
+        let mut wrong = bytes.clone();
+        let last_move = wrong.len() - 4;
+        wrong[last_move] |= 0x07;
+        assert_eq!(GameRecord::decode(&wrong), err);
+        // Huge moves count
+        assert_eq!(GameRecord::decode(b"ADG\x01\x00\xFF\xFF\xFF\xFF\x0F"), err);
+    }
+}
@@ -1,6 +1,6 @@
 //! Definition of the [BitBoard] structure and related methods implementation.
 
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
 
 // -----------------------------------------------------------------------------------
 // ansi-term on crates.io
@@ -9,10 +9,12 @@ use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
 // Rustdoc: https://docs.rs/ansi_term
 // Unicode box-drawing characters: https://en.wikipedia.org/wiki/Box-drawing_character
 // Chess symbols on unicode: https://en.wikipedia.org/wiki/Chess_symbols_in_Unicode
+#[cfg(feature = "std")]
 use ansi_term::Colour::{Black, Fixed};
 // -----------------------------------------------------------------------------------
 
 use crate::num::FromPrimitive;
+#[cfg(feature = "std")]
 use std::fmt;
 
 use crate::bbdefines::*;
@@ -194,6 +196,60 @@ impl BitBoard {
         *self &= BitBoard::from(!(FILES_BBS[f as usize]));
     }
 
+    /// Returns the occupancy of a [Rank] as an 8-bit value, with the bit 0
+    /// for the [File] A and the bit 7 for the [File] H.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::A3, Cell::C3, Cell::H3, Cell::C4]);
+    /// assert_eq!(bb.rank_bits(Rank::Rank3), 0b1000_0101);
+    /// assert_eq!(bb.rank_bits(Rank::Rank5), 0);
+    /// ```
+    pub fn rank_bits(&self, r: Rank) -> u8 {
+        (self.state >> (r as usize * NUM_FILES)) as u8
+    }
+
+    /// Sets the occupancy of a [Rank] from an 8-bit value (see
+    /// [rank_bits()](BitBoard::rank_bits)); the other ranks are not changed.
+    ///
+    pub fn set_rank_bits(&mut self, r: Rank, bits: u8) {
+        self.reset_rank(r);
+        self.state |= (bits as BitBoardState) << (r as usize * NUM_FILES);
+    }
+
+    /// Returns the occupancy of a [File] as an 8-bit value, with the bit 0
+    /// for the [Rank] 1 and the bit 7 for the [Rank] 8.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let mut bb = BitBoard::from_cells(&[Cell::E1, Cell::E2, Cell::E8, Cell::F5]);
+    /// assert_eq!(bb.file_bits(File::FileE), 0b1000_0011);
+    /// bb.set_file_bits(File::FileF, 0b0000_0110);
+    /// assert_eq!(bb, BitBoard::from_cells(&[Cell::E1, Cell::E2, Cell::E8, Cell::F2, Cell::F3]));
+    /// ```
+    pub fn file_bits(&self, f: File) -> u8 {
+        let file_a = (self.state >> f as usize) & FILES_BBS[File::FileA as usize];
+        let mut bits = 0;
+        for r in 0..NUM_RANKS {
+            bits |= ((file_a >> (r * NUM_FILES)) as u8 & 1) << r;
+        }
+        bits
+    }
+
+    /// Sets the occupancy of a [File] from an 8-bit value (see
+    /// [file_bits()](BitBoard::file_bits)); the other files are not changed.
+    ///
+    pub fn set_file_bits(&mut self, f: File, bits: u8) {
+        self.reset_file(f);
+        for r in 0..NUM_RANKS {
+            self.state |= (((bits >> r) & 1) as BitBoardState) << (r * NUM_FILES + f as usize);
+        }
+    }
+
     /// Sets all the cells of a [Diagonal] to busy state.
     ///
     pub fn set_diagonal(&mut self, d: Diagonal) {
@@ -328,6 +384,59 @@ impl BitBoard {
         cnt
     }
 
+    /// Returns the number of active cells among the four center cells
+    /// (D4, E4, D5 and E5, see [CENTER_BBS]).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::C4, Cell::D4, Cell::E5, Cell::F6]);
+    /// assert_eq!(bb.center_control_count(), 2);
+    /// ```
+    pub fn center_control_count(&self) -> usize {
+        (self.state & CENTER_BBS).count_ones() as usize
+    }
+
+    /// Returns the number of active cells of a [Rank].
+    ///
+    pub fn cells_on_rank(&self, r: Rank) -> usize {
+        self.rank_bits(r).count_ones() as usize
+    }
+
+    /// Returns the number of active cells of a [File].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::A1, Cell::A7, Cell::B7, Cell::H7]);
+    /// assert_eq!(bb.cells_on_file(File::FileA), 2);
+    /// assert_eq!(bb.cells_on_rank(Rank::Rank7), 3);
+    /// ```
+    pub fn cells_on_file(&self, f: File) -> usize {
+        (self.state & FILES_BBS[f as usize]).count_ones() as usize
+    }
+
+    /// Returns the number of active cells of each [Rank], indexed by the
+    /// [Rank] (the Rank 1 first).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let mut bb = BitBoard::from_cells(&[Cell::C3, Cell::F3, Cell::A8]);
+    /// bb.set_rank(Rank::Rank2);
+    /// assert_eq!(bb.density_by_rank(), [0, 8, 2, 0, 0, 0, 0, 1]);
+    /// ```
+    pub fn density_by_rank(&self) -> [u8; NUM_RANKS] {
+        let mut density = [0; NUM_RANKS];
+        for (r, d) in density.iter_mut().enumerate() {
+            *d = ((self.state >> (r * NUM_FILES)) as u8).count_ones() as u8;
+        }
+        density
+    }
+
     /// Clear the [BitBoard].
     ///
     /// After the call to this method all the Cell are set to free status.
@@ -386,6 +495,279 @@ impl BitBoard {
             }
         }
     }
+
+    /// Shifts all the active cells of the [BitBoard] by `n` steps in the given
+    /// [Direction], discarding the cells going outside the board (the cells
+    /// never "wrap around" from a side of the board to the other one).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::B2, Cell::G3, Cell::E7]);
+    /// // Shift by 2 steps North-East: G3 and E7 go outside the board
+    /// //    _________________________         _________________________
+    /// // r8|  .  .  .  .  .  .  .  . |     r8|  .  .  .  .  .  .  .  . |
+    /// // r7|  .  .  .  .  o  .  .  . |     r7|  .  .  .  .  .  .  .  . |
+    /// // r6|  .  .  .  .  .  .  .  . |     r6|  .  .  .  .  .  .  .  . |
+    /// // r5|  .  .  .  .  .  .  .  . |     r5|  .  .  .  .  .  .  .  . |
+    /// // r4|  .  .  .  .  .  .  .  . | ==> r4|  .  .  .  o  .  .  .  . |
+    /// // r3|  .  .  .  .  .  .  o  . |     r3|  .  .  .  .  .  .  .  . |
+    /// // r2|  .  o  .  .  .  .  .  . |     r2|  .  .  .  .  .  .  .  . |
+    /// // r1|  .  .  .  .  .  .  .  . |     r1|  .  .  .  .  .  .  .  . |
+    /// //     -------------------------         -------------------------
+    /// //     fa fb fc fd fe ff fg fh           fa fb fc fd fe ff fg fh
+    /// assert_eq!(
+    ///     bb.shift_by(Direction::NorthEast, 2),
+    ///     BitBoard::from_cells(&[Cell::D4])
+    /// );
+    /// assert_eq!(
+    ///     bb.shift_by(Direction::South, 3),
+    ///     BitBoard::from_cells(&[Cell::E4])
+    /// );
+    /// ```
+    pub fn shift_by(&self, dir: Direction, n: u8) -> BitBoard {
+        let (step_north, step_east) = dir.steps();
+        let (dn, de) = (step_north * n as i32, step_east * n as i32);
+        if dn.abs() >= NUM_RANKS as i32 || de.abs() >= NUM_FILES as i32 {
+            return BitBoard::new();
+        }
+        // Remove the cells in the files that would go outside the board
+        let mut mask = EMPTY_STATE;
+        for (f, file_bb) in FILES_BBS.iter().enumerate() {
+            let dest_file = f as i32 + de;
+            if (0..NUM_FILES as i32).contains(&dest_file) {
+                mask |= file_bb;
+            }
+        }
+        let state = self.state & mask;
+        let shift = dn * NUM_FILES as i32 + de;
+        BitBoard {
+            state: if shift >= 0 {
+                state << shift
+            } else {
+                state >> -shift
+            },
+        }
+    }
+
+    /// Returns the north fill of the [BitBoard]: each active cell is propagated
+    /// to all the cells north of it, up to the 8th [Rank].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::B6, Cell::G8]);
+    /// assert_eq!(
+    ///     bb.north_fill(),
+    ///     BitBoard::from_cells(&[Cell::B6, Cell::B7, Cell::B8, Cell::G8])
+    /// );
+    /// ```
+    pub fn north_fill(&self) -> BitBoard {
+        let mut s = self.state;
+        s |= s << 8;
+        s |= s << 16;
+        s |= s << 32;
+        BitBoard { state: s }
+    }
+
+    /// Returns the south fill of the [BitBoard]: each active cell is propagated
+    /// to all the cells south of it, down to the 1st [Rank].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::C3]);
+    /// assert_eq!(
+    ///     bb.south_fill(),
+    ///     BitBoard::from_cells(&[Cell::C1, Cell::C2, Cell::C3])
+    /// );
+    /// ```
+    pub fn south_fill(&self) -> BitBoard {
+        let mut s = self.state;
+        s |= s >> 8;
+        s |= s >> 16;
+        s |= s >> 32;
+        BitBoard { state: s }
+    }
+
+    /// Returns the file fill of the [BitBoard]: all the cells of the files with
+    /// at least an active cell are set.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bb = BitBoard::from_cells(&[Cell::A2, Cell::E5, Cell::E6]);
+    /// let mut files = BitBoard::new();
+    /// files.set_file(File::FileA);
+    /// files.set_file(File::FileE);
+    /// assert_eq!(bb.file_fill(), files);
+    /// ```
+    pub fn file_fill(&self) -> BitBoard {
+        self.north_fill() | self.south_fill()
+    }
+
+    /// Computes the intersection of each [BitBoard] of an array with the same mask.
+    ///
+    /// This is the typical operation needed to filter all the piece sets of an
+    /// army against a single occupancy (e.g. to find which pieces are in a given
+    /// region). The loop operates on a fixed size array without branches, so it
+    /// is vectorized by the compiler on targets supporting SIMD instructions.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bbs = [
+    ///     BitBoard::from_cells(&[Cell::A1, Cell::H8]),
+    ///     BitBoard::from_cells(&[Cell::A2, Cell::C2]),
+    ///     BitBoard::from_cells(&[Cell::D4]),
+    /// ];
+    /// let mut mask = BitBoard::new();
+    /// mask.set_file(File::FileA);
+    /// assert_eq!(
+    ///     BitBoard::batch_and(&bbs, mask),
+    ///     [
+    ///         BitBoard::from_cells(&[Cell::A1]),
+    ///         BitBoard::from_cells(&[Cell::A2]),
+    ///         BitBoard::new(),
+    ///     ]
+    /// );
+    /// ```
+    pub fn batch_and<const N: usize>(bbs: &[BitBoard; N], mask: BitBoard) -> [BitBoard; N] {
+        let mut res = *bbs;
+        for bb in res.iter_mut() {
+            bb.state &= mask.state;
+        }
+        res
+    }
+
+    /// Evaluate the number of active cells of each [BitBoard] of an array.
+    ///
+    /// As for [batch_and](BitBoard::batch_and), the computation is done without branches
+    /// on a fixed size array, so it can exploit the vector and population count
+    /// instructions of the target CPU.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::*;
+    /// # use abbadingo::bbdefines::*;
+    /// let bbs = [
+    ///     BitBoard::from_cells(&[Cell::A1, Cell::H8]),
+    ///     BitBoard::new(),
+    ///     BitBoard::from(0xFF_00_00_00_00_00_00_00),
+    /// ];
+    /// assert_eq!(BitBoard::batch_pop_count(&bbs), [2, 0, 8]);
+    /// ```
+    pub fn batch_pop_count<const N: usize>(bbs: &[BitBoard; N]) -> [usize; N] {
+        let mut res = [0; N];
+        for (cnt, bb) in res.iter_mut().zip(bbs.iter()) {
+            *cnt = bb.state.count_ones() as usize;
+        }
+        res
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Pawn structure helpers
+
+/// Returns the mask of the open files, i.e. the files without pawns of any colour.
+///
+/// # Arguments
+///
+/// * `white_pawns` - The [BitBoard] with the white pawns
+/// * `black_pawns` - The [BitBoard] with the black pawns
+///
+/// # Example
+/// ```
+/// # use abbadingo::bitboard::*;
+/// # use abbadingo::bbdefines::*;
+/// let white_pawns = BitBoard::from_cells(&[Cell::A2, Cell::B2, Cell::C2, Cell::F2, Cell::G2, Cell::H2]);
+/// let black_pawns = BitBoard::from_cells(&[Cell::A7, Cell::B7, Cell::E5, Cell::F7, Cell::G7, Cell::H7]);
+/// let mut expected = BitBoard::new();
+/// expected.set_file(File::FileD);
+/// assert_eq!(open_files(white_pawns, black_pawns), expected);
+/// ```
+pub fn open_files(white_pawns: BitBoard, black_pawns: BitBoard) -> BitBoard {
+    BitBoard::from(!(white_pawns | black_pawns).file_fill().state)
+}
+
+/// Returns the mask of the semi-open files for an army, i.e. the files without
+/// pawns of the army (the files can contain pawns of the opposite army).
+///
+/// # Arguments
+///
+/// * `own_pawns` - The [BitBoard] with the pawns of the army
+///
+/// # Example
+/// ```
+/// # use abbadingo::bitboard::*;
+/// # use abbadingo::bbdefines::*;
+/// let white_pawns = BitBoard::from_cells(&[Cell::A2, Cell::B2, Cell::C2, Cell::F2, Cell::G2, Cell::H2]);
+/// let mut expected = BitBoard::new();
+/// expected.set_file(File::FileD);
+/// expected.set_file(File::FileE);
+/// assert_eq!(semi_open_files(white_pawns), expected);
+/// ```
+pub fn semi_open_files(own_pawns: BitBoard) -> BitBoard {
+    BitBoard::from(!own_pawns.file_fill().state)
+}
+
+// ----------------------------------------------------------------------------
+// Cell-set macro
+
+/// Builds a [BitBoard] from a comma separated list of [Cell]s (e.g. `E4`),
+/// ranks (e.g. `rank 2`) and files (e.g. `file G`).
+///
+/// The macro expands to a constant expression, so it can be used to define
+/// `const` masks as well as readable test fixtures.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bitboard;
+/// # use abbadingo::bitboard::*;
+/// # use abbadingo::bbdefines::*;
+/// const CENTER: BitBoard = bitboard![D4, E4, D5, E5];
+/// assert_eq!(CENTER, BitBoard::from(0x00_00_00_18_18_00_00_00));
+///
+/// let bb = bitboard![E4, D5, rank 2, file G];
+/// let mut expected = BitBoard::from_cells(&[Cell::E4, Cell::D5]);
+/// expected.set_rank(Rank::Rank2);
+/// expected.set_file(File::FileG);
+/// assert_eq!(bb, expected);
+/// assert!(bitboard![].is_empty());
+/// ```
+#[macro_export]
+macro_rules! bitboard {
+    (@state) => {
+        $crate::bbdefines::EMPTY_STATE
+    };
+    (@state rank $r:literal $(, $($rest:tt)*)?) => {
+        $crate::bbdefines::RANKS_BBS[$r - 1] | $crate::bitboard!(@state $($($rest)*)?)
+    };
+    (@state file $f:ident $(, $($rest:tt)*)?) => {
+        $crate::bbdefines::FILES_BBS[$crate::bitboard!(@file $f)]
+            | $crate::bitboard!(@state $($($rest)*)?)
+    };
+    (@state $c:ident $(, $($rest:tt)*)?) => {
+        (1 << ($crate::bbdefines::Cell::$c as u32)) | $crate::bitboard!(@state $($($rest)*)?)
+    };
+    (@file A) => { 0 };
+    (@file B) => { 1 };
+    (@file C) => { 2 };
+    (@file D) => { 3 };
+    (@file E) => { 4 };
+    (@file F) => { 5 };
+    (@file G) => { 6 };
+    (@file H) => { 7 };
+    ($($item:tt)*) => {
+        $crate::bitboard::BitBoard {
+            state: $crate::bitboard!(@state $($item)*),
+        }
+    };
 }
 
 // ----------------------------------------------------------------------------
@@ -420,8 +802,9 @@ impl From<BitBoardState> for BitBoard {
 
 /// Display trait for [BitBoard] structure.
 ///
-/// Represent a bitboard in "ascii" form.
+/// Represent a bitboard in "ascii" form. Available only with the `std` feature.
 ///
+#[cfg(feature = "std")]
 impl fmt::Display for BitBoard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let bg_style = Black.on(Fixed(252));
@@ -430,18 +813,18 @@ impl fmt::Display for BitBoard {
             "{}",
             bg_style.paint("                                       ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("     a   b   c   d   e   f   g   h     ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("   ╭───┬───┬───┬───┬───┬───┬───┬───╮   ")
         ));
         for r in (0..8).rev() {
-            bb_str.push_str(&"\n".to_string());
+            bb_str.push('\n');
             bb_str.push_str(&format!("{}", bg_style.paint(" ")));
             bb_str.push_str(&format!("{}", bg_style.paint((r + 1).to_string())));
             bb_str.push_str(&format!("{}", bg_style.paint(" │ ")));
@@ -461,29 +844,29 @@ impl fmt::Display for BitBoard {
             bb_str.push_str(&format!("{}", bg_style.paint((r + 1).to_string())));
             bb_str.push_str(&format!("{}", bg_style.paint(" ")));
             if r > 0 {
-                bb_str.push_str(&"\n".to_string());
+                bb_str.push('\n');
                 bb_str.push_str(&format!(
                     "{}",
                     bg_style.paint("   ├───┼───┼───┼───┼───┼───┼───┼───┤   ")
                 ));
             }
         }
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("   ╰───┴───┴───┴───┴───┴───┴───┴───╯   ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("     a   b   c   d   e   f   g   h     ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("                                       ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         write!(f, "{}", bb_str)
     }
 }
@@ -534,6 +917,7 @@ impl BitXorAssign for BitBoard {
 // TESTS
 // ****************************************************************************
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::assign_op_pattern)]
 mod tests {
 
     use super::*;
@@ -814,4 +1198,122 @@ mod tests {
         bb1 &= bb3;
         assert_eq!(bb1, BitBoard::from(0x33_33_33_33_00_00_00_00));
     }
+
+    #[test]
+    fn batch_operations_on_the_initial_white_pieces() {
+        let pieces = [
+            BitBoard::from_cells(&[Cell::E1]),
+            BitBoard::from_cells(&[Cell::D1]),
+            BitBoard::from_cells(&[Cell::C1, Cell::F1]),
+            BitBoard::from_cells(&[Cell::B1, Cell::G1]),
+            BitBoard::from_cells(&[Cell::A1, Cell::H1]),
+            BitBoard::from(RANKS_BBS[Rank::Rank2 as usize]),
+        ];
+        assert_eq!(BitBoard::batch_pop_count(&pieces), [1, 1, 2, 2, 2, 8]);
+        let kingside = BitBoard::from(0xF0_F0_F0_F0_F0_F0_F0_F0);
+        let kingside_pieces = BitBoard::batch_and(&pieces, kingside);
+        assert_eq!(
+            BitBoard::batch_pop_count(&kingside_pieces),
+            [1, 0, 1, 1, 1, 4]
+        );
+        assert_eq!(kingside_pieces[4], BitBoard::from_cells(&[Cell::H1]));
+        assert_eq!(BitBoard::batch_pop_count::<0>(&[]), []);
+    }
+
+    #[test]
+    fn shift_by_on_the_full_board() {
+        let full = BitBoard::from(!EMPTY_STATE);
+        assert_eq!(full.shift_by(Direction::East, 3).pop_count(), 40);
+        assert_eq!(full.shift_by(Direction::NorthWest, 2).pop_count(), 36);
+        assert_eq!(full.shift_by(Direction::South, 7).state, RANKS_BBS[0]);
+        assert_eq!(full.shift_by(Direction::West, 7).state, FILES_BBS[0]);
+        assert!(full.shift_by(Direction::SouthEast, 8).is_empty());
+        assert_eq!(full.shift_by(Direction::North, 0), full);
+        let bb = BitBoard::from_cells(&[Cell::H1, Cell::A8]);
+        assert_eq!(bb.shift_by(Direction::East, 1).state, single_cell(Cell::B8));
+        assert_eq!(bb.shift_by(Direction::West, 1).state, single_cell(Cell::G1));
+    }
+
+    #[test]
+    fn open_and_semi_open_files() {
+        // Initial position
+        let wp = BitBoard::from(RANKS_BBS[Rank::Rank2 as usize]);
+        let bp = BitBoard::from(RANKS_BBS[Rank::Rank7 as usize]);
+        assert!(open_files(wp, bp).is_empty());
+        assert!(semi_open_files(wp).is_empty());
+        assert_eq!(
+            open_files(BitBoard::new(), BitBoard::new()).state,
+            !EMPTY_STATE
+        );
+        // Pawns on the board edges
+        let wp = BitBoard::from_cells(&[Cell::A8, Cell::H1]);
+        let mut expected = BitBoard::from(!EMPTY_STATE);
+        expected.reset_file(File::FileA);
+        expected.reset_file(File::FileH);
+        assert_eq!(semi_open_files(wp), expected);
+        assert_eq!(
+            open_files(wp, BitBoard::from_cells(&[Cell::D4])).pop_count(),
+            40
+        );
+    }
+
+    #[test]
+    fn bitboard_macro_builds_constant_masks() {
+        const EDGES: BitBoard = bitboard![rank 1, rank 8, file A, file H];
+        assert_eq!(EDGES.pop_count(), 28);
+        assert_eq!(EDGES.state, 0xFF_81_81_81_81_81_81_FF);
+        assert_eq!(
+            bitboard![A1, H8, file B,],
+            BitBoard::from(0x82_02_02_02_02_02_02_03)
+        );
+        assert_eq!(bitboard![G7], BitBoard::from_cells(&[Cell::G7]));
+    }
+
+    #[test]
+    fn rank_and_file_bits_round_trip() {
+        let bb = BitBoard::from(0x8F_00_42_18_00_F0_01_3C);
+        let mut by_ranks = BitBoard::new();
+        let mut by_files = BitBoard::new();
+        for i in 0..NUM_RANKS {
+            let r: Rank = num::FromPrimitive::from_usize(i).unwrap();
+            let f: File = num::FromPrimitive::from_usize(i).unwrap();
+            by_ranks.set_rank_bits(r, bb.rank_bits(r));
+            by_files.set_file_bits(f, bb.file_bits(f));
+        }
+        assert_eq!(by_ranks, bb);
+        assert_eq!(by_files, bb);
+        assert_eq!(bb.rank_bits(Rank::Rank8), 0x8F);
+        assert_eq!(bb.file_bits(File::FileH), 0b1000_0100);
+        assert_eq!(bb.file_bits(File::FileA), 0b1000_0010);
+
+        let mut full = BitBoard::from(0xFF_FF_FF_FF_FF_FF_FF_FF);
+        full.set_rank_bits(Rank::Rank4, 0);
+        full.set_file_bits(File::FileC, 0x0F);
+        assert_eq!(full.pop_count(), 64 - 8 - 4 + 1);
+    }
+
+    #[test]
+    fn population_statistics() {
+        let bb = BitBoard::from(0xFF_00_00_18_18_00_00_FF);
+        assert_eq!(bb.center_control_count(), 4);
+        assert_eq!(bb.density_by_rank(), [8, 0, 0, 2, 2, 0, 0, 8]);
+        assert_eq!(
+            bb.density_by_rank()
+                .iter()
+                .map(|&d| d as usize)
+                .sum::<usize>(),
+            bb.pop_count()
+        );
+        for i in 0..NUM_FILES {
+            let f: File = num::FromPrimitive::from_usize(i).unwrap();
+            let expected = if f == File::FileD || f == File::FileE {
+                4
+            } else {
+                2
+            };
+            assert_eq!(bb.cells_on_file(f), expected);
+        }
+        assert_eq!(bb.cells_on_rank(Rank::Rank5), 2);
+        assert_eq!(BitBoard::new().center_control_count(), 0);
+    }
 }
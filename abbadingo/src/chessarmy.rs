@@ -20,6 +20,7 @@
 //   ╰───┴───┴───┴───┴───┴───┴───┴───╯
 //     a   b   c   d   e   f   g   h
 
+#[cfg(feature = "std")]
 use std::fmt;
 
 // -----------------------------------------------------------------------------------
@@ -29,12 +30,15 @@ use std::fmt;
 // Rustdoc: https://docs.rs/ansi_term
 // Unicode box-drawing characters: https://en.wikipedia.org/wiki/Box-drawing_character
 // Chess symbols on unicode: https://en.wikipedia.org/wiki/Chess_symbols_in_Unicode
+#[cfg(feature = "std")]
 use ansi_term::Colour::{Black, Fixed};
 // -----------------------------------------------------------------------------------
 
+use crate::attacktables::*;
 use crate::bbdefines::*;
 use crate::bitboard::BitBoard;
 use crate::chessdefines::*;
+use crate::slidingattacks::{bishop_attacks, rook_attacks};
 
 /// Structure used to represent a Chess Army.
 ///
@@ -47,6 +51,26 @@ pub struct ChessArmy {
     pub colour: ArmyColour,
 }
 
+/// The [Cell]s controlled by a [ChessArmy], split by [ChessPiece] type.
+///
+/// It is computed in a single pass over the army pieces using the
+/// [attack_map()](crate::chessarmy::ChessArmy::attack_map) method.
+///
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AttackMap {
+    /// The cells controlled by the pieces of each type, indexed by [ChessPiece].
+    pub by_piece: [BitBoard; NUM_PIECES_TYPES],
+    /// The cells controlled by all the pieces of the army.
+    pub all: BitBoard,
+}
+
+impl AttackMap {
+    /// Returns the [BitBoard] with the [Cell]s controlled by the pieces of the given type.
+    pub fn get(&self, cp: ChessPiece) -> BitBoard {
+        self.by_piece[cp as usize]
+    }
+}
+
 impl ChessArmy {
     /// Default constructor for the [ChessArmy] struct.
     ///
@@ -159,6 +183,30 @@ impl ChessArmy {
         self.pieces_bmask[cp as usize] |= BitBoard::from_cells(cells);
     }
 
+    /// Remove some pieces of the given [ChessPiece] type from the [ChessArmy].
+    ///
+    /// Cells not occupied by a piece of the given type are silently ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * cp - The [ChessPiece] type to be removed from the [ChessArmy]
+    /// * cells - The [Cell]s where the pieces are placed
+    ///
+    /// # Example:
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::bitboard::{BitBoard};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece };
+    /// # use abbadingo::chessarmy::ChessArmy;
+    /// let mut army = ChessArmy::initial(ArmyColour::White);
+    /// army.remove_pieces(ChessPiece::Rook, &[Cell::A1, Cell::A2]);
+    /// assert_eq!(army.get_pieces(ChessPiece::Rook), BitBoard::from_cells(&[Cell::H1]));
+    /// assert_eq!(army.num_pieces(), 15);
+    ///```
+    pub fn remove_pieces(&mut self, cp: ChessPiece, cells: &[Cell]) {
+        self.pieces_bmask[cp as usize].reset_cells(cells);
+    }
+
     /// Returns the number of Pieces (including pawn) of a [ChessArmy].
     ///
     /// # Example
@@ -226,12 +274,165 @@ impl ChessArmy {
     /// ```
     ///
     pub fn controlled_cells(&self, intf_board: BitBoard) -> BitBoard {
-        self.king_controlled_cells()
-            | self.queens_controlled_cells(intf_board)
-            | self.bishops_controlled_cells(intf_board)
-            | self.knights_controlled_cells()
-            | self.rooks_controlled_cells(intf_board)
-            | self.pawns_controlled_cells()
+        self.attack_map(intf_board).all
+    }
+
+    /// Returns the [AttackMap] of the [ChessArmy], with the [Cell]s controlled by
+    /// the pieces of each type and by the whole army.
+    ///
+    /// The interference board is used as in [controlled_cells()](crate::chessarmy::ChessArmy::controlled_cells).
+    ///
+    /// # Arguments
+    ///
+    /// `intf_board`: A [BitBoard] with pieces limiting the "view" of the [ChessArmy]
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::bitboard::{BitBoard};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece};
+    /// # use abbadingo::chessarmy::{ChessArmy};
+    /// let w_army = ChessArmy::initial(ArmyColour::White);
+    /// let b_army = ChessArmy::initial(ArmyColour::Black);
+    /// let am = w_army.attack_map(b_army.occupied_cells());
+    /// assert_eq!(am.get(ChessPiece::Knight), BitBoard::from_cells(&[
+    ///     Cell::A3, Cell::C3, Cell::D2, Cell::E2, Cell::F3, Cell::H3
+    /// ]));
+    /// assert_eq!(am.get(ChessPiece::Rook), BitBoard::from_cells(&[
+    ///     Cell::A2, Cell::B1, Cell::G1, Cell::H2
+    /// ]));
+    /// assert_eq!(am.all, w_army.controlled_cells(b_army.occupied_cells()));
+    /// ```
+    ///
+    pub fn attack_map(&self, intf_board: BitBoard) -> AttackMap {
+        let mut am = AttackMap::default();
+        let busy_cells_bitboard = self.occupied_cells() | intf_board;
+        for (ndx, pieces) in self.pieces_bmask.iter().enumerate() {
+            // We can unwrap safely here... ndx is always a valid piece
+            let cp: ChessPiece = num::FromPrimitive::from_usize(ndx).unwrap();
+            let mut remaining = pieces.state;
+            while remaining != EMPTY_STATE {
+                // We can unwrap safely here... trailing_zeros() is always a valid cell
+                let c = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+                remaining &= remaining - 1; // Reset LS1B
+                am.by_piece[ndx] |= match cp {
+                    ChessPiece::Pawn => ChessArmy::pawn_controlled_cells(c, self.colour),
+                    _ => attacks_from(cp, c, busy_cells_bitboard),
+                };
+            }
+            am.all |= am.by_piece[ndx];
+        }
+        am
+    }
+
+    /// Returns the [BitBoard] with the pieces of the [ChessArmy] giving check
+    /// to the King of the `enemy` army.
+    ///
+    /// The view of the pieces is limited by the pieces of both the armies.
+    /// If the enemy army has no King the empty [BitBoard] is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `enemy`: The enemy [ChessArmy]
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::bitboard::{BitBoard};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece};
+    /// # use abbadingo::chessarmy::{ChessArmy};
+    /// let mut w_army = ChessArmy::new(ArmyColour::White);
+    /// w_army.place_pieces(ChessPiece::King, &[Cell::G1]);
+    /// w_army.place_pieces(ChessPiece::Rook, &[Cell::E1]);
+    /// w_army.place_pieces(ChessPiece::Knight, &[Cell::D6]);
+    /// w_army.place_pieces(ChessPiece::Bishop, &[Cell::A4]);
+    /// let mut b_army = ChessArmy::new(ArmyColour::Black);
+    /// b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// b_army.place_pieces(ChessPiece::Pawn, &[Cell::D7]);
+    /// // The Rook and the Knight give check, the Bishop is blocked by the d7 pawn
+    /// assert_eq!(
+    ///     w_army.pieces_giving_check(&b_army),
+    ///     BitBoard::from_cells(&[Cell::E1, Cell::D6])
+    /// );
+    /// ```
+    ///
+    pub fn pieces_giving_check(&self, enemy: &ChessArmy) -> BitBoard {
+        let king = match enemy.get_pieces(ChessPiece::King).active_cell() {
+            Some(k) => k,
+            None => return BitBoard::new(),
+        };
+        let occupancy = self.occupied_cells() | enemy.occupied_cells();
+        let queens = self.get_pieces(ChessPiece::Queen);
+
+        // A piece attacks the King if a piece of the same type placed in the
+        // King position attacks the piece (pawns are the only exception:
+        // we have to use the attacks of a pawn of the enemy colour)
+        (BitBoard::from(pawn_attacks(king, enemy.colour)) & self.get_pieces(ChessPiece::Pawn))
+            | (attacks_from(ChessPiece::Knight, king, occupancy)
+                & self.get_pieces(ChessPiece::Knight))
+            | (attacks_from(ChessPiece::Bishop, king, occupancy)
+                & (self.get_pieces(ChessPiece::Bishop) | queens))
+            | (attacks_from(ChessPiece::Rook, king, occupancy)
+                & (self.get_pieces(ChessPiece::Rook) | queens))
+            | (attacks_from(ChessPiece::King, king, occupancy) & self.get_pieces(ChessPiece::King))
+    }
+
+    /// Returns the [BitBoard] with the pieces of the [ChessArmy] that give a
+    /// discovered check to the King of the `enemy` army when moved, i.e. the pieces
+    /// that are the only obstacle between a Bishop, Rook or Queen of the army
+    /// and the enemy King.
+    ///
+    /// Note that the candidates moving along the line with the King do not
+    /// discover the check.
+    ///
+    /// # Arguments
+    ///
+    /// * `enemy`: The enemy [ChessArmy]
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::bitboard::{BitBoard};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece};
+    /// # use abbadingo::chessarmy::{ChessArmy};
+    /// let mut w_army = ChessArmy::new(ArmyColour::White);
+    /// w_army.place_pieces(ChessPiece::King, &[Cell::G1]);
+    /// w_army.place_pieces(ChessPiece::Rook, &[Cell::E1]);
+    /// w_army.place_pieces(ChessPiece::Knight, &[Cell::E4]);
+    /// let mut b_army = ChessArmy::new(ArmyColour::Black);
+    /// b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// assert!(w_army.pieces_giving_check(&b_army).is_empty());
+    /// assert_eq!(
+    ///     w_army.discovered_check_candidates(&b_army),
+    ///     BitBoard::from_cells(&[Cell::E4])
+    /// );
+    /// ```
+    ///
+    pub fn discovered_check_candidates(&self, enemy: &ChessArmy) -> BitBoard {
+        let king = match enemy.get_pieces(ChessPiece::King).active_cell() {
+            Some(k) => k,
+            None => return BitBoard::new(),
+        };
+        let own = self.occupied_cells();
+        let occupancy = own | enemy.occupied_cells();
+        let queens = self.get_pieces(ChessPiece::Queen);
+
+        // Sliding pieces that would attack the King on an empty board
+        let mut snipers = (attacks_from(ChessPiece::Bishop, king, BitBoard::new())
+            & (self.get_pieces(ChessPiece::Bishop) | queens))
+            | (attacks_from(ChessPiece::Rook, king, BitBoard::new())
+                & (self.get_pieces(ChessPiece::Rook) | queens));
+
+        let mut bb = BitBoard::new();
+        while !snipers.is_empty() {
+            let s = snipers.state.trailing_zeros() as usize;
+            snipers.state &= snipers.state - 1; // Reset LS1B
+            let blockers = BitBoard::from(BETWEEN[s][king as usize]) & occupancy;
+            if blockers.pop_count() == 1 {
+                bb |= blockers & own;
+            }
+        }
+        bb
     }
 
     /// Returns the [BitBoard] with the possible moves of a piece placed in the given
@@ -270,6 +471,139 @@ impl ChessArmy {
         }
     }
 
+    /// Returns the [BitBoard] with the safe moves of the [ChessArmy] King, i.e.
+    /// the possible moves that do not place the King in a [Cell] controlled by the
+    /// `enemy` army. If no safe moves are possible, the empty [BitBoard] is returned.
+    ///
+    /// The King does not block the view of the enemy sliding pieces, so the
+    /// cells behind the King along the line of a checking piece are not safe.
+    /// Protected enemy pieces cannot be captured.
+    ///
+    /// # Arguments
+    ///
+    /// * `enemy`: The enemy [ChessArmy]
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::bitboard::{BitBoard};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece};
+    /// # use abbadingo::chessarmy::{ChessArmy};
+    /// let mut w_army = ChessArmy::new(ArmyColour::White);
+    /// w_army.place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// let mut b_army = ChessArmy::new(ArmyColour::Black);
+    /// b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// b_army.place_pieces(ChessPiece::Rook, &[Cell::A1]);
+    /// b_army.place_pieces(ChessPiece::Pawn, &[Cell::E3]);
+    /// // The Rook controls the first rank, the pawn d2 and f2
+    /// assert_eq!(w_army.safe_moves_for_king(&b_army), BitBoard::from_cells(&[Cell::E2]));
+    /// ```
+    ///
+    pub fn safe_moves_for_king(&self, enemy: &ChessArmy) -> BitBoard {
+        let intf_board = self.occupied_cells() ^ self.get_pieces(ChessPiece::King);
+        let unsafe_cells = enemy.controlled_cells(intf_board);
+        (self.possible_moves_for_king() | unsafe_cells) ^ unsafe_cells
+    }
+
+    /// Returns true if the [ChessArmy] can castle on the given side, considering
+    /// the pieces placement and the cells controlled by the `enemy` army.
+    ///
+    /// The King and the Rook shall be in their initial [Cell]s, the cells between
+    /// them shall be free and the cells crossed by the King (its start and
+    /// destination cells included) shall not be controlled by the enemy.
+    /// The castling rights (i.e. if King or Rook have already moved) are not known
+    /// by the army and shall be checked by the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `enemy`: The enemy [ChessArmy]
+    /// * `side`: The [CastlingSide] of the castling
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::chessdefines::{ArmyColour, CastlingSide, ChessPiece};
+    /// # use abbadingo::chessarmy::{ChessArmy};
+    /// let mut w_army = ChessArmy::new(ArmyColour::White);
+    /// w_army.place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// w_army.place_pieces(ChessPiece::Rook, &[Cell::A1, Cell::H1]);
+    /// let mut b_army = ChessArmy::new(ArmyColour::Black);
+    /// b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// b_army.place_pieces(ChessPiece::Bishop, &[Cell::A6]);
+    /// // The Bishop controls f1
+    /// assert!(!w_army.can_castle(&b_army, CastlingSide::Kingside));
+    /// assert!(w_army.can_castle(&b_army, CastlingSide::Queenside));
+    /// ```
+    ///
+    pub fn can_castle(&self, enemy: &ChessArmy, side: CastlingSide) -> bool {
+        let (king, rook, free, crossed) = match (self.colour, side) {
+            (ArmyColour::White, CastlingSide::Kingside) => (
+                Cell::E1,
+                Cell::H1,
+                &[Cell::F1, Cell::G1][..],
+                &[Cell::E1, Cell::F1, Cell::G1],
+            ),
+            (ArmyColour::White, CastlingSide::Queenside) => (
+                Cell::E1,
+                Cell::A1,
+                &[Cell::B1, Cell::C1, Cell::D1][..],
+                &[Cell::E1, Cell::D1, Cell::C1],
+            ),
+            (ArmyColour::Black, CastlingSide::Kingside) => (
+                Cell::E8,
+                Cell::H8,
+                &[Cell::F8, Cell::G8][..],
+                &[Cell::E8, Cell::F8, Cell::G8],
+            ),
+            (ArmyColour::Black, CastlingSide::Queenside) => (
+                Cell::E8,
+                Cell::A8,
+                &[Cell::B8, Cell::C8, Cell::D8][..],
+                &[Cell::E8, Cell::D8, Cell::C8],
+            ),
+        };
+        self.get_piece_in_cell(king) == Some(ChessPiece::King)
+            && self.get_piece_in_cell(rook) == Some(ChessPiece::Rook)
+            && ((self.occupied_cells() | enemy.occupied_cells()) & BitBoard::from_cells(free))
+                .is_empty()
+            && (enemy.controlled_cells(self.occupied_cells()) & BitBoard::from_cells(crossed))
+                .is_empty()
+    }
+
+    /// Returns the [ChessPiece] occupying the given [Cell] if one,
+    /// or `None` if the [Cell] is free.
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - The [Cell] to check.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::{Cell};
+    /// # use abbadingo::chessdefines::{ArmyColour, ChessPiece};
+    /// # use abbadingo::chessarmy::ChessArmy;
+    /// let army = ChessArmy::initial(ArmyColour::White);
+    /// assert_eq!(army.get_piece_in_cell(Cell::G1), Some(ChessPiece::Knight));
+    /// assert_eq!(army.get_piece_in_cell(Cell::G3), None);
+    /// ```
+    pub fn get_piece_in_cell(&self, c: Cell) -> Option<ChessPiece> {
+        if self.get_pieces(ChessPiece::King).cell_is_active(c) {
+            Some(ChessPiece::King)
+        } else if self.get_pieces(ChessPiece::Queen).cell_is_active(c) {
+            Some(ChessPiece::Queen)
+        } else if self.get_pieces(ChessPiece::Bishop).cell_is_active(c) {
+            Some(ChessPiece::Bishop)
+        } else if self.get_pieces(ChessPiece::Knight).cell_is_active(c) {
+            Some(ChessPiece::Knight)
+        } else if self.get_pieces(ChessPiece::Rook).cell_is_active(c) {
+            Some(ChessPiece::Rook)
+        } else if self.get_pieces(ChessPiece::Pawn).cell_is_active(c) {
+            Some(ChessPiece::Pawn)
+        } else {
+            None
+        }
+    }
+
     // ---------------------------------------------------------------------------
     // PRIVATE METHODS
     // ---------------------------------------------------------------------------
@@ -313,31 +647,6 @@ impl ChessArmy {
         }
     }
 
-    /// Returns the [ChessPiece] occupying the given [Cell] if one,
-    /// or `None` if the [Cell] is free.
-    ///
-    /// # Arguments
-    ///
-    /// * `c` - The [Cell] to check.
-    ///
-    fn get_piece_in_cell(&self, c: Cell) -> Option<ChessPiece> {
-        if self.get_pieces(ChessPiece::King).cell_is_active(c) {
-            Some(ChessPiece::King)
-        } else if self.get_pieces(ChessPiece::Queen).cell_is_active(c) {
-            Some(ChessPiece::Queen)
-        } else if self.get_pieces(ChessPiece::Bishop).cell_is_active(c) {
-            Some(ChessPiece::Bishop)
-        } else if self.get_pieces(ChessPiece::Knight).cell_is_active(c) {
-            Some(ChessPiece::Knight)
-        } else if self.get_pieces(ChessPiece::Rook).cell_is_active(c) {
-            Some(ChessPiece::Rook)
-        } else if self.get_pieces(ChessPiece::Pawn).cell_is_active(c) {
-            Some(ChessPiece::Pawn)
-        } else {
-            None
-        }
-    }
-
     /// Returns the [Cell] with the position of the King.
     ///
     /// This is the only "get_position" function that makes sense because
@@ -352,7 +661,7 @@ impl ChessArmy {
     /// Returns the [BitBoard] with the [Cell]s controlled by the [ChessArmy] King.
     ///
     fn king_controlled_cells(&self) -> BitBoard {
-        BitBoard::from(crate::bbdefines::neighbour(self.get_king_position()))
+        BitBoard::from(KING_ATTACKS[self.get_king_position() as usize])
     }
 
     /// Returns the [BitBoard] with the [Cell]s controlled by the [ChessArmy] Pawns.
@@ -390,46 +699,7 @@ impl ChessArmy {
             if let Some(ChessPiece::Knight) =
                 self.get_piece_in_cell(num::FromPrimitive::from_usize(cell_ndx).unwrap())
             {
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), 2, 1)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), 1, 2)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), -1, 2)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), -2, 1)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), -2, -1)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), -1, -2)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), 1, -2)
-                {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) =
-                    calc_cell_after_steps(num::FromPrimitive::from_usize(cell_ndx).unwrap(), 2, -1)
-                {
-                    bb.set_cell(cell);
-                }
+                bb |= BitBoard::from(KNIGHT_ATTACKS[cell_ndx]);
                 remaining -= 1;
             }
             cell_ndx += 1;
@@ -458,88 +728,11 @@ impl ChessArmy {
 
         while cell_ndx <= Cell::H8 as usize && remaining > 0 {
             // We can unwrap safely here... cell_ndx is always valid
-            ////if (self.get_pieces(ChessPiece::Bishop)
-            ////    & BitBoard::from_cells(&[num::FromPrimitive::from_usize(cell_ndx).unwrap()]))
-            ////    != BitBoard::new()
-            if let Some(ChessPiece::Bishop) =
-                self.get_piece_in_cell(num::FromPrimitive::from_usize(cell_ndx).unwrap())
-            {
-                let f = file(num::FromPrimitive::from_usize(cell_ndx).unwrap());
-                let r = rank(num::FromPrimitive::from_usize(cell_ndx).unwrap());
-
-                // Bishop found in position cell_ndx, (file f, rank r)
-                // Eplore diagonal and antidiagonals for controlled
-                // cells. The cells are controlled until a busy cell
+            let c = num::FromPrimitive::from_usize(cell_ndx).unwrap();
+            if let Some(ChessPiece::Bishop) = self.get_piece_in_cell(c) {
+                // The cells are controlled until a busy cell
                 // is found: the busy cell is the last controlled one.
-
-                // Explore the left-lower section of the diagonal
-                let mut file_ndx = f as i32 - 1;
-                let mut rank_ndx = r as i32 - 1;
-                while file_ndx >= 0 && rank_ndx >= 0 {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_i32(file_ndx).unwrap(),
-                        num::FromPrimitive::from_i32(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        num::FromPrimitive::from_i32(file_ndx).unwrap(),
-                        num::FromPrimitive::from_i32(rank_ndx).unwrap(),
-                    )) {
-                        break;
-                    }
-                    file_ndx -= 1;
-                    rank_ndx -= 1;
-                }
-                // Explore the right-upper section of the diagonal
-                let mut file_ndx = f as usize + 1;
-                let mut rank_ndx = r as usize + 1;
-                while file_ndx < NUM_FILES && rank_ndx < NUM_RANKS {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    )) {
-                        break;
-                    }
-                    file_ndx += 1;
-                    rank_ndx += 1;
-                }
-                // Explore the left-upper section of the antidiagonal
-                let mut file_ndx = f as i32 - 1;
-                let mut rank_ndx = r as usize + 1;
-                while file_ndx >= 0 && rank_ndx < NUM_RANKS {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_i32(file_ndx).unwrap(),
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        num::FromPrimitive::from_i32(file_ndx).unwrap(),
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    )) {
-                        break;
-                    }
-                    file_ndx -= 1;
-                    rank_ndx += 1;
-                }
-                // Explore the right-lower section of the antidiagonal
-                let mut file_ndx = f as usize + 1;
-                let mut rank_ndx = r as i32 - 1;
-                while file_ndx < NUM_FILES && rank_ndx >= 0 {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        num::FromPrimitive::from_i32(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        num::FromPrimitive::from_i32(rank_ndx).unwrap(),
-                    )) {
-                        break;
-                    }
-                    file_ndx += 1;
-                    rank_ndx -= 1;
-                }
+                bb |= BitBoard::from(bishop_attacks(c, busy_cells_bitboard.state));
                 remaining -= 1;
             }
             cell_ndx += 1;
@@ -568,75 +761,11 @@ impl ChessArmy {
 
         while cell_ndx <= Cell::H8 as usize && remaining > 0 {
             // We can unwrap safely here... cell_ndx is always valid
-            if let Some(ChessPiece::Rook) =
-                self.get_piece_in_cell(num::FromPrimitive::from_usize(cell_ndx).unwrap())
-            {
-                let f = file(num::FromPrimitive::from_usize(cell_ndx).unwrap());
-                let r = rank(num::FromPrimitive::from_usize(cell_ndx).unwrap());
-
-                // Rook found in position cell_ndx, (file f, rank r)
-                // Eplore rank and file for controlled cells.
+            let c = num::FromPrimitive::from_usize(cell_ndx).unwrap();
+            if let Some(ChessPiece::Rook) = self.get_piece_in_cell(c) {
                 // The cells are controlled until a busy cell
                 // is found: the busy cell is the last controlled one.
-
-                // Explore the left side of the rank
-                let mut file_ndx = f as i32 - 1;
-                while file_ndx >= 0 {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_i32(file_ndx).unwrap(),
-                        r,
-                    );
-                    if busy_cells_bitboard
-                        .cell_is_active(to_cell(num::FromPrimitive::from_i32(file_ndx).unwrap(), r))
-                    {
-                        break;
-                    }
-                    file_ndx -= 1;
-                }
-                // Explore the right side of the rank
-                let mut file_ndx = f as usize + 1;
-                while file_ndx < NUM_FILES {
-                    bb.set_cell_from_file_and_rank(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        r,
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        num::FromPrimitive::from_usize(file_ndx).unwrap(),
-                        r,
-                    )) {
-                        break;
-                    }
-                    file_ndx += 1;
-                }
-                // Explore the lower side of the file
-                let mut rank_ndx = r as i32 - 1;
-                while rank_ndx >= 0 {
-                    bb.set_cell_from_file_and_rank(
-                        f,
-                        num::FromPrimitive::from_i32(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard
-                        .cell_is_active(to_cell(f, num::FromPrimitive::from_i32(rank_ndx).unwrap()))
-                    {
-                        break;
-                    }
-                    rank_ndx -= 1;
-                }
-                // Explore the upper side of the file
-                let mut rank_ndx = r as usize + 1;
-                while rank_ndx < NUM_RANKS {
-                    bb.set_cell_from_file_and_rank(
-                        f,
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    );
-                    if busy_cells_bitboard.cell_is_active(to_cell(
-                        f,
-                        num::FromPrimitive::from_usize(rank_ndx).unwrap(),
-                    )) {
-                        break;
-                    }
-                    rank_ndx += 1;
-                }
+                bb |= BitBoard::from(rook_attacks(c, busy_cells_bitboard.state));
                 remaining -= 1;
             }
             cell_ndx += 1;
@@ -658,28 +787,22 @@ impl ChessArmy {
     /// * `intf_board`: A [BitBoard] with pieces limiting the "view" of the [ChessArmy]
     ///
     fn queens_controlled_cells(&self, intf_board: BitBoard) -> BitBoard {
-        // Cells controlled by Queens is the union of the cells
-        // controlled by rooks and bishops in the same position
-        // of the queens. The code below is quite tricky... we have
-        // to convert bishops and rooks in pawn to mantain interference
-        // and avoid to signal wrong controlled cells and than:
-        //  - place Bishops in the Queens positions and compute the controlled cells
-        //  - place Rooks in the Queens positions and add the controlled cells
-        //
-        let mut fake_army = *self;
-        fake_army.pieces_bmask[ChessPiece::Pawn as usize] |=
-            fake_army.get_pieces(ChessPiece::Bishop);
-        fake_army.pieces_bmask[ChessPiece::Pawn as usize] |= fake_army.get_pieces(ChessPiece::Rook);
-
-        fake_army.pieces_bmask[ChessPiece::Bishop as usize] =
-            fake_army.get_pieces(ChessPiece::Queen);
-        fake_army.pieces_bmask[ChessPiece::Queen as usize] = BitBoard::new();
-        let mut bb = fake_army.bishops_controlled_cells(intf_board);
-
-        fake_army.pieces_bmask[ChessPiece::Rook as usize] =
-            fake_army.get_pieces(ChessPiece::Bishop);
-        fake_army.pieces_bmask[ChessPiece::Bishop as usize] = BitBoard::new();
-        bb |= fake_army.rooks_controlled_cells(intf_board);
+        let mut bb = BitBoard::new();
+        let mut remaining = self.get_pieces(ChessPiece::Queen).pop_count();
+        let busy_cells_bitboard = self.occupied_cells() | intf_board;
+        let mut cell_ndx = Cell::A1 as usize;
+
+        while cell_ndx <= Cell::H8 as usize && remaining > 0 {
+            // We can unwrap safely here... cell_ndx is always valid
+            let c = num::FromPrimitive::from_usize(cell_ndx).unwrap();
+            if let Some(ChessPiece::Queen) = self.get_piece_in_cell(c) {
+                // Cells controlled by a Queen are the union of the cells
+                // controlled by a Bishop and a Rook in the same position
+                bb |= attacks_from(ChessPiece::Queen, c, busy_cells_bitboard);
+                remaining -= 1;
+            }
+            cell_ndx += 1;
+        }
         bb
     }
 
@@ -696,26 +819,7 @@ impl ChessArmy {
     /// * `ac`: The [ArmyColour] of the pawn
     ///
     fn pawn_controlled_cells(c: Cell, ac: ArmyColour) -> BitBoard {
-        let mut bb = BitBoard::new();
-        match ac {
-            ArmyColour::White => {
-                if let Some(cell) = nw(c) {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) = ne(c) {
-                    bb.set_cell(cell);
-                }
-            }
-            ArmyColour::Black => {
-                if let Some(cell) = sw(c) {
-                    bb.set_cell(cell);
-                }
-                if let Some(cell) = se(c) {
-                    bb.set_cell(cell);
-                }
-            }
-        }
-        bb
+        BitBoard::from(pawn_attacks(c, ac))
     }
 
     /// Returns the [BitBoard] with the [Cell]s controlled by the [ChessArmy]
@@ -904,8 +1008,9 @@ impl ChessArmy {
 
 /// Display trait for [ChessArmy] structure.
 ///
-/// Represent a bitboard in "ascii" form.
+/// Represent a bitboard in "ascii" form. Available only with the `std` feature.
 ///
+#[cfg(feature = "std")]
 impl fmt::Display for ChessArmy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut king_sym = "♔";
@@ -929,18 +1034,18 @@ impl fmt::Display for ChessArmy {
             "{}",
             bg_style.paint("                                       ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("     a   b   c   d   e   f   g   h     ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("   ╭───┬───┬───┬───┬───┬───┬───┬───╮   ")
         ));
         for r in (0..8).rev() {
-            bb_str.push_str(&"\n".to_string());
+            bb_str.push('\n');
             bb_str.push_str(&format!("{}", bg_style.paint(" ")));
             bb_str.push_str(&format!("{}", bg_style.paint((r + 1).to_string())));
             bb_str.push_str(&format!("{}", bg_style.paint(" │ ")));
@@ -976,29 +1081,29 @@ impl fmt::Display for ChessArmy {
             bb_str.push_str(&format!("{}", bg_style.paint((r + 1).to_string())));
             bb_str.push_str(&format!("{}", bg_style.paint(" ")));
             if r > 0 {
-                bb_str.push_str(&"\n".to_string());
+                bb_str.push('\n');
                 bb_str.push_str(&format!(
                     "{}",
                     bg_style.paint("   ├───┼───┼───┼───┼───┼───┼───┼───┤   ")
                 ));
             }
         }
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("   ╰───┴───┴───┴───┴───┴───┴───┴───╯   ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("     a   b   c   d   e   f   g   h     ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         bb_str.push_str(&format!(
             "{}",
             bg_style.paint("                                       ")
         ));
-        bb_str.push_str(&"\n".to_string());
+        bb_str.push('\n');
         write!(f, "{}", bb_str)
     }
 }
@@ -2220,4 +2325,127 @@ mod tests {
         a2.place_pieces(ChessPiece::Pawn, &[Cell::D3, Cell::E2]);
         assert_eq!(a1, a2);
     }
+
+    #[test]
+    fn attack_map_is_consistent_with_the_controlled_cells_by_piece_type() {
+        let mut a = ChessArmy::new(ArmyColour::Black);
+        a.place_pieces(ChessPiece::King, &[Cell::G8]);
+        a.place_pieces(ChessPiece::Queen, &[Cell::D4, Cell::B6]);
+        a.place_pieces(ChessPiece::Bishop, &[Cell::C5]);
+        a.place_pieces(ChessPiece::Knight, &[Cell::E5, Cell::H1]);
+        a.place_pieces(ChessPiece::Rook, &[Cell::D8]);
+        a.place_pieces(ChessPiece::Pawn, &[Cell::F7, Cell::G7, Cell::H6, Cell::D5]);
+        let intf_board = BitBoard::from_cells(&[Cell::D2, Cell::A5, Cell::F4]);
+        let am = a.attack_map(intf_board);
+        let mut all = BitBoard::new();
+        for ndx in 0..NUM_PIECES_TYPES {
+            let cp = num::FromPrimitive::from_usize(ndx).unwrap();
+            assert_eq!(am.get(cp), a.controlled_cells_by_piece_type(cp, intf_board));
+            all |= am.get(cp);
+        }
+        assert_eq!(am.all, all);
+        assert_eq!(a.controlled_cells(intf_board), all);
+    }
+
+    #[test]
+    fn pieces_giving_check_by_all_piece_types() {
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::D5]);
+        b_army.place_pieces(ChessPiece::Rook, &[Cell::D7]);
+
+        let mut w_army = ChessArmy::new(ArmyColour::White);
+        w_army.place_pieces(ChessPiece::King, &[Cell::H1]);
+        w_army.place_pieces(ChessPiece::Pawn, &[Cell::C4, Cell::E6]);
+        w_army.place_pieces(ChessPiece::Knight, &[Cell::F4, Cell::H3]);
+        w_army.place_pieces(ChessPiece::Bishop, &[Cell::G8, Cell::B3, Cell::F3]);
+        w_army.place_pieces(ChessPiece::Rook, &[Cell::D8]);
+        w_army.place_pieces(ChessPiece::Queen, &[Cell::H5]);
+        // c4 pawn, f4 knight, f3 bishop and h5 queen give check; e6 pawn
+        // attacks d7 and f7, g8 and b3 bishops are blocked by own pawns,
+        // d8 rook is blocked by the d7 black rook
+        assert_eq!(
+            w_army.pieces_giving_check(&b_army),
+            BitBoard::from_cells(&[Cell::C4, Cell::F4, Cell::F3, Cell::H5])
+        );
+
+        // Black pawns attack downwards
+        let mut w_army = ChessArmy::new(ArmyColour::White);
+        w_army.place_pieces(ChessPiece::King, &[Cell::E4]);
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::A8]);
+        b_army.place_pieces(ChessPiece::Pawn, &[Cell::D5, Cell::E5, Cell::F3]);
+        assert_eq!(
+            b_army.pieces_giving_check(&w_army),
+            BitBoard::from_cells(&[Cell::D5])
+        );
+    }
+
+    #[test]
+    fn discovered_check_candidates_in_complex_position() {
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::E5]);
+        b_army.place_pieces(ChessPiece::Pawn, &[Cell::G7]);
+
+        let mut w_army = ChessArmy::new(ArmyColour::White);
+        w_army.place_pieces(ChessPiece::King, &[Cell::A1]);
+        w_army.place_pieces(ChessPiece::Queen, &[Cell::E1]);
+        w_army.place_pieces(ChessPiece::Bishop, &[Cell::B2, Cell::H8]);
+        w_army.place_pieces(ChessPiece::Rook, &[Cell::A5]);
+        w_army.place_pieces(ChessPiece::Knight, &[Cell::E3, Cell::C5]);
+        w_army.place_pieces(ChessPiece::Pawn, &[Cell::C3, Cell::F6]);
+        // e3 knight (queen line), c3 pawn (bishop diagonal), c5 knight
+        // (rook line); h8 bishop is blocked twice (g7, f6)
+        assert_eq!(
+            w_army.discovered_check_candidates(&b_army),
+            BitBoard::from_cells(&[Cell::E3, Cell::C3, Cell::C5])
+        );
+        assert!(w_army.pieces_giving_check(&b_army).is_empty());
+    }
+
+    #[test]
+    fn king_cannot_step_back_along_the_checking_line() {
+        let mut w_army = ChessArmy::new(ArmyColour::White);
+        w_army.place_pieces(ChessPiece::King, &[Cell::D4]);
+        w_army.place_pieces(ChessPiece::Pawn, &[Cell::C5]);
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::H8]);
+        b_army.place_pieces(ChessPiece::Bishop, &[Cell::A1]);
+        b_army.place_pieces(ChessPiece::Knight, &[Cell::B6]);
+        // The a1 bishop gives check and controls e5 behind the King,
+        // the knight controls c4 and d5
+        assert_eq!(
+            w_army.safe_moves_for_king(&b_army),
+            BitBoard::from_cells(&[Cell::D3, Cell::E3, Cell::E4])
+        );
+    }
+
+    #[test]
+    fn castling_conditions() {
+        let mut w_army = ChessArmy::initial(ArmyColour::White);
+        let b_army = ChessArmy::initial(ArmyColour::Black);
+        // Cells between King and Rooks are occupied
+        assert!(!w_army.can_castle(&b_army, CastlingSide::Kingside));
+        assert!(!w_army.can_castle(&b_army, CastlingSide::Queenside));
+
+        w_army = ChessArmy::new(ArmyColour::White);
+        w_army.place_pieces(ChessPiece::King, &[Cell::E1]);
+        w_army.place_pieces(ChessPiece::Rook, &[Cell::A1, Cell::H1]);
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+        b_army.place_pieces(ChessPiece::Rook, &[Cell::B8]);
+        // b1 can be controlled: the King does not cross it
+        assert!(w_army.can_castle(&b_army, CastlingSide::Queenside));
+        b_army.place_pieces(ChessPiece::Knight, &[Cell::B1]);
+        assert!(!w_army.can_castle(&b_army, CastlingSide::Queenside));
+        assert!(w_army.can_castle(&b_army, CastlingSide::Kingside));
+        // King in check
+        b_army.place_pieces(ChessPiece::Queen, &[Cell::E4]);
+        assert!(!w_army.can_castle(&b_army, CastlingSide::Kingside));
+
+        let mut b_army = ChessArmy::new(ArmyColour::Black);
+        b_army.place_pieces(ChessPiece::King, &[Cell::E8]);
+        b_army.place_pieces(ChessPiece::Rook, &[Cell::H8]);
+        assert!(b_army.can_castle(&w_army, CastlingSide::Kingside));
+        assert!(!b_army.can_castle(&w_army, CastlingSide::Queenside));
+    }
 }
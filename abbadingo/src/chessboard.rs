@@ -0,0 +1,1604 @@
+//! Definition of the [ChessBoard] structure: the complete state of a chess position.
+//!
+//! A [ChessBoard] holds the two [ChessArmy] instances and the position state
+//! that cannot be derived from the pieces placement: the army to move, the
+//! castling rights, the en-passant target cell and the move counters.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessboard::*;
+//! # use abbadingo::chessdefines::*;
+//! let board = ChessBoard::initial();
+//! assert_eq!(board.to_move, ArmyColour::White);
+//! assert_eq!(board.castling_rights, CastlingRights::all());
+//! assert_eq!(board.piece_at(Cell::D8), Some((ChessPiece::Queen, ArmyColour::Black)));
+//! assert_eq!(board.piece_at(Cell::D4), None);
+//! assert!(!board.is_in_check());
+//! ```
+//!
+//! # Playing moves
+//!
+//! The moves can be played in two ways:
+//!
+//! - in place, with [make_move()](ChessBoard::make_move) and
+//!   [unmake_move()](ChessBoard::unmake_move): the [MoveUndo] returned when the
+//!   move is made holds the state needed to take it back. This is the cheapest
+//!   way to walk a search tree depth-first with a single board;
+//! - in functional style, with [make_move_copy()](ChessBoard::make_move_copy),
+//!   that leaves the board untouched and returns the new position. A
+//!   [ChessBoard] is a small `Copy` value (twelve bitboards and a few counters),
+//!   so the positions can be shared read-only between threads (e.g. by a
+//!   concurrent tree search) without locks, each thread deriving its own
+//!   children positions.
+//!
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessboard::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::chessmove::ChessMove;
+//! let root = ChessBoard::initial();
+//! let e4 = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None);
+//!
+//! let child = root.make_move_copy(e4);
+//! assert_eq!(child.en_passant, Some(Cell::E3));
+//! assert_eq!(root, ChessBoard::initial());
+//!
+//! let mut board = root;
+//! let undo = board.make_move(e4);
+//! assert_eq!(board, child);
+//! board.unmake_move(e4, undo);
+//! assert_eq!(board, root);
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::attacktables::{attacks_from, BETWEEN, PAWN_ATTACKS};
+use crate::bbdefines::*;
+use crate::bitboard::BitBoard;
+use crate::chessarmy::ChessArmy;
+use crate::chessdefines::*;
+use crate::chessmove::ChessMove;
+use crate::game::{MoveGen, Position};
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// The pieces a pawn can be promoted to, in the order the promotions are generated
+const PROMOTION_PIECES: [ChessPiece; 4] = [
+    ChessPiece::Queen,
+    ChessPiece::Rook,
+    ChessPiece::Bishop,
+    ChessPiece::Knight,
+];
+
+// The pieces in the order they appear in the material signature
+const MATERIAL_KEY_PIECES: [ChessPiece; NUM_PIECES_TYPES] = [
+    ChessPiece::King,
+    ChessPiece::Queen,
+    ChessPiece::Rook,
+    ChessPiece::Bishop,
+    ChessPiece::Knight,
+    ChessPiece::Pawn,
+];
+
+/// A chess position: the two armies and the position state.
+///
+/// The armies are accessed with [army()](ChessBoard::army) and
+/// [army_mut()](ChessBoard::army_mut), indexed by their [ArmyColour].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChessBoard {
+    armies: [ChessArmy; 2],
+    /// The army to move.
+    pub to_move: ArmyColour,
+    /// The castling rights of both the armies.
+    pub castling_rights: CastlingRights,
+    /// The en-passant target cell, i.e. the cell "behind" a pawn that has just
+    /// made a two-steps move.
+    pub en_passant: Option<Cell>,
+    /// The number of half moves since the last capture or pawn move.
+    pub half_move_clock: u32,
+    /// The number of the full move, starting from 1 and incremented after each
+    /// Black move.
+    pub full_move_number: u32,
+}
+
+/// The part of the [ChessBoard] state that cannot be recovered from a move once
+/// it has been played, returned by [make_move()](ChessBoard::make_move) and
+/// needed by [unmake_move()](ChessBoard::unmake_move) to take the move back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MoveUndo {
+    castling_rights: CastlingRights,
+    en_passant: Option<Cell>,
+    half_move_clock: u32,
+}
+
+// The weights of the pieces in the game phase computation, indexed by ChessPiece
+const PHASE_WEIGHTS: [u32; NUM_PIECES_TYPES] = [0, 4, 1, 1, 2, 0];
+// The sum of the weights of the pieces of the initial position
+const TOTAL_PHASE_WEIGHT: u32 = 24;
+
+/// The largest value returned by [phase_value()](ChessBoard::phase_value),
+/// reached when only Kings and Pawns are on the board.
+pub const MAX_PHASE_VALUE: u32 = 256;
+
+/// The phase of a chess game, as classified by [phase()](ChessBoard::phase).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+/// The reasons why a [ChessBoard] is not a legal chess position, reported by
+/// [validate_position].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionError {
+    /// The army of the given colour has not exactly one King.
+    KingsNumber(ArmyColour),
+    /// The army that is not to move is in check.
+    SideNotToMoveInCheck,
+    /// The army of the given colour has pawns on the first or on the last rank.
+    PawnsOnBackRank(ArmyColour),
+    /// The en-passant target cell is not behind a pawn that has just made a
+    /// two-steps move.
+    EnPassantCell,
+    /// The castling right of the given colour and side is set, but the King or
+    /// the Rook are not in their initial cells.
+    CastlingRights(ArmyColour, CastlingSide),
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl ChessBoard {
+    /// Default constructor for the [ChessBoard] struct: instantiate an empty board,
+    /// with White to move, no castling rights and no en-passant target cell.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessboard::*;
+    /// let board = ChessBoard::new();
+    /// assert!(board.occupied_cells().is_empty());
+    /// assert_eq!(board.full_move_number, 1);
+    /// ```
+    pub fn new() -> ChessBoard {
+        ChessBoard {
+            armies: [
+                ChessArmy::new(ArmyColour::White),
+                ChessArmy::new(ArmyColour::Black),
+            ],
+            to_move: ArmyColour::White,
+            castling_rights: CastlingRights::none(),
+            en_passant: None,
+            half_move_clock: 0,
+            full_move_number: 1,
+        }
+    }
+
+    /// Returns a [ChessBoard] with the initial chess game position.
+    pub fn initial() -> ChessBoard {
+        ChessBoard {
+            armies: [
+                ChessArmy::initial(ArmyColour::White),
+                ChessArmy::initial(ArmyColour::Black),
+            ],
+            castling_rights: CastlingRights::all(),
+            ..ChessBoard::new()
+        }
+    }
+
+    /// Creates a [ChessBoard] from a "mailbox" array with the content of the
+    /// 64 cells, indexed by [Cell] (from A1 to H8), as for [new()](ChessBoard::new)
+    /// White is to move, with no castling rights and no en-passant target cell.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut cells = [None; 64];
+    /// cells[Cell::E1 as usize] = Some((ArmyColour::White, ChessPiece::King));
+    /// cells[Cell::E8 as usize] = Some((ArmyColour::Black, ChessPiece::King));
+    /// let board = ChessBoard::from_array(&cells);
+    /// assert_eq!(board.piece_at(Cell::E8), Some((ChessPiece::King, ArmyColour::Black)));
+    /// assert_eq!(board.to_array(), cells);
+    /// ```
+    pub fn from_array(cells: &[Option<(ArmyColour, ChessPiece)>; NUM_CELLS]) -> ChessBoard {
+        let mut board = ChessBoard::new();
+        for (ndx, content) in cells.iter().enumerate() {
+            if let Some((colour, cp)) = content {
+                // We can unwrap safely here... ndx is always a valid cell
+                let c = num::FromPrimitive::from_usize(ndx).unwrap();
+                board.army_mut(*colour).place_pieces(*cp, &[c]);
+            }
+        }
+        board
+    }
+
+    /// Returns the "mailbox" array with the content of the 64 cells of the
+    /// board, indexed by [Cell] (see [from_array()](ChessBoard::from_array)).
+    pub fn to_array(&self) -> [Option<(ArmyColour, ChessPiece)>; NUM_CELLS] {
+        let mut cells = [None; NUM_CELLS];
+        for (c, colour, cp) in self.pieces() {
+            cells[c as usize] = Some((colour, cp));
+        }
+        cells
+    }
+
+    /// Returns the [ChessArmy] of the given colour.
+    pub fn army(&self, c: ArmyColour) -> &ChessArmy {
+        &self.armies[c as usize]
+    }
+
+    /// Returns the [ChessArmy] of the given colour, to modify its pieces.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::new();
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// assert_eq!(board.piece_at(Cell::E8), Some((ChessPiece::King, ArmyColour::Black)));
+    /// ```
+    pub fn army_mut(&mut self, c: ArmyColour) -> &mut ChessArmy {
+        &mut self.armies[c as usize]
+    }
+
+    /// Returns the [ChessPiece] placed in the given [Cell] with the colour of
+    /// its army, or `None` if the [Cell] is free.
+    pub fn piece_at(&self, c: Cell) -> Option<(ChessPiece, ArmyColour)> {
+        self.armies
+            .iter()
+            .find_map(|a| a.get_piece_in_cell(c).map(|p| (p, a.colour)))
+    }
+
+    /// Returns an iterator over the pieces of both the armies, in [Cell] order
+    /// (from A1 to H8), each with its [Cell] and the colour of its army.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let board = ChessBoard::initial();
+    /// assert_eq!(board.pieces().count(), 32);
+    /// assert_eq!(
+    ///     board.pieces().nth(4),
+    ///     Some((Cell::E1, ArmyColour::White, ChessPiece::King))
+    /// );
+    /// assert_eq!(
+    ///     board.pieces().last(),
+    ///     Some((Cell::H8, ArmyColour::Black, ChessPiece::Rook))
+    /// );
+    /// ```
+    pub fn pieces(&self) -> impl Iterator<Item = (Cell, ArmyColour, ChessPiece)> + '_ {
+        let mut remaining = self.occupied_cells().state;
+        core::iter::from_fn(move || {
+            if remaining == EMPTY_STATE {
+                return None;
+            }
+            // We can unwrap safely here... trailing_zeros() is always a valid cell
+            let c = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+            remaining &= remaining - 1; // Reset LS1B
+            self.piece_at(c).map(|(cp, colour)| (c, colour, cp))
+        })
+    }
+
+    /// Returns a [BitBoard] with the cells occupied by the pieces of both the armies.
+    pub fn occupied_cells(&self) -> BitBoard {
+        self.armies[0].occupied_cells() | self.armies[1].occupied_cells()
+    }
+
+    /// Returns the material signature of the position: the pieces of the
+    /// White army followed by the ones of the Black army, with the characters
+    /// used in FEN strings and in the King, Queen, Rook, Bishop, Knight, Pawn
+    /// order.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::new();
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Rook, &[Cell::A1]);
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Pawn, &[Cell::E4]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::Rook, &[Cell::H8]);
+    /// assert_eq!(board.material_key(), "KRPkr");
+    /// assert_eq!(ChessBoard::initial().material_key(), "KQRRBBNNPPPPPPPPkqrrbbnnpppppppp");
+    /// ```
+    pub fn material_key(&self) -> String {
+        let mut key = String::new();
+        for colour in [ArmyColour::White, ArmyColour::Black] {
+            for cp in MATERIAL_KEY_PIECES {
+                let count = self.army(colour).get_pieces(cp).pop_count();
+                key.extend(core::iter::repeat_n(cp.to_char(colour), count));
+            }
+        }
+        key
+    }
+
+    /// Returns a hash of the material of the position: the positions with the
+    /// same [material_key()](ChessBoard::material_key), and only them, have
+    /// the same hash.
+    ///
+    /// The number of pieces of each type and colour is packed in 4 bits, in
+    /// the same order of the material signature, starting from the most
+    /// significant used bits (the White King) down to the least significant
+    /// ones (the Black Pawns).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessboard::*;
+    /// assert_eq!(ChessBoard::new().material_hash(), 0);
+    /// assert_eq!(ChessBoard::initial().material_hash(), 0x1_1_2_2_2_8_1_1_2_2_2_8);
+    /// ```
+    pub fn material_hash(&self) -> u64 {
+        let mut hash = 0;
+        for colour in [ArmyColour::White, ArmyColour::Black] {
+            for cp in MATERIAL_KEY_PIECES {
+                let count = self.army(colour).get_pieces(cp).pop_count() as u64;
+                hash = (hash << 4) | count;
+            }
+        }
+        hash
+    }
+
+    /// Returns the continuous game phase of the position, computed from the
+    /// material on the board, to be used by tapered evaluations: 0 when all
+    /// the pieces of the initial position are on the board, up to
+    /// [MAX_PHASE_VALUE] when only Kings and Pawns are left.
+    ///
+    /// Each Knight and Bishop weights 1, each Rook 2 and each Queen 4; the
+    /// extra material due to promotions is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::initial();
+    /// assert_eq!(board.phase_value(), 0);
+    /// board.army_mut(ArmyColour::White).remove_pieces(ChessPiece::Queen, &[Cell::D1]);
+    /// board.army_mut(ArmyColour::Black).remove_pieces(ChessPiece::Queen, &[Cell::D8]);
+    /// assert_eq!(board.phase_value(), 85);
+    /// assert_eq!(ChessBoard::new().phase_value(), MAX_PHASE_VALUE);
+    /// ```
+    pub fn phase_value(&self) -> u32 {
+        let weight: u32 = self
+            .pieces()
+            .map(|(_, _, cp)| PHASE_WEIGHTS[cp as usize])
+            .sum();
+        (TOTAL_PHASE_WEIGHT - weight.min(TOTAL_PHASE_WEIGHT)) * MAX_PHASE_VALUE / TOTAL_PHASE_WEIGHT
+    }
+
+    /// Returns the [GamePhase] of the position, classified from its
+    /// [phase_value()](ChessBoard::phase_value): the game is in the opening
+    /// while at most one minor piece has been traded off and it reaches the
+    /// endgame when no more than a Rook and a minor piece per side are left.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessboard::*;
+    /// assert_eq!(ChessBoard::initial().phase(), GamePhase::Opening);
+    /// assert_eq!(ChessBoard::new().phase(), GamePhase::Endgame);
+    /// ```
+    pub fn phase(&self) -> GamePhase {
+        match self.phase_value() {
+            v if v < MAX_PHASE_VALUE / 12 => GamePhase::Opening,
+            v if v < MAX_PHASE_VALUE * 3 / 4 => GamePhase::Middlegame,
+            _ => GamePhase::Endgame,
+        }
+    }
+
+    /// Returns the [BitBoard] with the [Cell]s of the pieces of the given type
+    /// of the army to move that can legally move to the destination [Cell].
+    ///
+    /// Used to disambiguate the moves written in SAN, it is also useful to
+    /// infer the start cell of a move when only its destination is known.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::bitboard::BitBoard;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let board = ChessBoard::initial();
+    /// assert_eq!(
+    ///     board.pieces_that_can_move_to(ChessPiece::Knight, Cell::F3),
+    ///     BitBoard::from_cells(&[Cell::G1])
+    /// );
+    /// assert_eq!(
+    ///     board.pieces_that_can_move_to(ChessPiece::Pawn, Cell::E4),
+    ///     BitBoard::from_cells(&[Cell::E2])
+    /// );
+    /// assert!(board.pieces_that_can_move_to(ChessPiece::Bishop, Cell::E3).is_empty());
+    /// ```
+    pub fn pieces_that_can_move_to(&self, cp: ChessPiece, dest: Cell) -> BitBoard {
+        let mut pieces = BitBoard::new();
+        for m in self.legal_moves() {
+            if m.moved_piece() == cp && m.destination_cell() == dest {
+                pieces.set_cell(m.start_cell());
+            }
+        }
+        pieces
+    }
+
+    /// Returns the control map of the position: for each [Cell] (from A1 to
+    /// H8) the number of White and Black pieces attacking it, with the view
+    /// of the sliding pieces limited by the pieces of both the armies.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// let control = ChessBoard::initial().control_map();
+    /// assert_eq!(control[Cell::D3 as usize], (2, 0));
+    /// assert_eq!(control[Cell::F6 as usize], (0, 3));
+    /// assert_eq!(control[Cell::E4 as usize], (0, 0));
+    /// ```
+    pub fn control_map(&self) -> [(u8, u8); NUM_CELLS] {
+        let mut control = [(0, 0); NUM_CELLS];
+        let occupancy = self.occupied_cells();
+        for (c, colour, cp) in self.pieces() {
+            let mut remaining = match cp {
+                ChessPiece::Pawn => PAWN_ATTACKS[colour as usize][c as usize],
+                _ => attacks_from(cp, c, occupancy).state,
+            };
+            while remaining != EMPTY_STATE {
+                let ndx = remaining.trailing_zeros() as usize;
+                match colour {
+                    ArmyColour::White => control[ndx].0 += 1,
+                    ArmyColour::Black => control[ndx].1 += 1,
+                }
+                remaining &= remaining - 1; // Reset LS1B
+            }
+        }
+        control
+    }
+
+    /// Returns the [BitBoard] with the [Cell]s controlled by the army of the
+    /// given colour, with the view of its pieces limited by the enemy pieces.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bitboard::BitBoard;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let board = ChessBoard::initial();
+    /// assert_eq!(
+    ///     board.controlled_cells(ArmyColour::White),
+    ///     BitBoard::from(0x00_00_00_00_00_FF_FF_7E)
+    /// );
+    /// ```
+    pub fn controlled_cells(&self, c: ArmyColour) -> BitBoard {
+        self.army(c)
+            .controlled_cells(self.army(!c).occupied_cells())
+    }
+
+    /// Returns the [BitBoard] with the enemy pieces giving check to the King
+    /// of the army to move.
+    pub fn checkers(&self) -> BitBoard {
+        self.army(!self.to_move)
+            .pieces_giving_check(self.army(self.to_move))
+    }
+
+    /// Returns true if the King of the army to move is in check.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::new();
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::Rook, &[Cell::A1]);
+    /// assert!(board.is_in_check());
+    /// board.to_move = ArmyColour::Black;
+    /// assert!(!board.is_in_check());
+    /// ```
+    pub fn is_in_check(&self) -> bool {
+        !self.checkers().is_empty()
+    }
+
+    /// Returns the legal moves of the army to move.
+    ///
+    /// The pawn moves reaching the last rank are expanded in the four possible
+    /// promotions; en-passant captures and castling moves are included.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::new();
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Rook, &[Cell::H1]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::King, &[Cell::E8]);
+    /// board.castling_rights.add(ArmyColour::White, CastlingSide::Kingside);
+    /// assert_eq!(ChessBoard::initial().legal_moves().len(), 20);
+    /// // 6 King moves, castling included, and 9 Rook moves
+    /// assert_eq!(board.legal_moves().len(), 15);
+    /// ```
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        let us = self.to_move;
+        let (army, enemy) = (self.army(us), self.army(!us));
+        let mut moves = Vec::new();
+        for ndx in 0..NUM_PIECES_TYPES {
+            // We can unwrap safely here... ndx is always a valid piece
+            let cp: ChessPiece = num::FromPrimitive::from_usize(ndx).unwrap();
+            let mut remaining = army.get_pieces(cp).state;
+            while remaining != EMPTY_STATE {
+                // We can unwrap safely here... trailing_zeros() is always a valid cell
+                let start = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+                remaining &= remaining - 1; // Reset LS1B
+                let dests =
+                    army.possible_moves_for_piece_in_cell(cp, start, enemy.occupied_cells());
+                self.push_piece_moves(&mut moves, cp, start, dests.state);
+            }
+        }
+        if let Some(ep) = self.en_passant {
+            self.push_en_passant_captures(&mut moves, ep);
+        }
+        let (king, kingside_dest, queenside_dest) = match us {
+            ArmyColour::White => (Cell::E1, Cell::G1, Cell::C1),
+            ArmyColour::Black => (Cell::E8, Cell::G8, Cell::C8),
+        };
+        for (side, dest) in &[
+            (CastlingSide::Kingside, kingside_dest),
+            (CastlingSide::Queenside, queenside_dest),
+        ] {
+            if self.castling_rights.allows(us, *side) && army.can_castle(enemy, *side) {
+                moves.push(ChessMove::new(ChessPiece::King, king, *dest, None, None));
+            }
+        }
+        self.retain_legal_moves(&mut moves);
+        moves
+    }
+
+    /// Returns the legal moves of the army to move when its King is in check,
+    /// generating only the moves that can resolve the check: the King moves
+    /// and, when a single enemy piece is giving check, the captures of the
+    /// checking piece (en-passant included) and the interpositions between
+    /// the checking piece and the King.
+    ///
+    /// If the King is not in check it returns the [legal_moves()](ChessBoard::legal_moves).
+    /// The moves are the same of [legal_moves()](ChessBoard::legal_moves),
+    /// possibly in a different order.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// let mut board = ChessBoard::new();
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Rook, &[Cell::A2]);
+    /// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Knight, &[Cell::G3]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::King, &[Cell::H8]);
+    /// board.army_mut(ArmyColour::Black).place_pieces(ChessPiece::Rook, &[Cell::E5]);
+    /// // 4 King moves and the interpositions Ra2-e2, Ng3-e2 and Ng3-e4
+    /// assert_eq!(board.evasion_moves().len(), 7);
+    /// ```
+    pub fn evasion_moves(&self) -> Vec<ChessMove> {
+        let checkers = self.checkers();
+        if checkers.is_empty() {
+            return self.legal_moves();
+        }
+        let us = self.to_move;
+        let (army, enemy) = (self.army(us), self.army(!us));
+        let mut moves = Vec::new();
+        // We can unwrap safely here... the King of the army to move is in check
+        let king = army.get_pieces(ChessPiece::King).active_cell().unwrap();
+        let dests =
+            army.possible_moves_for_piece_in_cell(ChessPiece::King, king, enemy.occupied_cells());
+        self.push_piece_moves(&mut moves, ChessPiece::King, king, dests.state);
+        // With a double check only the King can move
+        if let Some(checker) = checkers.active_cell() {
+            let targets = checkers.state | BETWEEN[king as usize][checker as usize];
+            for ndx in 0..NUM_PIECES_TYPES {
+                // We can unwrap safely here... ndx is always a valid piece
+                let cp: ChessPiece = num::FromPrimitive::from_usize(ndx).unwrap();
+                if cp == ChessPiece::King {
+                    continue;
+                }
+                let mut remaining = army.get_pieces(cp).state;
+                while remaining != EMPTY_STATE {
+                    // We can unwrap safely here... trailing_zeros() is always a valid cell
+                    let start = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+                    remaining &= remaining - 1; // Reset LS1B
+                    let dests =
+                        army.possible_moves_for_piece_in_cell(cp, start, enemy.occupied_cells());
+                    self.push_piece_moves(&mut moves, cp, start, dests.state & targets);
+                }
+            }
+            // The en-passant capture of the Pawn giving check after its double step
+            if let Some(ep) = self.en_passant {
+                let pawn = match us {
+                    ArmyColour::White => s(ep),
+                    ArmyColour::Black => n(ep),
+                };
+                if pawn == Some(checker) {
+                    self.push_en_passant_captures(&mut moves, ep);
+                }
+            }
+        }
+        self.retain_legal_moves(&mut moves);
+        moves
+    }
+
+    /// Returns the candidate moves that are legal in the position, in the
+    /// order they are given.
+    ///
+    /// The legal moves are generated once (handling checks and pins a single
+    /// time) and each candidate is looked up among them, so that validating
+    /// a long list of moves does not cost a full legality check per move.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// let e4 = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None);
+    /// let e5 = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E5, None, None);
+    /// let nf3 = ChessMove::new(ChessPiece::Knight, Cell::G1, Cell::F3, None, None);
+    /// assert_eq!(ChessBoard::initial().filter_legal(&[e4, e5, nf3]), vec![e4, nf3]);
+    /// ```
+    pub fn filter_legal(&self, candidates: &[ChessMove]) -> Vec<ChessMove> {
+        let legal = self.legal_moves();
+        // The destinations of the legal moves of each start cell, to discard
+        // most of the illegal candidates without scanning the legal moves
+        let mut dests = [EMPTY_STATE; NUM_CELLS];
+        for m in &legal {
+            dests[m.start_cell() as usize] |= single_cell(m.destination_cell());
+        }
+        candidates
+            .iter()
+            .filter(|m| {
+                dests[m.start_cell() as usize] & single_cell(m.destination_cell()) != EMPTY_STATE
+                    && legal.contains(m)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns true if the given move is a plausible premove for the army
+    /// that is not to move, i.e. a move that can become legal after any reply
+    /// of the army to move.
+    ///
+    /// The moved piece shall be in the start [Cell] and the destination shall
+    /// not be occupied by a piece of the same army. The reply of the opponent
+    /// may move or capture any piece, so the geometry of the move is checked
+    /// on an empty board: pawns may push (by two cells from their start rank)
+    /// or capture diagonally, and they shall promote exactly when reaching the
+    /// last rank. Castling requires the castling right and no own pieces
+    /// between the King and the Rook. The taken piece is not checked, since it
+    /// depends on the reply.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// let board = ChessBoard::initial();
+    /// let qh4 = ChessMove::new(ChessPiece::Queen, Cell::D8, Cell::H4, None, None);
+    /// let ng6 = ChessMove::new(ChessPiece::Knight, Cell::G8, Cell::G6, None, None);
+    /// let e4 = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None);
+    /// assert!(board.is_plausible_premove(qh4));
+    /// assert!(!board.is_plausible_premove(ng6));
+    /// // White is to move: its moves are not premoves
+    /// assert!(!board.is_plausible_premove(e4));
+    /// ```
+    pub fn is_plausible_premove(&self, m: ChessMove) -> bool {
+        let us = !self.to_move;
+        let (piece, start, dest) = (m.moved_piece(), m.start_cell(), m.destination_cell());
+        let own = self.army(us).occupied_cells().state;
+        if self.army(us).get_piece_in_cell(start) != Some(piece)
+            || own & single_cell(dest) != EMPTY_STATE
+        {
+            return false;
+        }
+        let last_rank = match us {
+            ArmyColour::White => Rank::Rank8,
+            ArmyColour::Black => Rank::Rank1,
+        };
+        let promotes = piece == ChessPiece::Pawn && rank(dest) == last_rank;
+        match m.promoted_piece() {
+            Some(p) if !promotes || !PROMOTION_PIECES.contains(&p) => return false,
+            None if promotes => return false,
+            _ => {}
+        }
+        if m.is_a_castling_move() {
+            let (side, rook) = match (us, dest) {
+                (ArmyColour::White, Cell::G1) => (CastlingSide::Kingside, Cell::H1),
+                (ArmyColour::White, Cell::C1) => (CastlingSide::Queenside, Cell::A1),
+                (ArmyColour::Black, Cell::G8) => (CastlingSide::Kingside, Cell::H8),
+                (ArmyColour::Black, Cell::C8) => (CastlingSide::Queenside, Cell::A8),
+                // A King on the enemy back rank is not castling
+                _ => return false,
+            };
+            return self.castling_rights.allows(us, side)
+                && BETWEEN[start as usize][rook as usize] & own == EMPTY_STATE;
+        }
+        let reachable = match piece {
+            ChessPiece::Pawn => {
+                let (push, start_rank) = match us {
+                    ArmyColour::White => (single_cell(start) << NUM_FILES, Rank::Rank2),
+                    ArmyColour::Black => (single_cell(start) >> NUM_FILES, Rank::Rank7),
+                };
+                let double_push = match us {
+                    ArmyColour::White => push << NUM_FILES,
+                    ArmyColour::Black => push >> NUM_FILES,
+                };
+                let mut reachable = push | PAWN_ATTACKS[us as usize][start as usize];
+                if rank(start) == start_rank {
+                    reachable |= double_push;
+                }
+                reachable
+            }
+            _ => attacks_from(piece, start, BitBoard::new()).state,
+        };
+        reachable & single_cell(dest) != EMPTY_STATE
+    }
+
+    /// Plays a move of the army to move in place, and returns the [MoveUndo]
+    /// to be passed to [unmake_move()](ChessBoard::unmake_move) to take it back.
+    ///
+    /// The move is not validated: it shall be a legal move in the position,
+    /// with the taken piece set for the captures (en-passant included).
+    /// Castling moves are recognized as King moves from e1/e8 to the g or c file.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// let mut board = ChessBoard::initial();
+    /// board.make_move(ChessMove::new(ChessPiece::Knight, Cell::G1, Cell::F3, None, None));
+    /// assert_eq!(board.to_move, ArmyColour::Black);
+    /// assert_eq!(board.piece_at(Cell::F3), Some((ChessPiece::Knight, ArmyColour::White)));
+    /// assert_eq!((board.half_move_clock, board.full_move_number), (1, 1));
+    /// ```
+    pub fn make_move(&mut self, m: ChessMove) -> MoveUndo {
+        let undo = MoveUndo {
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            half_move_clock: self.half_move_clock,
+        };
+        let us = self.to_move;
+        let piece = m.moved_piece();
+        let (start, dest) = (m.start_cell(), m.destination_cell());
+
+        if let Some(taken) = m.taken_piece() {
+            let taken_cell = ChessBoard::taken_cell(m, self.en_passant);
+            self.army_mut(!us).remove_pieces(taken, &[taken_cell]);
+        }
+        self.army_mut(us).remove_pieces(piece, &[start]);
+        self.army_mut(us)
+            .place_pieces(m.promoted_piece().unwrap_or(piece), &[dest]);
+        if let Some((rook_start, rook_dest)) = ChessBoard::castling_rook_cells(m) {
+            self.army_mut(us)
+                .remove_pieces(ChessPiece::Rook, &[rook_start]);
+            self.army_mut(us)
+                .place_pieces(ChessPiece::Rook, &[rook_dest]);
+        }
+
+        if piece == ChessPiece::King {
+            self.castling_rights.remove_all(us);
+        }
+        // A move from or to a corner cell moves or captures the Rook there
+        for c in &[start, dest] {
+            match c {
+                Cell::A1 => self.castling_rights.remove_queenside(ArmyColour::White),
+                Cell::H1 => self.castling_rights.remove_kingside(ArmyColour::White),
+                Cell::A8 => self.castling_rights.remove_queenside(ArmyColour::Black),
+                Cell::H8 => self.castling_rights.remove_kingside(ArmyColour::Black),
+                _ => {}
+            }
+        }
+        self.en_passant = m.en_passant_cell();
+        if piece == ChessPiece::Pawn || m.taken_piece().is_some() {
+            self.half_move_clock = 0;
+        } else {
+            self.half_move_clock += 1;
+        }
+        if us == ArmyColour::Black {
+            self.full_move_number += 1;
+        }
+        self.to_move = !us;
+        undo
+    }
+
+    /// Takes back a move played with [make_move()](ChessBoard::make_move),
+    /// restoring the position as it was before the move.
+    ///
+    /// # Arguments
+    ///
+    /// * `m`: The last move played in the position
+    /// * `undo`: The [MoveUndo] returned by [make_move()](ChessBoard::make_move) for `m`
+    ///
+    pub fn unmake_move(&mut self, m: ChessMove, undo: MoveUndo) {
+        let us = !self.to_move;
+        let piece = m.moved_piece();
+        let (start, dest) = (m.start_cell(), m.destination_cell());
+
+        if let Some((rook_start, rook_dest)) = ChessBoard::castling_rook_cells(m) {
+            self.army_mut(us)
+                .remove_pieces(ChessPiece::Rook, &[rook_dest]);
+            self.army_mut(us)
+                .place_pieces(ChessPiece::Rook, &[rook_start]);
+        }
+        self.army_mut(us)
+            .remove_pieces(m.promoted_piece().unwrap_or(piece), &[dest]);
+        self.army_mut(us).place_pieces(piece, &[start]);
+        if let Some(taken) = m.taken_piece() {
+            let taken_cell = ChessBoard::taken_cell(m, undo.en_passant);
+            self.army_mut(!us).place_pieces(taken, &[taken_cell]);
+        }
+
+        if us == ArmyColour::Black {
+            self.full_move_number -= 1;
+        }
+        self.to_move = us;
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.half_move_clock = undo.half_move_clock;
+    }
+
+    /// Returns the position reached playing a move of the army to move, leaving
+    /// the [ChessBoard] untouched.
+    ///
+    /// The same requirements of [make_move()](ChessBoard::make_move) apply to the move.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// let board = ChessBoard::initial();
+    /// let after = board.make_move_copy(ChessMove::new(ChessPiece::Pawn, Cell::D2, Cell::D3, None, None));
+    /// assert_eq!(after.piece_at(Cell::D3), Some((ChessPiece::Pawn, ArmyColour::White)));
+    /// assert_eq!(board.piece_at(Cell::D3), None);
+    /// ```
+    pub fn make_move_copy(&self, m: ChessMove) -> ChessBoard {
+        let mut board = *self;
+        board.make_move(m);
+        board
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Returns the [Cell] of the piece taken by a capture, given the en-passant
+    /// target cell of the position where the move is played: it is the destination
+    /// cell, except for the en-passant captures.
+    ///
+    fn taken_cell(m: ChessMove, en_passant: Option<Cell>) -> Cell {
+        let dest = m.destination_cell();
+        if m.moved_piece() == ChessPiece::Pawn && en_passant == Some(dest) {
+            // We can unwrap safely here... the en-passant cell is on the 3rd or 6th rank
+            let taken = match rank(dest) {
+                Rank::Rank6 => s(dest),
+                _ => n(dest),
+            };
+            return taken.unwrap();
+        }
+        dest
+    }
+
+    /// Returns true if the given en-passant target cell is consistent with the
+    /// position: it is on the 6th (3rd) rank if White (Black) is to move, an enemy
+    /// pawn is in front of it, and both the target cell and the start cell of
+    /// the pawn are free. The [en_passant](ChessBoard::en_passant) field is
+    /// public, and the en-passant captures are generated only if this holds.
+    ///
+    fn en_passant_is_possible(&self, ep: Cell) -> bool {
+        let (expected_rank, pawn, start) = match self.to_move {
+            ArmyColour::White => (Rank::Rank6, s(ep), n(ep)),
+            ArmyColour::Black => (Rank::Rank3, n(ep), s(ep)),
+        };
+        rank(ep) == expected_rank
+            && self.piece_at(ep).is_none()
+            && pawn.and_then(|c| self.piece_at(c)) == Some((ChessPiece::Pawn, !self.to_move))
+            && start.is_some_and(|c| self.piece_at(c).is_none())
+    }
+
+    /// Adds the moves of the piece of the army to move in the start [Cell] to
+    /// the given destinations, with the taken pieces and, for the Pawns
+    /// reaching the last rank, the promotions.
+    ///
+    fn push_piece_moves(
+        &self,
+        moves: &mut Vec<ChessMove>,
+        cp: ChessPiece,
+        start: Cell,
+        mut dests: BitBoardState,
+    ) {
+        let enemy = self.army(!self.to_move);
+        let last_rank = match self.to_move {
+            ArmyColour::White => Rank::Rank8,
+            ArmyColour::Black => Rank::Rank1,
+        };
+        while dests != EMPTY_STATE {
+            // We can unwrap safely here... trailing_zeros() is always a valid cell
+            let dest = num::FromPrimitive::from_u32(dests.trailing_zeros()).unwrap();
+            dests &= dests - 1; // Reset LS1B
+            let taken = enemy.get_piece_in_cell(dest);
+            if cp == ChessPiece::Pawn && rank(dest) == last_rank {
+                for promoted in &PROMOTION_PIECES {
+                    moves.push(ChessMove::new(cp, start, dest, taken, Some(*promoted)));
+                }
+            } else {
+                moves.push(ChessMove::new(cp, start, dest, taken, None));
+            }
+        }
+    }
+
+    /// Adds the en-passant captures to the given en-passant target [Cell] of
+    /// the Pawns of the army to move, if the en-passant capture is possible.
+    ///
+    fn push_en_passant_captures(&self, moves: &mut Vec<ChessMove>, ep: Cell) {
+        if !self.en_passant_is_possible(ep) {
+            return;
+        }
+        let us = self.to_move;
+        let mut remaining = PAWN_ATTACKS[!us as usize][ep as usize]
+            & self.army(us).get_pieces(ChessPiece::Pawn).state;
+        while remaining != EMPTY_STATE {
+            // We can unwrap safely here... trailing_zeros() is always a valid cell
+            let start = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+            remaining &= remaining - 1; // Reset LS1B
+            moves.push(ChessMove::new(
+                ChessPiece::Pawn,
+                start,
+                ep,
+                Some(ChessPiece::Pawn),
+                None,
+            ));
+        }
+    }
+
+    /// Discards the moves leaving the King of the army to move in check.
+    ///
+    fn retain_legal_moves(&self, moves: &mut Vec<ChessMove>) {
+        let us = self.to_move;
+        moves.retain(|m| {
+            let after = self.make_move_copy(*m);
+            after
+                .army(!us)
+                .pieces_giving_check(after.army(us))
+                .is_empty()
+        });
+    }
+
+    /// Returns the start and destination [Cell]s of the Rook if the move is a
+    /// castling move, `None` otherwise.
+    ///
+    fn castling_rook_cells(m: ChessMove) -> Option<(Cell, Cell)> {
+        if !m.is_a_castling_move() {
+            return None;
+        }
+        match m.destination_cell() {
+            Cell::G1 => Some((Cell::H1, Cell::F1)),
+            Cell::C1 => Some((Cell::A1, Cell::D1)),
+            Cell::G8 => Some((Cell::H8, Cell::F8)),
+            _ => Some((Cell::A8, Cell::D8)),
+        }
+    }
+}
+
+/// Checks that a [ChessBoard] is a legal chess position, returning all the
+/// [PositionError]s found if it is not.
+///
+/// The position is legal if:
+///
+///  - each army has exactly one King;
+///  - the army that is not to move is not in check;
+///  - there are no pawns on the first and on the last rank;
+///  - the en-passant target cell, if any, is behind a pawn that has just made
+///    a two-steps move (see [en_passant](ChessBoard::en_passant));
+///  - for each castling right, the King and the Rook are in their initial cells.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessboard::*;
+/// # use abbadingo::chessdefines::*;
+/// assert_eq!(validate_position(&ChessBoard::initial()), Ok(()));
+///
+/// let mut board = ChessBoard::new();
+/// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::King, &[Cell::E1]);
+/// board.army_mut(ArmyColour::White).place_pieces(ChessPiece::Pawn, &[Cell::A8]);
+/// board.castling_rights = CastlingRights::all();
+/// assert_eq!(
+///     validate_position(&board),
+///     Err(vec![
+///         PositionError::KingsNumber(ArmyColour::Black),
+///         PositionError::PawnsOnBackRank(ArmyColour::White),
+///         PositionError::CastlingRights(ArmyColour::White, CastlingSide::Kingside),
+///         PositionError::CastlingRights(ArmyColour::White, CastlingSide::Queenside),
+///         PositionError::CastlingRights(ArmyColour::Black, CastlingSide::Kingside),
+///         PositionError::CastlingRights(ArmyColour::Black, CastlingSide::Queenside),
+///     ])
+/// );
+/// ```
+pub fn validate_position(board: &ChessBoard) -> Result<(), Vec<PositionError>> {
+    let mut errors = Vec::new();
+    for colour in &[ArmyColour::White, ArmyColour::Black] {
+        if board.army(*colour).get_pieces(ChessPiece::King).pop_count() != 1 {
+            errors.push(PositionError::KingsNumber(*colour));
+        }
+    }
+    // The checks can be computed only if both the Kings are on the board
+    if errors.is_empty()
+        && !board
+            .army(board.to_move)
+            .pieces_giving_check(board.army(!board.to_move))
+            .is_empty()
+    {
+        errors.push(PositionError::SideNotToMoveInCheck);
+    }
+    let back_ranks = RANKS_BBS[Rank::Rank1 as usize] | RANKS_BBS[Rank::Rank8 as usize];
+    for colour in &[ArmyColour::White, ArmyColour::Black] {
+        if board.army(*colour).get_pieces(ChessPiece::Pawn).state & back_ranks != EMPTY_STATE {
+            errors.push(PositionError::PawnsOnBackRank(*colour));
+        }
+    }
+    if let Some(ep) = board.en_passant {
+        if !board.en_passant_is_possible(ep) {
+            errors.push(PositionError::EnPassantCell);
+        }
+    }
+    for (colour, king, rooks) in &[
+        (ArmyColour::White, Cell::E1, [Cell::H1, Cell::A1]),
+        (ArmyColour::Black, Cell::E8, [Cell::H8, Cell::A8]),
+    ] {
+        let army = board.army(*colour);
+        for (side, rook) in [CastlingSide::Kingside, CastlingSide::Queenside]
+            .iter()
+            .zip(rooks.iter())
+        {
+            if board.castling_rights.allows(*colour, *side)
+                && (army.get_piece_in_cell(*king) != Some(ChessPiece::King)
+                    || army.get_piece_in_cell(*rook) != Some(ChessPiece::Rook))
+            {
+                errors.push(PositionError::CastlingRights(*colour, *side));
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for ChessBoard structure
+
+/// The default [ChessBoard] is the initial chess game position.
+impl Default for ChessBoard {
+    fn default() -> Self {
+        Self::initial()
+    }
+}
+
+/// [Position] trait for [ChessBoard] structure.
+///
+/// The game is over only when the army to move has no legal moves: it is lost
+/// if the King is in check (checkmate), drawn otherwise (stalemate). The other
+/// draw rules (fifty moves, repetitions, insufficient material) are not applied.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::fenrecord::FenRecord;
+/// # use abbadingo::game::Position;
+/// # use abbadingo::testpositions::*;
+/// assert_eq!(FenRecord::try_from(CHECKMATE.fen).unwrap().board().terminal_score(), Some(0.0));
+/// assert_eq!(FenRecord::try_from(STALEMATE.fen).unwrap().board().terminal_score(), Some(0.5));
+/// assert_eq!(FenRecord::try_from(KIWIPETE.fen).unwrap().board().terminal_score(), None);
+/// ```
+///
+impl Position for ChessBoard {
+    type Move = ChessMove;
+
+    fn make_move(&mut self, m: ChessMove) {
+        ChessBoard::make_move(self, m);
+    }
+
+    fn terminal_score(&self) -> Option<f64> {
+        if !self.legal_moves().is_empty() {
+            None
+        } else if self.is_in_check() {
+            Some(0.0)
+        } else {
+            Some(0.5)
+        }
+    }
+}
+
+/// [MoveGen] trait for [ChessBoard] structure: the legal moves of the army to move.
+///
+impl MoveGen for ChessBoard {
+    fn generate_moves(&self, moves: &mut Vec<ChessMove>) {
+        moves.extend(self.legal_moves());
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::fenrecord::FenRecord;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn initial_position() {
+        let board = ChessBoard::default();
+        assert_eq!(
+            board.occupied_cells(),
+            BitBoard::from(0xFF_FF_00_00_00_00_FF_FF)
+        );
+        assert_eq!(board.army(ArmyColour::Black).colour, ArmyColour::Black);
+        assert_eq!(board.army(ArmyColour::White).num_pieces(), 16);
+        assert_eq!(board.en_passant, None);
+        assert_eq!((board.half_move_clock, board.full_move_number), (0, 1));
+        assert_eq!(
+            board.piece_at(Cell::E2),
+            Some((ChessPiece::Pawn, ArmyColour::White))
+        );
+        assert_eq!(
+            board.piece_at(Cell::G8),
+            Some((ChessPiece::Knight, ArmyColour::Black))
+        );
+        assert_eq!(
+            board.controlled_cells(ArmyColour::Black),
+            BitBoard::from(0x7E_FF_FF_00_00_00_00_00)
+        );
+    }
+
+    #[test]
+    fn mailbox_arrays_round_trip() {
+        let board = ChessBoard::initial();
+        let cells = board.to_array();
+        assert_eq!(cells.iter().filter(|c| c.is_some()).count(), 32);
+        assert_eq!(
+            cells[Cell::D1 as usize],
+            Some((ArmyColour::White, ChessPiece::Queen))
+        );
+        assert_eq!(cells[Cell::D4 as usize], None);
+        let rebuilt = ChessBoard::from_array(&cells);
+        assert_eq!(rebuilt.occupied_cells(), board.occupied_cells());
+        assert_eq!(
+            rebuilt.army(ArmyColour::Black),
+            board.army(ArmyColour::Black)
+        );
+        assert_eq!(
+            ChessBoard::from_array(&[None; NUM_CELLS]),
+            ChessBoard::new()
+        );
+    }
+
+    #[test]
+    fn material_key_and_hash_follow_the_pieces() {
+        let mut initial = ChessBoard::initial();
+        let hash = initial.material_hash();
+        let e4 = ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None);
+        initial.make_move(e4);
+        assert_eq!(initial.material_hash(), hash);
+
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::E1]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::E8]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Pawn, &[Cell::D5]);
+        assert_eq!(board.material_key(), "Kkp");
+        let kpk = board.material_hash();
+        // Moving a piece keeps the material class, changing colour does not
+        board
+            .army_mut(ArmyColour::Black)
+            .remove_pieces(ChessPiece::Pawn, &[Cell::D5]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Pawn, &[Cell::A2]);
+        assert_eq!(board.material_hash(), kpk);
+        board
+            .army_mut(ArmyColour::Black)
+            .remove_pieces(ChessPiece::Pawn, &[Cell::A2]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Pawn, &[Cell::A2]);
+        assert_eq!(board.material_key(), "KPk");
+        assert_ne!(board.material_hash(), kpk);
+    }
+
+    #[test]
+    fn game_phase_follows_the_traded_material() {
+        let mut board = ChessBoard::initial();
+        let white = ArmyColour::White;
+        let black = ArmyColour::Black;
+        board
+            .army_mut(white)
+            .remove_pieces(ChessPiece::Knight, &[Cell::B1]);
+        assert_eq!(board.phase_value(), 10);
+        assert_eq!(board.phase(), GamePhase::Opening);
+        board
+            .army_mut(black)
+            .remove_pieces(ChessPiece::Knight, &[Cell::B8]);
+        assert_eq!(board.phase(), GamePhase::Middlegame);
+        let traded = [Cell::D1, Cell::D8];
+        board
+            .army_mut(white)
+            .remove_pieces(ChessPiece::Queen, &traded);
+        board
+            .army_mut(black)
+            .remove_pieces(ChessPiece::Queen, &traded);
+        let traded = [Cell::A1, Cell::A8, Cell::C1, Cell::C8, Cell::G1, Cell::G8];
+        for colour in [white, black] {
+            for cp in [ChessPiece::Rook, ChessPiece::Bishop, ChessPiece::Knight] {
+                board.army_mut(colour).remove_pieces(cp, &traded);
+            }
+        }
+        // A Rook and a Bishop per side left
+        assert_eq!(board.phase_value(), 192);
+        assert_eq!(board.phase(), GamePhase::Endgame);
+
+        // Extra material due to promotions does not go beyond the opening
+        let mut board = ChessBoard::initial();
+        board
+            .army_mut(white)
+            .place_pieces(ChessPiece::Queen, &[Cell::D4]);
+        assert_eq!(board.phase_value(), 0);
+    }
+
+    #[test]
+    fn pieces_that_can_move_to_a_cell_exclude_the_pinned_ones() {
+        let mut board = ChessBoard::new();
+        let white = board.army_mut(ArmyColour::White);
+        white.place_pieces(ChessPiece::King, &[Cell::E1]);
+        white.place_pieces(ChessPiece::Knight, &[Cell::E2, Cell::B1, Cell::F5]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::E8]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Rook, &[Cell::E7]);
+        assert_eq!(
+            board.pieces_that_can_move_to(ChessPiece::Knight, Cell::C3),
+            BitBoard::from_cells(&[Cell::B1])
+        );
+        assert_eq!(
+            board.pieces_that_can_move_to(ChessPiece::Knight, Cell::D4),
+            BitBoard::from_cells(&[Cell::F5])
+        );
+        assert!(board
+            .pieces_that_can_move_to(ChessPiece::Knight, Cell::G1)
+            .is_empty());
+    }
+
+    #[test]
+    fn control_map_agrees_with_the_controlled_cells() {
+        let board = ChessBoard::initial();
+        let control = board.control_map();
+        for (ndx, (white, black)) in control.iter().enumerate() {
+            let bit = BitBoard::from(1 << ndx);
+            let white_controls = board.controlled_cells(ArmyColour::White) & bit;
+            let black_controls = board.controlled_cells(ArmyColour::Black) & bit;
+            assert_eq!(*white > 0, !white_controls.is_empty());
+            assert_eq!(*black > 0, !black_controls.is_empty());
+        }
+        // Own pieces are defended too: the e2 pawn by King, Queen, Bishop and Knight
+        assert_eq!(control[Cell::E2 as usize], (4, 0));
+        assert_eq!(control.iter().map(|(w, _)| *w as u32).sum::<u32>(), 38);
+    }
+
+    #[test]
+    fn filter_legal_keeps_only_the_legal_moves() {
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::E1]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Bishop, &[Cell::E2]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::E8]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Rook, &[Cell::E7]);
+        let legal = board.legal_moves();
+        assert_eq!(board.filter_legal(&legal), legal);
+        let pinned = ChessMove::new(ChessPiece::Bishop, Cell::E2, Cell::D3, None, None);
+        let king = ChessMove::new(ChessPiece::King, Cell::E1, Cell::D1, None, None);
+        let wrong_capture = ChessMove::new(
+            ChessPiece::King,
+            Cell::E1,
+            Cell::D1,
+            Some(ChessPiece::Pawn),
+            None,
+        );
+        assert_eq!(
+            board.filter_legal(&[pinned, king, wrong_capture, king]),
+            vec![king, king]
+        );
+        assert!(board.filter_legal(&[]).is_empty());
+    }
+
+    #[test]
+    fn premoves_are_checked_on_an_empty_board() {
+        let board = ChessBoard::initial();
+        let premove = |cp, start, dest, taken, promoted| {
+            board.is_plausible_premove(ChessMove::new(cp, start, dest, taken, promoted))
+        };
+        // Own pieces block the destination, not the path
+        assert!(premove(ChessPiece::Bishop, Cell::F8, Cell::B4, None, None));
+        assert!(!premove(ChessPiece::Bishop, Cell::F8, Cell::E7, None, None));
+        assert!(!premove(ChessPiece::Bishop, Cell::F8, Cell::F6, None, None));
+        assert!(!premove(ChessPiece::Knight, Cell::F8, Cell::E6, None, None));
+        // Pawn pushes and captures
+        assert!(premove(ChessPiece::Pawn, Cell::E7, Cell::E5, None, None));
+        assert!(premove(
+            ChessPiece::Pawn,
+            Cell::E7,
+            Cell::D6,
+            Some(ChessPiece::Pawn),
+            None
+        ));
+        assert!(!premove(ChessPiece::Pawn, Cell::E7, Cell::E4, None, None));
+        assert!(!premove(ChessPiece::Pawn, Cell::E7, Cell::E8, None, None));
+        assert!(!premove(
+            ChessPiece::Pawn,
+            Cell::E7,
+            Cell::E6,
+            None,
+            Some(ChessPiece::Queen)
+        ));
+        // Castling needs the right and no own pieces between King and Rook
+        assert!(!premove(ChessPiece::King, Cell::E8, Cell::G8, None, None));
+
+        let black_to_play = FenRecord::try_from("r3k2r/1p6/8/8/8/8/8/R3K2R w Qk - 0 1")
+            .unwrap()
+            .board();
+        let premove = |cp, start, dest, promoted| {
+            black_to_play.is_plausible_premove(ChessMove::new(cp, start, dest, None, promoted))
+        };
+        assert!(premove(ChessPiece::King, Cell::E8, Cell::G8, None));
+        assert!(!premove(ChessPiece::King, Cell::E8, Cell::C8, None));
+        assert!(premove(ChessPiece::Rook, Cell::A8, Cell::A1, None));
+        assert!(premove(ChessPiece::Pawn, Cell::B7, Cell::B5, None));
+        assert!(!premove(ChessPiece::Rook, Cell::A1, Cell::A8, None));
+    }
+
+    #[test]
+    fn evasion_moves_are_the_legal_moves_when_in_check() {
+        fn check_evasions(board: &ChessBoard, depth: u32, checks: &mut u32) {
+            let mut legal = board.legal_moves();
+            if board.is_in_check() {
+                let mut evasions = board.evasion_moves();
+                legal.sort();
+                evasions.sort();
+                assert_eq!(evasions, legal);
+                *checks += 1;
+            }
+            if depth > 0 {
+                for m in legal {
+                    check_evasions(&board.make_move_copy(m), depth - 1, checks);
+                }
+            }
+        }
+        let mut checks = 0;
+        for pos in crate::testpositions::ALL_PERFT_POSITIONS {
+            let board = FenRecord::try_from(pos.fen).unwrap().board();
+            check_evasions(&board, 2, &mut checks);
+        }
+        assert!(checks > 100);
+    }
+
+    #[test]
+    fn checkers_of_the_army_to_move() {
+        let mut board = ChessBoard::new();
+        board.to_move = ArmyColour::Black;
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::G1]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Knight, &[Cell::F6]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Queen, &[Cell::E2]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::E8]);
+        assert_eq!(
+            board.checkers(),
+            BitBoard::from_cells(&[Cell::F6, Cell::E2])
+        );
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Pawn, &[Cell::E7]);
+        assert_eq!(board.checkers(), BitBoard::from_cells(&[Cell::F6]));
+    }
+
+    #[test]
+    fn castling_and_rook_moves_update_castling_rights() {
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::E1]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Rook, &[Cell::A1, Cell::H1]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::E8]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Rook, &[Cell::A8, Cell::H8]);
+        board.castling_rights = CastlingRights::all();
+        let start = board;
+
+        let castle = ChessMove::new(ChessPiece::King, Cell::E1, Cell::G1, None, None);
+        let undo = board.make_move(castle);
+        assert_eq!(
+            board.army(ArmyColour::White).get_pieces(ChessPiece::Rook),
+            BitBoard::from_cells(&[Cell::A1, Cell::F1])
+        );
+        assert_eq!(
+            board.piece_at(Cell::G1),
+            Some((ChessPiece::King, ArmyColour::White))
+        );
+        assert_eq!(
+            board.castling_rights,
+            CastlingRights::from_bits(
+                CastlingRights::BLACK_KINGSIDE | CastlingRights::BLACK_QUEENSIDE
+            )
+        );
+
+        // Moving the a8 rook (here capturing the a1 rook) removes the Black
+        // queen side castling, the king side one is kept
+        let rxa1 = ChessMove::new(
+            ChessPiece::Rook,
+            Cell::A8,
+            Cell::A1,
+            Some(ChessPiece::Rook),
+            None,
+        );
+        let after = board.make_move_copy(rxa1);
+        assert_eq!(
+            after.castling_rights,
+            CastlingRights::from_bits(CastlingRights::BLACK_KINGSIDE)
+        );
+        assert_eq!(after.full_move_number, 2);
+        assert_eq!(after.half_move_clock, 0);
+
+        board.unmake_move(castle, undo);
+        assert_eq!(board, start);
+    }
+
+    #[test]
+    fn en_passant_captures_need_an_enemy_pawn() {
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::E1]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Pawn, &[Cell::E5]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::H8]);
+        board.en_passant = Some(Cell::D6);
+        let exd6 = ChessMove::new(
+            ChessPiece::Pawn,
+            Cell::E5,
+            Cell::D6,
+            Some(ChessPiece::Pawn),
+            None,
+        );
+        assert!(!board.legal_moves().contains(&exd6));
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Pawn, &[Cell::D5]);
+        assert!(board.legal_moves().contains(&exd6));
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Knight, &[Cell::D6]);
+        assert!(!board.legal_moves().contains(&exd6));
+    }
+
+    #[test]
+    fn en_passant_and_promotion_are_taken_back() {
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::E1]);
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::Pawn, &[Cell::E5, Cell::B7]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::King, &[Cell::H8]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Pawn, &[Cell::D7]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Knight, &[Cell::C8]);
+        board.to_move = ArmyColour::Black;
+        board.half_move_clock = 7;
+        let start = board;
+
+        let d5 = ChessMove::new(ChessPiece::Pawn, Cell::D7, Cell::D5, None, None);
+        let exd6 = ChessMove::new(
+            ChessPiece::Pawn,
+            Cell::E5,
+            Cell::D6,
+            Some(ChessPiece::Pawn),
+            None,
+        );
+        let bxc8 = ChessMove::new(
+            ChessPiece::Pawn,
+            Cell::B7,
+            Cell::C8,
+            Some(ChessPiece::Knight),
+            Some(ChessPiece::Queen),
+        );
+        let undo_d5 = board.make_move(d5);
+        assert_eq!(board.en_passant, Some(Cell::D6));
+        let undo_exd6 = board.make_move(exd6);
+        assert_eq!(board.piece_at(Cell::D5), None);
+        assert_eq!(board.army(ArmyColour::Black).num_pieces(), 2);
+        assert_eq!(board.en_passant, None);
+        let kh7 = ChessMove::new(ChessPiece::King, Cell::H8, Cell::H7, None, None);
+        let undo_kh7 = board.make_move(kh7);
+        let after_kh7 = board;
+        let undo_bxc8 = board.make_move(bxc8);
+        assert_eq!(
+            board.piece_at(Cell::C8),
+            Some((ChessPiece::Queen, ArmyColour::White))
+        );
+        assert!(board
+            .army(ArmyColour::White)
+            .get_pieces(ChessPiece::Pawn)
+            .cell_is_active(Cell::D6));
+
+        board.unmake_move(bxc8, undo_bxc8);
+        assert_eq!(board, after_kh7);
+        board.unmake_move(kh7, undo_kh7);
+        board.unmake_move(exd6, undo_exd6);
+        assert_eq!(
+            board.piece_at(Cell::D5),
+            Some((ChessPiece::Pawn, ArmyColour::Black))
+        );
+        board.unmake_move(d5, undo_d5);
+        assert_eq!(board, start);
+    }
+}
@@ -2,10 +2,12 @@
 //!
 //! In this module there are for example definition for Chess pieces, and Army colours
 
-use std::fmt;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Not;
 
+use crate::bbdefines::Direction;
 use crate::error::AbbaDingoError;
-use std::convert::TryFrom;
 
 // ********************************************************************************
 // ********************************************************************************
@@ -16,7 +18,7 @@ use std::convert::TryFrom;
 /// The colour of a chess Army.
 ///
 /// Army colour can be `White` or `Black`.
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum ArmyColour {
     White,
     Black,
@@ -25,7 +27,7 @@ pub enum ArmyColour {
 /// The chess pieces.
 ///
 /// The enum with the traditional chess pieces, from King to Pawn.
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum ChessPiece {
     King,
     Queen,
@@ -38,12 +40,397 @@ pub enum ChessPiece {
 /// The number of possible Pieces types
 pub const NUM_PIECES_TYPES: usize = 6;
 
+/// The side of the board where a castling is performed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    /// King side castling (O-O).
+    Kingside,
+    /// Queen side castling (O-O-O).
+    Queenside,
+}
+
+/// The castling rights of both armies, stored as a set of 4 flags.
+///
+/// The rights only record if the King and the Rook involved have not moved yet:
+/// they do not consider if the castling is currently possible (e.g. cells between
+/// King and Rook occupied or attacked).
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::chessdefines::*;
+/// let mut cr = CastlingRights::try_from("KQk").unwrap();
+/// assert!(cr.allows(ArmyColour::White, CastlingSide::Queenside));
+/// assert!(!cr.allows(ArmyColour::Black, CastlingSide::Queenside));
+/// cr.remove_kingside(ArmyColour::White);
+/// assert_eq!(format!("{}", cr), "Qk");
+/// cr.remove_all(ArmyColour::White);
+/// cr.remove_all(ArmyColour::Black);
+/// assert_eq!(format!("{}", cr), "-");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CastlingRights {
+    flags: u8,
+}
+
+/// The result of a chess game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    /// The White army won the game.
+    WhiteWins,
+    /// The Black army won the game.
+    BlackWins,
+    /// The game ended in a draw.
+    Draw,
+    /// The game is still in progress (or its result is unknown).
+    Ongoing,
+}
+
+/// The reason why a chess game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminationReason {
+    Checkmate,
+    Stalemate,
+    FiftyMove,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    Resignation,
+    Timeout,
+    Agreement,
+}
+
 // ********************************************************************************
 // ********************************************************************************
 // FUNCTIONS / METHODS / TRAITS
 // ********************************************************************************
 // ********************************************************************************
 
+impl ArmyColour {
+    /// Returns the opposite [ArmyColour] (also available with the `!` operator).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// assert_eq!(ArmyColour::White.opposite(), ArmyColour::Black);
+    /// assert_eq!(!ArmyColour::Black, ArmyColour::White);
+    /// ```
+    pub const fn opposite(self) -> ArmyColour {
+        match self {
+            ArmyColour::White => ArmyColour::Black,
+            ArmyColour::Black => ArmyColour::White,
+        }
+    }
+
+    /// Returns the [Direction] of the pawns pushes for the army
+    /// (North for White, South for Black).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// assert_eq!(ArmyColour::White.forward_direction(), Direction::North);
+    /// assert_eq!(ArmyColour::Black.forward_direction(), Direction::South);
+    /// ```
+    pub const fn forward_direction(self) -> Direction {
+        match self {
+            ArmyColour::White => Direction::North,
+            ArmyColour::Black => Direction::South,
+        }
+    }
+}
+
+impl ChessPiece {
+    /// Returns the conventional material value of the piece, in centipawns.
+    ///
+    /// The King, that cannot be exchanged, has value 0.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// assert_eq!(ChessPiece::Pawn.value(), 100);
+    /// assert_eq!(ChessPiece::Queen.value(), 900);
+    /// ```
+    pub const fn value(self) -> u32 {
+        match self {
+            ChessPiece::King => 0,
+            ChessPiece::Queen => 900,
+            ChessPiece::Bishop => 300,
+            ChessPiece::Knight => 300,
+            ChessPiece::Rook => 500,
+            ChessPiece::Pawn => 100,
+        }
+    }
+
+    /// Returns true if the piece is a slider (Queen, Bishop or Rook), i.e. a
+    /// piece that moves along rays and can be blocked.
+    pub const fn is_slider(self) -> bool {
+        matches!(
+            self,
+            ChessPiece::Queen | ChessPiece::Bishop | ChessPiece::Rook
+        )
+    }
+
+    /// Returns the character used to represent the piece in FEN strings:
+    /// uppercase for the White army, lowercase for the Black one.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// assert_eq!(ChessPiece::Knight.to_char(ArmyColour::White), 'N');
+    /// assert_eq!(ChessPiece::Knight.to_char(ArmyColour::Black), 'n');
+    /// ```
+    pub const fn to_char(self, colour: ArmyColour) -> char {
+        let c = match self {
+            ChessPiece::King => 'K',
+            ChessPiece::Queen => 'Q',
+            ChessPiece::Bishop => 'B',
+            ChessPiece::Knight => 'N',
+            ChessPiece::Rook => 'R',
+            ChessPiece::Pawn => 'P',
+        };
+        match colour {
+            ArmyColour::White => c,
+            ArmyColour::Black => c.to_ascii_lowercase(),
+        }
+    }
+
+    /// Converts a character used in FEN strings to the corresponding piece and
+    /// army colour, returns `None` if the character does not represent a piece.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// assert_eq!(ChessPiece::from_char('q'), Some((ChessPiece::Queen, ArmyColour::Black)));
+    /// assert_eq!(ChessPiece::from_char('P'), Some((ChessPiece::Pawn, ArmyColour::White)));
+    /// assert_eq!(ChessPiece::from_char('x'), None);
+    /// ```
+    pub const fn from_char(c: char) -> Option<(ChessPiece, ArmyColour)> {
+        let colour = if c.is_ascii_uppercase() {
+            ArmyColour::White
+        } else {
+            ArmyColour::Black
+        };
+        let piece = match c.to_ascii_uppercase() {
+            'K' => ChessPiece::King,
+            'Q' => ChessPiece::Queen,
+            'B' => ChessPiece::Bishop,
+            'N' => ChessPiece::Knight,
+            'R' => ChessPiece::Rook,
+            'P' => ChessPiece::Pawn,
+            _ => return None,
+        };
+        Some((piece, colour))
+    }
+}
+
+impl CastlingRights {
+    /// White can castle king side.
+    pub const WHITE_KINGSIDE: u8 = 0x01;
+    /// White can castle queen side.
+    pub const WHITE_QUEENSIDE: u8 = 0x02;
+    /// Black can castle king side.
+    pub const BLACK_KINGSIDE: u8 = 0x04;
+    /// Black can castle queen side.
+    pub const BLACK_QUEENSIDE: u8 = 0x08;
+
+    /// Returns the castling rights with no castling allowed.
+    pub const fn none() -> CastlingRights {
+        CastlingRights { flags: 0 }
+    }
+
+    /// Returns the castling rights with all the castlings allowed,
+    /// as in the initial standard position.
+    pub const fn all() -> CastlingRights {
+        CastlingRights {
+            flags: CastlingRights::WHITE_KINGSIDE
+                | CastlingRights::WHITE_QUEENSIDE
+                | CastlingRights::BLACK_KINGSIDE
+                | CastlingRights::BLACK_QUEENSIDE,
+        }
+    }
+
+    /// Builds the castling rights from a set of flags (only the 4 least
+    /// significant bits are considered).
+    pub const fn from_bits(flags: u8) -> CastlingRights {
+        CastlingRights {
+            flags: flags & CastlingRights::all().flags,
+        }
+    }
+
+    /// Returns the flags of the castling rights.
+    pub const fn bits(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns true if the given army can castle on the given side.
+    pub const fn allows(&self, colour: ArmyColour, side: CastlingSide) -> bool {
+        self.flags & CastlingRights::flag(colour, side) != 0
+    }
+
+    /// Returns true if no castling is allowed.
+    pub const fn is_empty(&self) -> bool {
+        self.flags == 0
+    }
+
+    /// Allows the given army to castle on the given side.
+    pub fn add(&mut self, colour: ArmyColour, side: CastlingSide) {
+        self.flags |= CastlingRights::flag(colour, side);
+    }
+
+    /// Removes the right to castle on the given side to the given army.
+    pub fn remove(&mut self, colour: ArmyColour, side: CastlingSide) {
+        self.flags &= !CastlingRights::flag(colour, side);
+    }
+
+    /// Removes the right to castle king side to the given army
+    /// (e.g. after a move of its king side Rook).
+    pub fn remove_kingside(&mut self, colour: ArmyColour) {
+        self.remove(colour, CastlingSide::Kingside);
+    }
+
+    /// Removes the right to castle queen side to the given army
+    /// (e.g. after a move of its queen side Rook).
+    pub fn remove_queenside(&mut self, colour: ArmyColour) {
+        self.remove(colour, CastlingSide::Queenside);
+    }
+
+    /// Removes all the castling rights of the given army (e.g. after a King move).
+    pub fn remove_all(&mut self, colour: ArmyColour) {
+        self.remove_kingside(colour);
+        self.remove_queenside(colour);
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Returns the flag of the castling of the given army on the given side.
+    ///
+    const fn flag(colour: ArmyColour, side: CastlingSide) -> u8 {
+        match (colour, side) {
+            (ArmyColour::White, CastlingSide::Kingside) => CastlingRights::WHITE_KINGSIDE,
+            (ArmyColour::White, CastlingSide::Queenside) => CastlingRights::WHITE_QUEENSIDE,
+            (ArmyColour::Black, CastlingSide::Kingside) => CastlingRights::BLACK_KINGSIDE,
+            (ArmyColour::Black, CastlingSide::Queenside) => CastlingRights::BLACK_QUEENSIDE,
+        }
+    }
+}
+
+impl GameResult {
+    /// Returns the [GameResult] of a game won by the given army.
+    pub const fn win_for(colour: ArmyColour) -> GameResult {
+        match colour {
+            ArmyColour::White => GameResult::WhiteWins,
+            ArmyColour::Black => GameResult::BlackWins,
+        }
+    }
+
+    /// Returns the winner of the game, if any.
+    pub const fn winner(self) -> Option<ArmyColour> {
+        match self {
+            GameResult::WhiteWins => Some(ArmyColour::White),
+            GameResult::BlackWins => Some(ArmyColour::Black),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the game is over.
+    pub const fn is_over(self) -> bool {
+        !matches!(self, GameResult::Ongoing)
+    }
+
+    /// Returns the result string used in PGN ("1-0", "0-1", "1/2-1/2" or "*").
+    pub const fn as_pgn(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+}
+
+impl TerminationReason {
+    /// Returns true if the game ended with this reason is drawn
+    /// (whatever the winner for the other reasons).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// assert!(TerminationReason::Stalemate.is_draw());
+    /// assert!(!TerminationReason::Timeout.is_draw());
+    /// ```
+    pub const fn is_draw(self) -> bool {
+        matches!(
+            self,
+            TerminationReason::Stalemate
+                | TerminationReason::FiftyMove
+                | TerminationReason::ThreefoldRepetition
+                | TerminationReason::InsufficientMaterial
+                | TerminationReason::Agreement
+        )
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for ArmyColour enum
+
+/// Not trait for [ArmyColour] enum: returns the opposite colour.
+impl Not for ArmyColour {
+    type Output = ArmyColour;
+    fn not(self) -> Self::Output {
+        self.opposite()
+    }
+}
+
+/// Display trait for [ArmyColour] enum.
+///
+/// Display an [ArmyColour] as in the active colour field of FEN strings ("w" or "b").
+///
+/// # Example
+/// ```
+/// # use abbadingo::chessdefines::*;
+/// assert_eq!(format!("{}", ArmyColour::White), "w");
+/// assert_eq!(format!("{}", ArmyColour::Black), "b");
+/// ```
+///
+impl fmt::Display for ArmyColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArmyColour::White => write!(f, "w"),
+            ArmyColour::Black => write!(f, "b"),
+        }
+    }
+}
+
+/// Tentatively convert a char with the active colour field of FEN strings
+/// ('w' or 'b') to the corresponding [ArmyColour].
+///
+/// For other values Err(AbbaDingoError::IllegalConversionToArmyColour) is returned.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// assert_eq!(ArmyColour::try_from('b'), Ok(ArmyColour::Black));
+/// assert_eq!(ArmyColour::try_from('W'), Err(AbbaDingoError::IllegalConversionToArmyColour));
+/// ```
+///
+impl TryFrom<char> for ArmyColour {
+    type Error = AbbaDingoError;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'w' => Ok(ArmyColour::White),
+            'b' => Ok(ArmyColour::Black),
+            _ => Err(AbbaDingoError::IllegalConversionToArmyColour),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for ChessPiece enum
+
 /// Display trait for [ChessPiece] structure.
 ///
 /// Display a [ChessPiece] into its String representation ("King", "Queen", ...,"pawn").
@@ -56,15 +443,14 @@ pub const NUM_PIECES_TYPES: usize = 6;
 ///
 impl fmt::Display for ChessPiece {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let str_repr = (match self {
+        let str_repr = match self {
             ChessPiece::King => "King",
             ChessPiece::Queen => "Queen",
             ChessPiece::Bishop => "Bishop",
             ChessPiece::Knight => "Knight",
             ChessPiece::Rook => "Rook",
             ChessPiece::Pawn => "pawn",
-        })
-        .to_string();
+        };
         write!(f, "{}", str_repr)
     }
 }
@@ -98,3 +484,231 @@ impl TryFrom<&str> for ChessPiece {
         }
     }
 }
+
+// ----------------------------------------------------------------------------
+// Traits implementation for GameResult enum
+
+/// Display trait for [GameResult] enum.
+///
+/// Display a [GameResult] as the result string used in PGN.
+///
+/// # Example
+/// ```
+/// # use abbadingo::chessdefines::*;
+/// assert_eq!(format!("{}", GameResult::Draw), "1/2-1/2");
+/// assert_eq!(format!("{}", GameResult::Ongoing), "*");
+/// ```
+///
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_pgn())
+    }
+}
+
+/// Tentatively convert a &str with a PGN result string to the
+/// corresponding [GameResult].
+///
+/// For values different from "1-0", "0-1", "1/2-1/2" and "*"
+/// Err(AbbaDingoError::IllegalConversionToGameResult) is returned.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// assert_eq!(GameResult::try_from("0-1"), Ok(GameResult::BlackWins));
+/// assert_eq!(
+///     GameResult::try_from("0.5-0.5"),
+///     Err(AbbaDingoError::IllegalConversionToGameResult)
+/// );
+/// ```
+///
+impl TryFrom<&str> for GameResult {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Ongoing),
+            _ => Err(AbbaDingoError::IllegalConversionToGameResult),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for CastlingRights structure
+
+/// Display trait for [CastlingRights] structure.
+///
+/// Display the castling rights as in the castling availability field of FEN
+/// strings ("KQkq", "Kq", ..., "-" if no castling is allowed).
+///
+/// # Example
+/// ```
+/// # use abbadingo::chessdefines::*;
+/// assert_eq!(format!("{}", CastlingRights::all()), "KQkq");
+/// assert_eq!(format!("{}", CastlingRights::none()), "-");
+/// ```
+///
+impl fmt::Display for CastlingRights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for (flag, c) in [
+            (CastlingRights::WHITE_KINGSIDE, 'K'),
+            (CastlingRights::WHITE_QUEENSIDE, 'Q'),
+            (CastlingRights::BLACK_KINGSIDE, 'k'),
+            (CastlingRights::BLACK_QUEENSIDE, 'q'),
+        ] {
+            if self.flags & flag != 0 {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tentatively convert a &str with the castling availability field of a FEN
+/// string to the corresponding [CastlingRights].
+///
+/// The flags have to be in the "KQkq" order, without repetitions; "-" means
+/// no castling allowed. For other values
+/// Err(AbbaDingoError::IllegalConversionToCastlingRights) is returned.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// assert_eq!(CastlingRights::try_from("KQkq"), Ok(CastlingRights::all()));
+/// assert_eq!(CastlingRights::try_from("-"), Ok(CastlingRights::none()));
+/// assert_eq!(
+///     CastlingRights::try_from("kK"),
+///     Err(AbbaDingoError::IllegalConversionToCastlingRights)
+/// );
+/// ```
+///
+impl TryFrom<&str> for CastlingRights {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value == "-" {
+            return Ok(CastlingRights::none());
+        }
+        let err = AbbaDingoError::IllegalConversionToCastlingRights;
+        let mut flags = 0;
+        let mut last = 0;
+        for c in value.chars() {
+            let flag = match c {
+                'K' => CastlingRights::WHITE_KINGSIDE,
+                'Q' => CastlingRights::WHITE_QUEENSIDE,
+                'k' => CastlingRights::BLACK_KINGSIDE,
+                'q' => CastlingRights::BLACK_QUEENSIDE,
+                _ => return Err(err),
+            };
+            // The flags values are increasing in the "KQkq" order
+            if flag <= last {
+                return Err(err);
+            }
+            flags |= flag;
+            last = flag;
+        }
+        if flags == 0 {
+            return Err(err);
+        }
+        Ok(CastlingRights::from_bits(flags))
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn piece_chars_round_trip() {
+        for i in 0..NUM_PIECES_TYPES {
+            let p: ChessPiece = num::FromPrimitive::from_usize(i).unwrap();
+            for c in [ArmyColour::White, ArmyColour::Black] {
+                assert_eq!(ChessPiece::from_char(p.to_char(c)), Some((p, c)));
+            }
+        }
+        assert_eq!(ChessPiece::from_char('1'), None);
+    }
+
+    #[test]
+    fn army_colour_conversions() {
+        for c in [ArmyColour::White, ArmyColour::Black] {
+            let ch = format!("{}", c).chars().next().unwrap();
+            assert_eq!(ArmyColour::try_from(ch), Ok(c));
+            assert_eq!(!!c, c);
+            assert_ne!(c.opposite(), c);
+        }
+        assert_eq!(
+            ArmyColour::try_from('-'),
+            Err(AbbaDingoError::IllegalConversionToArmyColour)
+        );
+    }
+
+    #[test]
+    fn castling_rights_conversions() {
+        for f in 0..16 {
+            let cr = CastlingRights::from_bits(f);
+            let s = format!("{}", cr);
+            assert_eq!(CastlingRights::try_from(s.as_str()), Ok(cr));
+        }
+        for s in ["", "KK", "qk", "K-", "KQkqq", "x"] {
+            assert_eq!(
+                CastlingRights::try_from(s),
+                Err(AbbaDingoError::IllegalConversionToCastlingRights)
+            );
+        }
+    }
+
+    #[test]
+    fn castling_rights_flags() {
+        let mut cr = CastlingRights::all();
+        cr.remove_queenside(ArmyColour::Black);
+        assert!(cr.allows(ArmyColour::Black, CastlingSide::Kingside));
+        assert!(!cr.allows(ArmyColour::Black, CastlingSide::Queenside));
+        cr.remove_all(ArmyColour::White);
+        assert_eq!(cr.bits(), CastlingRights::BLACK_KINGSIDE);
+        cr.add(ArmyColour::White, CastlingSide::Queenside);
+        assert_eq!(format!("{}", cr), "Qk");
+        assert_eq!(CastlingRights::default(), CastlingRights::none());
+    }
+
+    #[test]
+    fn game_results() {
+        for r in [
+            GameResult::WhiteWins,
+            GameResult::BlackWins,
+            GameResult::Draw,
+            GameResult::Ongoing,
+        ] {
+            assert_eq!(GameResult::try_from(r.as_pgn()), Ok(r));
+        }
+        assert_eq!(
+            GameResult::win_for(ArmyColour::Black).winner(),
+            Some(ArmyColour::Black)
+        );
+        assert_eq!(GameResult::Draw.winner(), None);
+        assert!(GameResult::Draw.is_over());
+        assert!(!GameResult::Ongoing.is_over());
+    }
+
+    #[test]
+    fn sliders() {
+        assert!(ChessPiece::Queen.is_slider());
+        assert!(ChessPiece::Rook.is_slider());
+        assert!(ChessPiece::Bishop.is_slider());
+        assert!(!ChessPiece::Knight.is_slider());
+        assert!(!ChessPiece::King.is_slider());
+        assert!(!ChessPiece::Pawn.is_slider());
+    }
+}
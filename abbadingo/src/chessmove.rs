@@ -2,10 +2,15 @@
 //! and related methods implementation.
 //!
 
-use std::fmt;
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
 
 use crate::bbdefines::*;
+use crate::chessboard::ChessBoard;
 use crate::chessdefines::*;
+use crate::error::AbbaDingoError;
 
 pub const EMPTY_CHESSMOVE: u32 = 0;
 pub const INVALID_CHESSMOVE: u32 = 0x80_00_00_00;
@@ -39,10 +44,10 @@ const INVALID_CELL: u32 = 0x00000040;
 ///  # Examples:
 ///
 ///    - Pawn e2 to e3: `0 1000000 010100 001100 000 110 110 101` =
-///                     `0100 0000 0101 0000 1100 0001 1011 0101` = `0x4050C1B5`
+///      `0100 0000 0101 0000 1100 0001 1011 0101` = `0x4050C1B5`
 ///
 
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ChessMove {
     pub m: u32,
 }
@@ -148,6 +153,259 @@ impl ChessMove {
                         || (self.destination_cell() == Cell::C8))))
     }
 
+    /// Resolves a move in Standard Algebraic Notation (SAN) into the
+    /// corresponding legal [ChessMove] in the given position.
+    ///
+    /// The start [Cell] is found among the legal moves of the position, using
+    /// the file and/or rank disambiguation if present. The check and mate
+    /// indicators, the `!`/`?` suffixes and the optional ` e.p.` suffix of the
+    /// en-passant captures are accepted; the castling moves can be written both
+    /// with letters (`O-O`) and zeroes (`0-0`), the promotions both with and
+    /// without the `=`. If the move is not legal in the position, or is
+    /// ambiguous, Err(AbbaDingoError::IllegalSanMove) is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::*;
+    /// # use abbadingo::error::AbbaDingoError;
+    /// # use abbadingo::fenrecord::FenRecord;
+    /// let board = FenRecord::try_from("r3k3/7P/8/3pP3/8/8/8/1N2KN2 w q d6 0 1").unwrap().board();
+    /// let m = ChessMove::from_san("Nbd2", &board).unwrap();
+    /// assert_eq!((m.start_cell(), m.destination_cell()), (Cell::B1, Cell::D2));
+    /// let m = ChessMove::from_san("exd6 e.p.", &board).unwrap();
+    /// assert_eq!(m.taken_piece(), Some(ChessPiece::Pawn));
+    /// let m = ChessMove::from_san("h8=Q+", &board).unwrap();
+    /// assert_eq!(m.promoted_piece(), Some(ChessPiece::Queen));
+    /// assert_eq!(ChessMove::from_san("Nd2", &board), Err(AbbaDingoError::IllegalSanMove));
+    /// assert_eq!(ChessMove::from_san("O-O-O", &board), Err(AbbaDingoError::IllegalSanMove));
+    /// ```
+    pub fn from_san(san: &str, board: &ChessBoard) -> Result<ChessMove, AbbaDingoError> {
+        let err = || AbbaDingoError::IllegalSanMove;
+        let suffixes = |c| "+#!?".contains(c);
+        let san = san.trim_end_matches(suffixes);
+        let en_passant = san.ends_with("e.p.");
+        let san = san
+            .trim_end_matches("e.p.")
+            .trim_end()
+            .trim_end_matches(suffixes);
+        let legal_moves = board.legal_moves();
+        let castling_dest = match san {
+            "O-O" | "0-0" => Some(File::FileG),
+            "O-O-O" | "0-0-0" => Some(File::FileC),
+            _ => None,
+        };
+        if let Some(f) = castling_dest {
+            return legal_moves
+                .into_iter()
+                .find(|m| m.is_a_castling_move() && file(m.destination_cell()) == f)
+                .ok_or_else(err);
+        }
+        if !san.is_ascii() {
+            return Err(err());
+        }
+
+        let (piece, san) = match san.get(..1).map(ChessPiece::try_from) {
+            Some(Ok(p)) => (p, &san[1..]),
+            _ => (ChessPiece::Pawn, san),
+        };
+        let (san, promoted) = match san.find('=') {
+            Some(ndx) => (
+                &san[..ndx],
+                Some(ChessPiece::try_from(&san[ndx + 1..]).map_err(|_| err())?),
+            ),
+            None => match san
+                .len()
+                .checked_sub(1)
+                .map(|ndx| ChessPiece::try_from(&san[ndx..]))
+            {
+                Some(Ok(p)) if piece == ChessPiece::Pawn => (&san[..san.len() - 1], Some(p)),
+                _ => (san, None),
+            },
+        };
+        if san.len() < 2 {
+            return Err(err());
+        }
+        let dest = Cell::try_from(&san[san.len() - 2..]).map_err(|_| err())?;
+        let disambiguation = san[..san.len() - 2].trim_end_matches('x');
+        let (from_file, from_rank) = match disambiguation.len() {
+            0 => (None, None),
+            1 => match File::try_from(disambiguation) {
+                Ok(f) => (Some(f), None),
+                Err(_) => (
+                    None,
+                    Some(Rank::try_from(disambiguation).map_err(|_| err())?),
+                ),
+            },
+            2 => {
+                let c = Cell::try_from(disambiguation).map_err(|_| err())?;
+                (Some(file(c)), Some(rank(c)))
+            }
+            _ => return Err(err()),
+        };
+
+        let mut candidates = legal_moves.into_iter().filter(|m| {
+            m.moved_piece() == piece
+                && m.destination_cell() == dest
+                && m.promoted_piece() == promoted
+                && !m.is_a_castling_move()
+                && from_file.is_none_or(|f| file(m.start_cell()) == f)
+                && from_rank.is_none_or(|r| rank(m.start_cell()) == r)
+                && (!en_passant || (piece == ChessPiece::Pawn && board.en_passant == Some(dest)))
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            _ => Err(err()),
+        }
+    }
+
+    /// Returns the move in Standard Algebraic Notation (SAN), given the position
+    /// where it is played (the move shall be legal in the position).
+    ///
+    /// The piece letter is omitted for pawns, whose captures start with the file
+    /// of the start [Cell]. When another piece of the same type can reach the
+    /// destination, the start file, rank or [Cell] is added to disambiguate.
+    /// The promotions end with `=` and the piece letter, and the moves giving
+    /// check or checkmate with `+` or `#`.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::*;
+    /// # use abbadingo::fenrecord::FenRecord;
+    /// let board = FenRecord::try_from("r3k3/7P/8/3pP3/8/8/8/1N2KN2 w q d6 0 1").unwrap().board();
+    /// let nbd2 = ChessMove::new(ChessPiece::Knight, Cell::B1, Cell::D2, None, None);
+    /// assert_eq!(nbd2.to_san(&board), "Nbd2");
+    /// let exd6 = ChessMove::new(ChessPiece::Pawn, Cell::E5, Cell::D6, Some(ChessPiece::Pawn), None);
+    /// assert_eq!(exd6.to_san(&board), "exd6");
+    /// let h8q = ChessMove::new(ChessPiece::Pawn, Cell::H7, Cell::H8, None, Some(ChessPiece::Queen));
+    /// assert_eq!(h8q.to_san(&board), "h8=Q+");
+    /// ```
+    pub fn to_san(&self, board: &ChessBoard) -> String {
+        let (start, dest) = (self.start_cell(), self.destination_cell());
+        let mut san = String::new();
+        if self.is_a_castling_move() {
+            san.push_str(if file(dest) == File::FileG {
+                "O-O"
+            } else {
+                "O-O-O"
+            });
+        } else {
+            let piece = self.moved_piece();
+            if piece == ChessPiece::Pawn {
+                if self.taken_piece().is_some() {
+                    san.push_str(&Into::<String>::into(file(start)));
+                }
+            } else {
+                san.push(piece.to_char(ArmyColour::White));
+                // The other pieces of the same type that can reach the destination
+                let mut others = board.pieces_that_can_move_to(piece, dest);
+                others.reset_cell(start);
+                if !others.is_empty() {
+                    if others.state & file_mask(start) == EMPTY_STATE {
+                        san.push_str(&Into::<String>::into(file(start)));
+                    } else if others.state & rank_mask(start) == EMPTY_STATE {
+                        san.push_str(&Into::<String>::into(rank(start)));
+                    } else {
+                        san.push_str(&Into::<String>::into(start));
+                    }
+                }
+            }
+            if self.taken_piece().is_some() {
+                san.push('x');
+            }
+            san.push_str(&Into::<String>::into(dest));
+            if let Some(p) = self.promoted_piece() {
+                san.push('=');
+                san.push(p.to_char(ArmyColour::White));
+            }
+        }
+        let after = board.make_move_copy(*self);
+        if after.is_in_check() {
+            san.push(if after.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        san
+    }
+
+    /// Returns the move in the long algebraic notation used by the UCI protocol:
+    /// start and destination [Cell]s, followed by the lowercase letter of the
+    /// promoted piece, if any. Castling moves are King moves (e.g. `e1g1`).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::*;
+    /// assert_eq!(ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None).to_uci(), "e2e4");
+    /// assert_eq!(
+    ///     ChessMove::new(ChessPiece::Pawn, Cell::E7, Cell::E8, None, Some(ChessPiece::Queen)).to_uci(),
+    ///     "e7e8q"
+    /// );
+    /// ```
+    pub fn to_uci(&self) -> String {
+        let mut uci: String = self.start_cell().into();
+        uci.push_str(&Into::<String>::into(self.destination_cell()));
+        if let Some(p) = self.promoted_piece() {
+            uci.push(p.to_char(ArmyColour::Black));
+        }
+        uci
+    }
+
+    /// Resolves a move in the long algebraic notation used by the UCI protocol
+    /// (see [to_uci()](ChessMove::to_uci)) into the corresponding legal
+    /// [ChessMove] in the given position.
+    ///
+    /// If the string is malformed or the move is not legal in the position
+    /// (the null move `0000` included), Err(AbbaDingoError::IllegalUciMove)
+    /// is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::*;
+    /// # use abbadingo::error::AbbaDingoError;
+    /// let board = ChessBoard::initial();
+    /// let m = ChessMove::try_from_uci("g1f3", &board).unwrap();
+    /// assert_eq!(m.moved_piece(), ChessPiece::Knight);
+    /// assert_eq!(ChessMove::try_from_uci("e2e5", &board), Err(AbbaDingoError::IllegalUciMove));
+    /// assert_eq!(ChessMove::try_from_uci("0000", &board), Err(AbbaDingoError::IllegalUciMove));
+    /// ```
+    pub fn try_from_uci(uci: &str, board: &ChessBoard) -> Result<ChessMove, AbbaDingoError> {
+        let err = || AbbaDingoError::IllegalUciMove;
+        if !uci.is_ascii() || (uci.len() != 4 && uci.len() != 5) {
+            return Err(err());
+        }
+        let start = Cell::try_from(&uci[0..2]).map_err(|_| err())?;
+        let dest = Cell::try_from(&uci[2..4]).map_err(|_| err())?;
+        let promoted = match &uci[4..] {
+            "" => None,
+            "q" => Some(ChessPiece::Queen),
+            "r" => Some(ChessPiece::Rook),
+            "b" => Some(ChessPiece::Bishop),
+            "n" => Some(ChessPiece::Knight),
+            _ => return Err(err()),
+        };
+        board
+            .legal_moves()
+            .into_iter()
+            .find(|m| {
+                m.start_cell() == start
+                    && m.destination_cell() == dest
+                    && m.promoted_piece() == promoted
+            })
+            .ok_or_else(err)
+    }
+
     // ---------------------------------------------------------------------------
     // PRIVATE METHODS
     // ---------------------------------------------------------------------------
@@ -174,6 +432,55 @@ impl ChessMove {
 // ----------------------------------------------------------------------------
 // Traits implementation for ChessMove structure
 
+/// Ord trait for [ChessMove] structure.
+///
+/// The moves are sorted using a canonical ordering: by start [Cell], then by
+/// destination [Cell] and then by promoted piece (moves without promotion first,
+/// then promotions in [ChessPiece] order: Queen, Bishop, Knight, Rook). Moves
+/// flagged as invalid follow all the valid moves. Moves that are equal under these
+/// criteria are sorted using the internal representation, so the ordering is
+/// total and consistent with equality.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::{Cell};
+/// # use abbadingo::chessdefines::{ChessPiece};
+/// # use abbadingo::chessmove::*;
+/// let mut moves = vec![
+///     ChessMove::new(ChessPiece::Knight, Cell::G1, Cell::F3, None, None),
+///     ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None),
+///     ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E3, None, None),
+/// ];
+/// moves.sort();
+/// // g1 (cell index 6) comes before e2 (cell index 12)
+/// assert_eq!(moves[0].start_cell(), Cell::G1);
+/// assert_eq!(moves[1].destination_cell(), Cell::E3);
+/// assert_eq!(moves[2].destination_cell(), Cell::E4);
+/// ```
+///
+impl Ord for ChessMove {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |cm: &ChessMove| {
+            (
+                cm.m & INVALID_CHESSMOVE,
+                cm.start_cell(),
+                cm.destination_cell(),
+                cm.promoted_piece(),
+                cm.m,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+/// PartialOrd trait for [ChessMove] structure, consistent with [Ord].
+///
+impl PartialOrd for ChessMove {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 /// Display trait for [ChessMove] structure.
 ///
 /// Display the [ChessMove] in an human-readable format.
@@ -213,6 +520,114 @@ impl fmt::Display for ChessMove {
 mod tests {
 
     use super::*;
+    use crate::fenrecord::FenRecord;
+
+    #[test]
+    fn san_moves_are_generated() {
+        let board = FenRecord::try_from("4k3/1P6/8/8/8/8/4K3/R6R w - - 0 1")
+            .unwrap()
+            .board();
+        for san in ["Rad1", "Rhd1", "Ra5", "b8=N", "b8=Q+", "Rh8+", "Kd3"] {
+            assert_eq!(
+                ChessMove::from_san(san, &board).unwrap().to_san(&board),
+                san
+            );
+        }
+        let board = FenRecord::try_from("4k3/8/8/1N6/8/8/4K3/1N1N4 w - - 0 1")
+            .unwrap()
+            .board();
+        for san in ["Nb1c3", "N5c3", "Ndc3", "Nd2"] {
+            assert_eq!(
+                ChessMove::from_san(san, &board).unwrap().to_san(&board),
+                san
+            );
+        }
+    }
+
+    #[test]
+    fn uci_moves_round_trip() {
+        let board = FenRecord::try_from("r3k2r/1P6/8/3pP3/8/8/8/4K3 w kq d6 0 1")
+            .unwrap()
+            .board();
+        for m in board.legal_moves() {
+            assert_eq!(ChessMove::try_from_uci(&m.to_uci(), &board), Ok(m));
+        }
+        let m = ChessMove::try_from_uci("b7a8n", &board).unwrap();
+        assert_eq!(m.taken_piece(), Some(ChessPiece::Rook));
+        assert_eq!(m.promoted_piece(), Some(ChessPiece::Knight));
+        let m = ChessMove::try_from_uci("e5d6", &board).unwrap();
+        assert_eq!(m.taken_piece(), Some(ChessPiece::Pawn));
+        for uci in ["b7b8", "b7b8k", "b7b8Q", "e1g1", "e1e3", "e5e6x", "e5"] {
+            assert_eq!(
+                ChessMove::try_from_uci(uci, &board),
+                Err(AbbaDingoError::IllegalUciMove),
+                "{}",
+                uci
+            );
+        }
+        let board = board.make_move_copy(ChessMove::try_from_uci("e1d1", &board).unwrap());
+        assert_eq!(
+            ChessMove::try_from_uci("e8g8", &board).unwrap().to_uci(),
+            "e8g8"
+        );
+        assert!(ChessMove::try_from_uci("e8g8", &board)
+            .unwrap()
+            .is_a_castling_move());
+    }
+
+    #[test]
+    fn san_moves_are_disambiguated() {
+        let board = FenRecord::try_from("4k3/1P6/8/8/8/8/4K3/R6R w - - 0 1")
+            .unwrap()
+            .board();
+        assert_eq!(
+            ChessMove::from_san("Rd1", &board),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+        assert_eq!(
+            ChessMove::from_san("Rad1", &board).unwrap().start_cell(),
+            Cell::A1
+        );
+        assert_eq!(
+            ChessMove::from_san("Rh1d1", &board).unwrap().start_cell(),
+            Cell::H1
+        );
+        assert_eq!(
+            ChessMove::from_san("R1a5", &board).unwrap().start_cell(),
+            Cell::A1
+        );
+        assert_eq!(
+            ChessMove::from_san("b8=N+", &board)
+                .unwrap()
+                .promoted_piece(),
+            Some(ChessPiece::Knight)
+        );
+        assert_eq!(
+            ChessMove::from_san("b8Q", &board).unwrap().promoted_piece(),
+            Some(ChessPiece::Queen)
+        );
+        assert_eq!(
+            ChessMove::from_san("b8", &board),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+        assert_eq!(
+            ChessMove::from_san("O-O", &board),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+        let board = FenRecord::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1")
+            .unwrap()
+            .board();
+        assert_eq!(
+            ChessMove::from_san("dxe3e.p.", &board)
+                .unwrap()
+                .taken_piece(),
+            Some(ChessPiece::Pawn)
+        );
+        assert_eq!(
+            ChessMove::from_san("Kd7 e.p.", &board),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+    }
 
     #[test]
     fn default_value_for_chess_move_is_empty_move() {
@@ -628,6 +1043,66 @@ mod tests {
         assert!(!cm.is_a_castling_move());
     }
 
+    // --- Ordering testing
+    #[test]
+    fn chess_moves_canonical_ordering() {
+        let promotions = [
+            ChessMove::new(
+                ChessPiece::Pawn,
+                Cell::B7,
+                Cell::B8,
+                None,
+                Some(ChessPiece::Rook),
+            ),
+            ChessMove::new(
+                ChessPiece::Pawn,
+                Cell::B7,
+                Cell::B8,
+                None,
+                Some(ChessPiece::Queen),
+            ),
+            ChessMove::new(
+                ChessPiece::Pawn,
+                Cell::B7,
+                Cell::B8,
+                None,
+                Some(ChessPiece::Knight),
+            ),
+        ];
+        let mut sorted = promotions;
+        sorted.sort();
+        assert_eq!(sorted, [promotions[1], promotions[2], promotions[0]]);
+
+        let invalid = ChessMove {
+            m: INVALID_CHESSMOVE,
+        };
+        let last = ChessMove::new(ChessPiece::King, Cell::H8, Cell::G8, None, None);
+        assert!(invalid > last);
+        assert!(ChessMove::new(ChessPiece::Rook, Cell::A1, Cell::A8, None, None) < last);
+        // Same cells, different taken pieces: still totally ordered
+        let a = ChessMove::new(ChessPiece::Rook, Cell::A1, Cell::A8, None, None);
+        let b = ChessMove::new(
+            ChessPiece::Rook,
+            Cell::A1,
+            Cell::A8,
+            Some(ChessPiece::Rook),
+            None,
+        );
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(a.cmp(&a), Ordering::Equal);
+    }
+
+    #[test]
+    fn cells_files_and_ranks_ordering() {
+        assert!(Cell::A1 < Cell::B1);
+        assert!(Cell::H1 < Cell::A2);
+        assert!(File::FileA < File::FileH);
+        assert!(Rank::Rank1 < Rank::Rank8);
+        let mut cells = [Cell::E4, Cell::A8, Cell::D1];
+        cells.sort();
+        assert_eq!(cells, [Cell::D1, Cell::E4, Cell::A8]);
+    }
+
     //// Test print function
     //fn TestPrintFunction)
     //{
@@ -0,0 +1,543 @@
+//! Definition of the [TimeControl] used in a chess game and of the [ChessClock]
+//! that keeps track of the time used by the two armies.
+//!
+//! The [ChessClock] does not read the time by itself: the time spent for each
+//! move is provided by the user when the clock is "pressed" at the end of the
+//! move, so that the module can be used with any time source (and also in
+//! `no_std` environments).
+//!
+//! # Example
+//! ```
+//! # use core::time::Duration;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::clock::*;
+//! // 3 minutes + 2 seconds increment per move
+//! let mut clock = ChessClock::new(TimeControl::increment(
+//!     Duration::from_secs(180),
+//!     Duration::from_secs(2),
+//! ));
+//! assert!(clock.press(Duration::from_secs(10)));
+//! assert_eq!(clock.remaining(ArmyColour::White), Duration::from_secs(172));
+//! assert_eq!(clock.to_move(), ArmyColour::Black);
+//! ```
+//!
+//! The module also provides a simple time-usage model, [suggest_time_for_move],
+//! to decide how much time to spend for a move.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::chessdefines::{ArmyColour, GameResult, TerminationReason};
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// Number of moves expected to be still played at the beginning of the game,
+// and the minimum number of moves always expected to be still played.
+const MAX_MOVES_TO_GO: u32 = 40;
+const MIN_MOVES_TO_GO: u32 = 20;
+
+// The suggested time never exceeds this fraction of the remaining time.
+const MAX_TIME_FRACTION: u32 = 3;
+
+// Limits of the complexity hint.
+const MIN_COMPLEXITY: f64 = 0.25;
+const MAX_COMPLEXITY: f64 = 4.0;
+
+/// The time bonus given to a player for each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBonus {
+    /// No bonus.
+    None,
+    /// Fischer increment: the given time is added after each move.
+    Increment(Duration),
+    /// Simple (US) delay: the first part of each move, up to the
+    /// given time, is not charged to the player.
+    Delay(Duration),
+}
+
+/// A stage of a [TimeControl].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControlStage {
+    /// The number of moves to be played in the stage, `None` for the
+    /// rest of the game.
+    pub moves: Option<u32>,
+    /// The time added to the clock at the beginning of the stage.
+    pub time: Duration,
+    /// The bonus given for each move played in the stage.
+    pub bonus: TimeBonus,
+}
+
+/// The time control of a chess game, made by one or more [TimeControlStage]s.
+///
+/// The last stage is used for the rest of the game, whatever its number of moves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    stages: Vec<TimeControlStage>,
+}
+
+/// A chess clock, tracking the remaining time of the two armies according
+/// to a [TimeControl].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChessClock {
+    time_control: TimeControl,
+    remaining: [Duration; 2],
+    stage: [usize; 2],
+    moves_in_stage: [u32; 2],
+    to_move: ArmyColour,
+    flagged: Option<ArmyColour>,
+    move_times: Vec<Duration>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl TimeControl {
+    /// Creates a sudden death time control: the whole game has to be played
+    /// in the given time.
+    pub fn sudden_death(time: Duration) -> TimeControl {
+        TimeControl::multi_stage(vec![TimeControlStage {
+            moves: None,
+            time,
+            bonus: TimeBonus::None,
+        }])
+    }
+
+    /// Creates a time control with a base time and a Fischer increment per move.
+    pub fn increment(time: Duration, increment: Duration) -> TimeControl {
+        TimeControl::multi_stage(vec![TimeControlStage {
+            moves: None,
+            time,
+            bonus: TimeBonus::Increment(increment),
+        }])
+    }
+
+    /// Creates a time control with a base time and a simple delay per move.
+    pub fn delay(time: Duration, delay: Duration) -> TimeControl {
+        TimeControl::multi_stage(vec![TimeControlStage {
+            moves: None,
+            time,
+            bonus: TimeBonus::Delay(delay),
+        }])
+    }
+
+    /// Creates a time control with several stages, e.g. 40 moves in 90 minutes
+    /// followed by 30 minutes for the rest of the game.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stages` is empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use core::time::Duration;
+    /// # use abbadingo::clock::*;
+    /// let tc = TimeControl::multi_stage(vec![
+    ///     TimeControlStage {
+    ///         moves: Some(40),
+    ///         time: Duration::from_secs(90 * 60),
+    ///         bonus: TimeBonus::Increment(Duration::from_secs(30)),
+    ///     },
+    ///     TimeControlStage {
+    ///         moves: None,
+    ///         time: Duration::from_secs(30 * 60),
+    ///         bonus: TimeBonus::Increment(Duration::from_secs(30)),
+    ///     },
+    /// ]);
+    /// assert_eq!(tc.stages().len(), 2);
+    /// ```
+    pub fn multi_stage(stages: Vec<TimeControlStage>) -> TimeControl {
+        assert!(
+            !stages.is_empty(),
+            "a time control needs at least one stage"
+        );
+        TimeControl { stages }
+    }
+
+    /// Returns the stages of the time control.
+    pub fn stages(&self) -> &[TimeControlStage] {
+        &self.stages
+    }
+}
+
+impl ChessClock {
+    /// Creates a new clock for the given [TimeControl], with the White army to move.
+    pub fn new(time_control: TimeControl) -> ChessClock {
+        let initial_time = time_control.stages[0].time;
+        ChessClock {
+            time_control,
+            remaining: [initial_time; 2],
+            stage: [0; 2],
+            moves_in_stage: [0; 2],
+            to_move: ArmyColour::White,
+            flagged: None,
+            move_times: Vec::new(),
+        }
+    }
+
+    /// Returns the [TimeControl] of the clock.
+    pub fn time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+
+    /// Returns the time remaining to the given army.
+    pub fn remaining(&self, colour: ArmyColour) -> Duration {
+        self.remaining[colour as usize]
+    }
+
+    /// Returns the army whose clock is running.
+    pub fn to_move(&self) -> ArmyColour {
+        self.to_move
+    }
+
+    /// Returns the army that has run out of time, if any.
+    pub fn flagged(&self) -> Option<ArmyColour> {
+        self.flagged
+    }
+
+    /// Returns the result of the game as far as the clock is concerned: the
+    /// game is lost by the flagged army, if any, by [TerminationReason::Timeout].
+    ///
+    /// The clock does not know the position, so it cannot detect the cases
+    /// where the opponent has insufficient material to win and the game is drawn.
+    ///
+    /// # Example
+    /// ```
+    /// # use core::time::Duration;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::clock::*;
+    /// let mut clock = ChessClock::new(TimeControl::sudden_death(Duration::from_secs(60)));
+    /// assert_eq!(clock.result(), None);
+    /// clock.press(Duration::from_secs(61));
+    /// assert_eq!(clock.result(), Some((GameResult::BlackWins, TerminationReason::Timeout)));
+    /// ```
+    pub fn result(&self) -> Option<(GameResult, TerminationReason)> {
+        self.flagged
+            .map(|c| (GameResult::win_for(!c), TerminationReason::Timeout))
+    }
+
+    /// Returns the time spent for each move played so far, in order
+    /// (White and Black moves alternated).
+    pub fn move_times(&self) -> &[Duration] {
+        &self.move_times
+    }
+
+    /// Suggests the time to spend for the current move of the army to move
+    /// (see [suggest_time_for_move]).
+    ///
+    /// In a stage with a limited number of moves, the remaining time is
+    /// divided among the moves still to be played in the stage, if fewer
+    /// than the expected ones. Once the moves of the last stage have been
+    /// played, the expected moves to go are used again.
+    ///
+    /// # Example
+    /// ```
+    /// # use core::time::Duration;
+    /// # use abbadingo::clock::*;
+    /// let clock = ChessClock::new(TimeControl::multi_stage(vec![TimeControlStage {
+    ///     moves: Some(10),
+    ///     time: Duration::from_secs(100),
+    ///     bonus: TimeBonus::None,
+    /// }]));
+    /// assert_eq!(clock.suggest_time_for_move(1.0), Duration::from_secs(10));
+    /// ```
+    pub fn suggest_time_for_move(&self, complexity_hint: f64) -> Duration {
+        let c = self.to_move as usize;
+        let stage = self.time_control.stages[self.stage[c]];
+        let bonus = match stage.bonus {
+            TimeBonus::None => Duration::ZERO,
+            TimeBonus::Increment(d) | TimeBonus::Delay(d) => d,
+        };
+        let move_number = self.move_times.len() as u32 / 2 + 1;
+        let mut moves_to_go = expected_moves_to_go(move_number);
+        if let Some(m) = stage.moves {
+            let stage_moves_to_go = m.saturating_sub(self.moves_in_stage[c]);
+            if stage_moves_to_go > 0 {
+                moves_to_go = moves_to_go.min(stage_moves_to_go);
+            }
+        }
+        suggest_time(self.remaining[c], bonus, moves_to_go, complexity_hint)
+    }
+
+    /// Presses the clock at the end of a move of the army to move, that
+    /// spent `elapsed` time for the move.
+    ///
+    /// The time (net of the delay, if any) is charged to the army to move,
+    /// the increment is added and, if the stage is completed, the time of the
+    /// next stage is added as well; then the clock of the other army starts.
+    ///
+    /// If the elapsed time exceeds the remaining time the army to move is
+    /// flagged, its clock is stopped and `false` is returned; after a flag
+    /// the clock is not updated anymore.
+    ///
+    /// # Example
+    /// ```
+    /// # use core::time::Duration;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::clock::*;
+    /// let mut clock = ChessClock::new(TimeControl::delay(
+    ///     Duration::from_secs(60),
+    ///     Duration::from_secs(5),
+    /// ));
+    /// assert!(clock.press(Duration::from_secs(3)));
+    /// assert_eq!(clock.remaining(ArmyColour::White), Duration::from_secs(60));
+    /// assert!(!clock.press(Duration::from_secs(70)));
+    /// assert_eq!(clock.flagged(), Some(ArmyColour::Black));
+    /// assert_eq!(clock.remaining(ArmyColour::Black), Duration::ZERO);
+    /// ```
+    pub fn press(&mut self, elapsed: Duration) -> bool {
+        if self.flagged.is_some() {
+            return false;
+        }
+        let c = self.to_move as usize;
+        let stage = self.time_control.stages[self.stage[c]];
+        let charged = match stage.bonus {
+            TimeBonus::Delay(d) => elapsed.saturating_sub(d),
+            _ => elapsed,
+        };
+        self.move_times.push(elapsed);
+        if charged > self.remaining[c] {
+            self.remaining[c] = Duration::ZERO;
+            self.flagged = Some(self.to_move);
+            return false;
+        }
+        self.remaining[c] -= charged;
+        if let TimeBonus::Increment(i) = stage.bonus {
+            self.remaining[c] += i;
+        }
+        self.moves_in_stage[c] += 1;
+        if stage.moves == Some(self.moves_in_stage[c])
+            && self.stage[c] + 1 < self.time_control.stages.len()
+        {
+            self.stage[c] += 1;
+            self.moves_in_stage[c] = 0;
+            self.remaining[c] += self.time_control.stages[self.stage[c]].time;
+        }
+        self.to_move = !self.to_move;
+        true
+    }
+}
+
+/// Suggests the time to spend for a move, given the remaining time, the
+/// bonus per move (increment or delay), the number of the move in the game
+/// (starting from 1) and a complexity hint.
+///
+/// The remaining time is divided among the moves expected to be still played
+/// (from 40 at the beginning of the game down to 20 after move 40), adding
+/// most of the bonus. The result is then scaled by the complexity hint (1.0
+/// for a normal position, greater for complex positions, smaller for simple
+/// ones or obvious moves; the hint is clamped between 0.25 and 4.0, and a
+/// non-finite hint is taken as 1.0), and it is anyway limited to one third
+/// of the remaining time.
+///
+/// # Example
+/// ```
+/// # use core::time::Duration;
+/// # use abbadingo::clock::*;
+/// let t = suggest_time_for_move(Duration::from_secs(300), Duration::ZERO, 1, 1.0);
+/// assert_eq!(t, Duration::from_millis(7500));
+/// let t = suggest_time_for_move(Duration::from_secs(300), Duration::from_secs(2), 1, 2.0);
+/// assert_eq!(t, Duration::from_secs(18));
+/// // Never more than a third of the remaining time
+/// let t = suggest_time_for_move(Duration::from_secs(3), Duration::from_secs(10), 50, 4.0);
+/// assert_eq!(t, Duration::from_secs(1));
+/// ```
+pub fn suggest_time_for_move(
+    remaining: Duration,
+    increment: Duration,
+    move_number: u32,
+    complexity_hint: f64,
+) -> Duration {
+    suggest_time(
+        remaining,
+        increment,
+        expected_moves_to_go(move_number),
+        complexity_hint,
+    )
+}
+
+/// Returns the number of moves expected to be still played in the game
+/// at the given move number.
+///
+fn expected_moves_to_go(move_number: u32) -> u32 {
+    MAX_MOVES_TO_GO
+        .saturating_sub(move_number.saturating_sub(1) / 2)
+        .max(MIN_MOVES_TO_GO)
+}
+
+/// Implementation of the time-usage model with an explicit number of moves
+/// to go (see [suggest_time_for_move]).
+///
+fn suggest_time(
+    remaining: Duration,
+    increment: Duration,
+    moves_to_go: u32,
+    complexity_hint: f64,
+) -> Duration {
+    let base = remaining / moves_to_go.max(1) + increment * 3 / 4;
+    let complexity = if complexity_hint.is_finite() {
+        complexity_hint.clamp(MIN_COMPLEXITY, MAX_COMPLEXITY)
+    } else {
+        1.0
+    };
+    base.mul_f64(complexity).min(remaining / MAX_TIME_FRACTION)
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn sudden_death_clock() {
+        let mut clock = ChessClock::new(TimeControl::sudden_death(secs(300)));
+        assert_eq!(clock.to_move(), ArmyColour::White);
+        assert!(clock.press(secs(10)));
+        assert!(clock.press(secs(20)));
+        assert!(clock.press(secs(30)));
+        assert_eq!(clock.remaining(ArmyColour::White), secs(260));
+        assert_eq!(clock.remaining(ArmyColour::Black), secs(280));
+        assert_eq!(clock.to_move(), ArmyColour::Black);
+        assert_eq!(clock.move_times(), &[secs(10), secs(20), secs(30)]);
+        assert_eq!(clock.flagged(), None);
+    }
+
+    #[test]
+    fn delay_is_not_charged() {
+        let mut clock = ChessClock::new(TimeControl::delay(secs(60), secs(5)));
+        assert!(clock.press(secs(5)));
+        assert!(clock.press(secs(8)));
+        assert_eq!(clock.remaining(ArmyColour::White), secs(60));
+        assert_eq!(clock.remaining(ArmyColour::Black), secs(57));
+    }
+
+    #[test]
+    fn multi_stage_adds_time_at_the_end_of_the_stage() {
+        let mut clock = ChessClock::new(TimeControl::multi_stage(vec![
+            TimeControlStage {
+                moves: Some(2),
+                time: secs(100),
+                bonus: TimeBonus::None,
+            },
+            TimeControlStage {
+                moves: None,
+                time: secs(50),
+                bonus: TimeBonus::Increment(secs(10)),
+            },
+        ]));
+        for _ in 0..4 {
+            assert!(clock.press(secs(10)));
+        }
+        assert_eq!(clock.remaining(ArmyColour::White), secs(130));
+        assert_eq!(clock.remaining(ArmyColour::Black), secs(130));
+        // Second stage: increment applies
+        assert!(clock.press(secs(30)));
+        assert_eq!(clock.remaining(ArmyColour::White), secs(110));
+    }
+
+    #[test]
+    fn flagged_clock_is_stopped() {
+        let mut clock = ChessClock::new(TimeControl::increment(secs(10), secs(1)));
+        assert!(!clock.press(secs(11)));
+        assert_eq!(clock.flagged(), Some(ArmyColour::White));
+        assert_eq!(clock.remaining(ArmyColour::White), Duration::ZERO);
+        assert_eq!(clock.to_move(), ArmyColour::White);
+        assert!(!clock.press(secs(1)));
+        assert_eq!(clock.remaining(ArmyColour::Black), secs(10));
+        assert_eq!(clock.move_times().len(), 1);
+    }
+
+    #[test]
+    fn suggested_time_decreases_with_the_moves_to_go() {
+        let early = suggest_time_for_move(secs(600), Duration::ZERO, 1, 1.0);
+        let late = suggest_time_for_move(secs(600), Duration::ZERO, 60, 1.0);
+        assert_eq!(early, secs(15));
+        assert_eq!(late, secs(30));
+        // Complexity hint is clamped
+        assert_eq!(
+            suggest_time_for_move(secs(600), Duration::ZERO, 1, 100.0),
+            secs(60)
+        );
+        assert_eq!(
+            suggest_time_for_move(secs(600), Duration::ZERO, 1, 0.0),
+            Duration::from_millis(3750)
+        );
+        assert_eq!(
+            suggest_time_for_move(Duration::ZERO, secs(5), 1, 1.0),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn clock_suggested_time_uses_the_current_stage() {
+        let mut clock = ChessClock::new(TimeControl::multi_stage(vec![
+            TimeControlStage {
+                moves: Some(2),
+                time: secs(100),
+                bonus: TimeBonus::None,
+            },
+            TimeControlStage {
+                moves: None,
+                time: secs(50),
+                bonus: TimeBonus::Increment(secs(4)),
+            },
+        ]));
+        // Two moves to go in the first stage, limited to a third of the time
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(100) / 3);
+        assert!(clock.press(secs(10)));
+        assert!(clock.press(secs(10)));
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(90) / 3);
+        assert!(clock.press(secs(10)));
+        assert!(clock.press(secs(10)));
+        // Second stage: 130 seconds, 39 moves to go, 3/4 of the increment
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(130) / 39 + secs(3));
+    }
+
+    #[test]
+    fn clock_suggested_time_after_the_last_stage() {
+        let mut clock = ChessClock::new(TimeControl::multi_stage(vec![TimeControlStage {
+            moves: Some(1),
+            time: secs(90),
+            bonus: TimeBonus::None,
+        }]));
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(30));
+        assert!(clock.press(secs(10)));
+        assert!(clock.press(secs(10)));
+        // The moves of the stage have been played: 40 moves to go
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(80) / 40);
+        assert!(clock.press(secs(10)));
+        assert!(clock.press(secs(10)));
+        assert_eq!(clock.suggest_time_for_move(1.0), secs(70) / 39);
+    }
+
+    #[test]
+    fn non_finite_complexity_hints_are_ignored() {
+        let normal = suggest_time_for_move(secs(300), Duration::ZERO, 1, 1.0);
+        for hint in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                suggest_time_for_move(secs(300), Duration::ZERO, 1, hint),
+                normal
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn time_control_without_stages_panics() {
+        TimeControl::multi_stage(vec![]);
+    }
+}
@@ -0,0 +1,554 @@
+//! Distance-to-mate (DTM) tables for the three-men chess endings.
+//!
+//! A [DtmTable] stores, for every legal position of an ending with the two
+//! kings and a single piece of the strong side (KQK, KRK and KPK, see
+//! [DtmEnding]), the number of plies to checkmate with perfect play of both
+//! sides, or that the position is a draw. The tables are generated by
+//! retrograde analysis: starting from the checkmates, at each pass the
+//! positions where the strong side can reach a position won in `n - 1` plies,
+//! and the ones where the weak side cannot avoid them, are marked as won in
+//! `n` plies.
+//!
+//! The KPK table depends on the KQK and KRK tables (the pawn can promote to a
+//! Queen or to a Rook), that are generated as well by [DtmTable::generate].
+//!
+//! A table can be saved and loaded (see [DtmTable::encode] and
+//! [DtmTable::decode]), to avoid the generation cost.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::dtm::*;
+//! let krk = DtmTable::generate(DtmEnding::Krk);
+//! let (w, b) = (ArmyColour::White, ArmyColour::Black);
+//! // Rh8 mates
+//! assert_eq!(krk.probe(w, w, Cell::B6, Cell::H1, Cell::A8), Some(1));
+//! assert_eq!(krk.probe(w, b, Cell::B6, Cell::H8, Cell::A8), Some(0));
+//! // The black King captures the Rook
+//! assert_eq!(krk.probe(w, b, Cell::B6, Cell::B8, Cell::A8), None);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::attacktables::*;
+use crate::bbdefines::*;
+use crate::chessdefines::ArmyColour;
+use crate::error::AbbaDingoError;
+use crate::slidingattacks::{queen_attacks, rook_attacks};
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// The current version of the binary format of the tables.
+pub const DTM_TABLE_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 3] = b"ADT";
+const ENDINGS: [DtmEnding; 3] = [DtmEnding::Kqk, DtmEnding::Krk, DtmEnding::Kpk];
+
+// Values of the positions in the tables (the other values are the DTM in plies).
+const UNKNOWN: u8 = 255;
+const DRAW: u8 = 254;
+const INVALID: u8 = 253;
+
+// The cells of the a1-d1-d4 triangle, where the weak king is placed in the
+// normalized KQK and KRK positions.
+const TRIANGLE_CELLS: [usize; 10] = [0, 1, 9, 2, 10, 18, 3, 11, 19, 27];
+
+// Number of cells of the pawn in the normalized KPK positions
+// (files a to d, ranks 2 to 7).
+const PAWN_CELLS: usize = 4 * 6;
+
+/// The endings with a [DtmTable].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DtmEnding {
+    /// King and Queen vs King.
+    Kqk,
+    /// King and Rook vs King.
+    Krk,
+    /// King and Pawn vs King.
+    Kpk,
+}
+
+/// A distance-to-mate table.
+///
+/// Positions are stored normalized, with the strong side playing White and
+/// using the symmetries of the board allowed by the ending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DtmTable {
+    ending: DtmEnding,
+    dtm: Vec<u8>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl DtmEnding {
+    /// Returns the number of positions of the table of the ending.
+    const fn size(self) -> usize {
+        match self {
+            DtmEnding::Kqk | DtmEnding::Krk => 2 * TRIANGLE_CELLS.len() * NUM_CELLS * NUM_CELLS,
+            DtmEnding::Kpk => 2 * NUM_CELLS * NUM_CELLS * PAWN_CELLS,
+        }
+    }
+}
+
+impl DtmTable {
+    /// Generates the table of an ending.
+    ///
+    /// The generation takes a noticeable time (especially in debug builds),
+    /// so it should be performed only once.
+    pub fn generate(ending: DtmEnding) -> DtmTable {
+        match ending {
+            DtmEnding::Kpk => {
+                let kqk = DtmTable::generate(DtmEnding::Kqk);
+                let krk = DtmTable::generate(DtmEnding::Krk);
+                DtmTable::generate_with_promotions(ending, Some((&kqk, &krk)))
+            }
+            _ => DtmTable::generate_with_promotions(ending, None),
+        }
+    }
+
+    /// Returns the ending of the table.
+    pub fn ending(&self) -> DtmEnding {
+        self.ending
+    }
+
+    /// Probes the table, returning the number of plies to checkmate if the
+    /// `strong_side` wins the position, `None` if the position is a draw.
+    ///
+    /// # Arguments
+    ///
+    /// * `strong_side`: The [ArmyColour] of the army with the piece
+    /// * `to_move`: The [ArmyColour] of the army to move
+    /// * `strong_king`: The [Cell] of the king of the strong side
+    /// * `piece`: The [Cell] of the piece (Queen, Rook or Pawn) of the strong side
+    /// * `weak_king`: The [Cell] of the king of the weak side
+    ///
+    /// `None` is returned also for illegal positions (e.g. adjacent kings, pawn
+    /// on the first or last rank).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::dtm::*;
+    /// let kqk = DtmTable::generate(DtmEnding::Kqk);
+    /// let (w, b) = (ArmyColour::White, ArmyColour::Black);
+    /// // Stalemate
+    /// assert_eq!(kqk.probe(b, w, Cell::B3, Cell::C2, Cell::A1), None);
+    /// // Qc1 mates
+    /// assert_eq!(kqk.probe(b, b, Cell::B3, Cell::C4, Cell::A1), Some(1));
+    /// ```
+    pub fn probe(
+        &self,
+        strong_side: ArmyColour,
+        to_move: ArmyColour,
+        strong_king: Cell,
+        piece: Cell,
+        weak_king: Cell,
+    ) -> Option<u32> {
+        let (mut sk, mut pc, mut wk) = (strong_king as usize, piece as usize, weak_king as usize);
+        if strong_side == ArmyColour::Black {
+            // Flip the board vertically so that the pawn moves north
+            sk ^= 56;
+            pc ^= 56;
+            wk ^= 56;
+        }
+        if self.ending == DtmEnding::Kpk && !(NUM_FILES..NUM_CELLS - NUM_FILES).contains(&pc) {
+            return None;
+        }
+        match self.dtm[index(self.ending, to_move != strong_side, wk, sk, pc)] {
+            DRAW | INVALID => None,
+            plies => Some(plies as u32),
+        }
+    }
+
+    /// Encodes the table in a binary format: the magic bytes `ADT`, the format
+    /// version (currently [DTM_TABLE_VERSION]), the ending and the values of the
+    /// positions (one byte each).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 2 + self.dtm.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(DTM_TABLE_VERSION);
+        bytes.push(ENDINGS.iter().position(|e| *e == self.ending).unwrap() as u8);
+        bytes.extend_from_slice(&self.dtm);
+        bytes
+    }
+
+    /// Decodes a table encoded with [DtmTable::encode].
+    ///
+    /// Err(AbbaDingoError::IllegalDtmTable) is returned if the data is
+    /// truncated or corrupted or if it has been written by an unsupported
+    /// version of the format.
+    ///
+    pub fn decode(bytes: &[u8]) -> Result<DtmTable, AbbaDingoError> {
+        let err = AbbaDingoError::IllegalDtmTable;
+        if bytes.len() < 5 || &bytes[0..3] != MAGIC || bytes[3] != DTM_TABLE_VERSION {
+            return Err(err);
+        }
+        let ending = *ENDINGS.get(bytes[4] as usize).ok_or(err)?;
+        let dtm = &bytes[5..];
+        if dtm.len() != ending.size() || dtm.contains(&UNKNOWN) {
+            return Err(AbbaDingoError::IllegalDtmTable);
+        }
+        Ok(DtmTable {
+            ending,
+            dtm: Vec::from(dtm),
+        })
+    }
+
+    /// Loads a table from a file written by [DtmTable::save].
+    ///
+    /// Err(AbbaDingoError::CannotReadFile) is returned if the file cannot be
+    /// read, Err(AbbaDingoError::IllegalDtmTable) if its content is not valid.
+    ///
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<DtmTable, AbbaDingoError> {
+        let bytes = std::fs::read(path).map_err(|_| AbbaDingoError::CannotReadFile)?;
+        DtmTable::decode(&bytes)
+    }
+
+    /// Saves the table to a file, in the format of [DtmTable::encode].
+    ///
+    /// Err(AbbaDingoError::CannotWriteFile) is returned if the file cannot be written.
+    ///
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), AbbaDingoError> {
+        std::fs::write(path, self.encode()).map_err(|_| AbbaDingoError::CannotWriteFile)
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Generates the table of an ending, using the tables of the endings
+    /// reached with the promotions of the pawn (to Queen and to Rook) if needed.
+    ///
+    fn generate_with_promotions(
+        ending: DtmEnding,
+        promotions: Option<(&DtmTable, &DtmTable)>,
+    ) -> DtmTable {
+        let size = ending.size();
+        let mut dtm: Vec<u8> = (0..size)
+            .map(|ndx| initial_classification(ending, ndx))
+            .collect();
+        let mut successors = Vec::new();
+
+        // At pass n, the positions won in n plies are found; the values found
+        // during the pass (n itself) are considered unknown to keep the
+        // distances exact.
+        for n in 1..INVALID {
+            let mut found = false;
+            for ndx in 0..size {
+                if dtm[ndx] != UNKNOWN {
+                    continue;
+                }
+                let (weak_to_move, _, _, _) = decode(ending, ndx);
+                successors.clear();
+                push_successors(ending, ndx, &dtm, promotions, &mut successors);
+                if weak_to_move {
+                    // Won only if all the moves lead to won positions
+                    if successors.contains(&DRAW) {
+                        dtm[ndx] = DRAW;
+                    } else if successors.iter().all(|v| *v < n) {
+                        // Not all the successors can be found in previous
+                        // passes, otherwise the position would be known
+                        found = true;
+                        dtm[ndx] = n;
+                    }
+                } else if successors.contains(&(n - 1)) {
+                    // Won if a move leads to a position won in n - 1 plies
+                    found = true;
+                    dtm[ndx] = n;
+                }
+            }
+            if !found {
+                break;
+            }
+        }
+
+        for v in dtm.iter_mut() {
+            if *v == UNKNOWN {
+                *v = DRAW;
+            }
+        }
+        DtmTable { ending, dtm }
+    }
+}
+
+/// Computes the index in the table of a position, normalizing it.
+///
+/// # Arguments
+///
+/// * `ending`: the [DtmEnding] of the table
+/// * `weak_to_move`: true if the weak side (the one without piece) is to move
+/// * `wk`, `sk`, `pc`: the indexes of the cells of the weak king, of the strong
+///   king and of the piece (for the KPK ending, on ranks 2 to 7)
+///
+fn index(ending: DtmEnding, weak_to_move: bool, wk: usize, sk: usize, pc: usize) -> usize {
+    let (mut wk, mut sk, mut pc) = (wk, sk, pc);
+    match ending {
+        DtmEnding::Kqk | DtmEnding::Krk => {
+            // Move the weak king in the a1-d1-d4 triangle
+            if wk % NUM_FILES >= 4 {
+                wk ^= 7;
+                sk ^= 7;
+                pc ^= 7;
+            }
+            if wk / NUM_FILES >= 4 {
+                wk ^= 56;
+                sk ^= 56;
+                pc ^= 56;
+            }
+            if wk / NUM_FILES > wk % NUM_FILES {
+                wk = transpose(wk);
+                sk = transpose(sk);
+                pc = transpose(pc);
+            }
+            let (f, r) = (wk % NUM_FILES, wk / NUM_FILES);
+            let wk_ndx = f * (f + 1) / 2 + r;
+            ((wk_ndx * NUM_CELLS + sk) * NUM_CELLS + pc) * 2 + weak_to_move as usize
+        }
+        DtmEnding::Kpk => {
+            // Move the pawn on the files a to d
+            if pc % NUM_FILES >= 4 {
+                wk ^= 7;
+                sk ^= 7;
+                pc ^= 7;
+            }
+            let pc_ndx = (pc / NUM_FILES - 1) * 4 + pc % NUM_FILES;
+            ((wk * NUM_CELLS + sk) * PAWN_CELLS + pc_ndx) * 2 + weak_to_move as usize
+        }
+    }
+}
+
+/// Decodes an index of the table into its components
+/// (weak side to move, weak king, strong king, piece).
+///
+fn decode(ending: DtmEnding, ndx: usize) -> (bool, usize, usize, usize) {
+    let weak_to_move = !ndx.is_multiple_of(2);
+    let ndx = ndx / 2;
+    match ending {
+        DtmEnding::Kqk | DtmEnding::Krk => (
+            weak_to_move,
+            TRIANGLE_CELLS[ndx / (NUM_CELLS * NUM_CELLS)],
+            (ndx / NUM_CELLS) % NUM_CELLS,
+            ndx % NUM_CELLS,
+        ),
+        DtmEnding::Kpk => {
+            let pc_ndx = ndx % PAWN_CELLS;
+            (
+                weak_to_move,
+                ndx / (NUM_CELLS * PAWN_CELLS),
+                (ndx / PAWN_CELLS) % NUM_CELLS,
+                (pc_ndx / 4 + 1) * NUM_FILES + pc_ndx % 4,
+            )
+        }
+    }
+}
+
+/// Mirrors a cell index along the a1-h8 diagonal.
+///
+const fn transpose(c: usize) -> usize {
+    (c % NUM_FILES) * NUM_FILES + c / NUM_FILES
+}
+
+/// Returns the cells attacked by the piece of the strong side.
+///
+fn piece_attacks(ending: DtmEnding, pc: usize, occupancy: BitBoardState) -> BitBoardState {
+    // We can unwrap safely here... pc is always a valid cell
+    let c = num::FromPrimitive::from_usize(pc).unwrap();
+    match ending {
+        DtmEnding::Kqk => queen_attacks(c, occupancy),
+        DtmEnding::Krk => rook_attacks(c, occupancy),
+        DtmEnding::Kpk => PAWN_ATTACKS[ArmyColour::White as usize][pc],
+    }
+}
+
+/// Returns the cells attacked by the strong side. The weak king does not
+/// block the view of the sliding pieces.
+///
+fn strong_side_attacks(ending: DtmEnding, sk: usize, pc: usize) -> BitBoardState {
+    KING_ATTACKS[sk] | piece_attacks(ending, pc, (1 << sk) | (1 << pc))
+}
+
+/// Classifies a position using only the position itself (without looking at
+/// the successors): illegal positions, checkmates and immediate draws
+/// (stalemate or piece captured).
+///
+fn initial_classification(ending: DtmEnding, ndx: usize) -> u8 {
+    let (weak_to_move, wk, sk, pc) = decode(ending, ndx);
+    let attacked = strong_side_attacks(ending, sk, pc);
+
+    if wk == sk
+        || wk == pc
+        || sk == pc
+        || KING_ATTACKS[sk] & (1 << wk) != 0
+        || (!weak_to_move && attacked & (1 << wk) != 0)
+    {
+        return INVALID;
+    }
+
+    if weak_to_move {
+        if KING_ATTACKS[wk] & !KING_ATTACKS[sk] & (1 << pc) != 0 {
+            // The piece can be captured
+            return DRAW;
+        }
+        if KING_ATTACKS[wk] & !attacked == EMPTY_STATE {
+            return if attacked & (1 << wk) != 0 {
+                // Checkmate
+                0
+            } else {
+                // Stalemate
+                DRAW
+            };
+        }
+    }
+
+    UNKNOWN
+}
+
+/// Appends to `successors` the values of the positions reached with the
+/// moves of the position with index `ndx`.
+///
+/// The values of the positions reached with the promotion of the pawn are
+/// taken from the `promotions` tables (KQK and KRK).
+///
+fn push_successors(
+    ending: DtmEnding,
+    ndx: usize,
+    dtm: &[u8],
+    promotions: Option<(&DtmTable, &DtmTable)>,
+    successors: &mut Vec<u8>,
+) {
+    let (weak_to_move, wk, sk, pc) = decode(ending, ndx);
+    if weak_to_move {
+        // The captures of the piece are found by initial_classification()
+        let mut moves = KING_ATTACKS[wk] & !strong_side_attacks(ending, sk, pc) & !(1 << pc);
+        while moves != EMPTY_STATE {
+            let dest = moves.trailing_zeros() as usize;
+            moves &= moves - 1;
+            successors.push(dtm[index(ending, false, dest, sk, pc)]);
+        }
+        return;
+    }
+
+    let mut moves = KING_ATTACKS[sk] & !KING_ATTACKS[wk] & !(1 << pc);
+    while moves != EMPTY_STATE {
+        let dest = moves.trailing_zeros() as usize;
+        moves &= moves - 1;
+        successors.push(dtm[index(ending, true, wk, dest, pc)]);
+    }
+
+    let busy = (1 << sk) | (1 << wk);
+    if ending == DtmEnding::Kpk {
+        let push = pc + NUM_FILES;
+        if busy & (1 << push) != 0 {
+            return;
+        }
+        if push / NUM_FILES == NUM_RANKS - 1 {
+            // We can unwrap safely here... the KPK table is generated with the promotions tables
+            let (kqk, krk) = promotions.unwrap();
+            for table in [kqk, krk] {
+                successors.push(table.dtm[index(table.ending, true, wk, sk, push)]);
+            }
+        } else {
+            successors.push(dtm[index(ending, true, wk, sk, push)]);
+            let double_push = push + NUM_FILES;
+            if pc / NUM_FILES == 1 && busy & (1 << double_push) == 0 {
+                successors.push(dtm[index(ending, true, wk, sk, double_push)]);
+            }
+        }
+    } else {
+        let mut moves = piece_attacks(ending, pc, busy | (1 << pc)) & !busy;
+        while moves != EMPTY_STATE {
+            let dest = moves.trailing_zeros() as usize;
+            moves &= moves - 1;
+            successors.push(dtm[index(ending, true, wk, sk, dest)]);
+        }
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::kpk::KpkBitbase;
+
+    #[test]
+    fn index_and_decode_are_consistent() {
+        for ending in ENDINGS {
+            for ndx in (0..ending.size()).step_by(97) {
+                let (weak_to_move, wk, sk, pc) = decode(ending, ndx);
+                assert_eq!(index(ending, weak_to_move, wk, sk, pc), ndx);
+            }
+        }
+    }
+
+    #[test]
+    fn longest_mates() {
+        let max_dtm = |t: &DtmTable| {
+            t.dtm
+                .iter()
+                .filter(|v| **v < INVALID)
+                .max()
+                .copied()
+                .unwrap()
+        };
+        // Mate in 10 moves for KQK, in 16 moves for KRK (weak side to move)
+        assert_eq!(max_dtm(&DtmTable::generate(DtmEnding::Kqk)), 20);
+        assert_eq!(max_dtm(&DtmTable::generate(DtmEnding::Krk)), 32);
+    }
+
+    #[test]
+    fn kpk_table_agrees_with_the_kpk_bitbase() {
+        let dtm = DtmTable::generate(DtmEnding::Kpk);
+        let kpk = KpkBitbase::generate();
+        let (w, b) = (ArmyColour::White, ArmyColour::Black);
+        for ndx in 0..DtmEnding::Kpk.size() {
+            if initial_classification(DtmEnding::Kpk, ndx) == INVALID {
+                continue;
+            }
+            let (weak_to_move, wk, sk, pc) = decode(DtmEnding::Kpk, ndx);
+            let to_move = if weak_to_move { b } else { w };
+            let [wk, sk, pc]: [Cell; 3] =
+                [wk, sk, pc].map(|c| num::FromPrimitive::from_usize(c).unwrap());
+            assert_eq!(
+                Some(dtm.probe(w, to_move, sk, pc, wk).is_some()),
+                kpk.probe(w, to_move, sk, pc, wk)
+            );
+        }
+        // The pawn runs to promotion: 4 pushes, then KQK
+        assert_eq!(
+            dtm.probe(w, w, Cell::H1, Cell::A4, Cell::H8).map(|p| p % 2),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let kqk = DtmTable::generate(DtmEnding::Kqk);
+        let mut bytes = kqk.encode();
+        assert_eq!(&bytes[0..5], b"ADT\x01\x00");
+        assert_eq!(DtmTable::decode(&bytes), Ok(kqk));
+        bytes.pop();
+        assert_eq!(
+            DtmTable::decode(&bytes),
+            Err(AbbaDingoError::IllegalDtmTable)
+        );
+        assert_eq!(
+            DtmTable::decode(b"ADT\x01\x03"),
+            Err(AbbaDingoError::IllegalDtmTable)
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Loader of chess test suites in the
+//! [Extended Position Description](https://www.chessprogramming.org/Extended_Position_Description)
+//! (EPD) format.
+//!
+//! A test suite is a text with one position per line; each position can be
+//! expressed as an EPD record (the first 4 fields of a FEN record followed by
+//! a list of operations separated by `;`) or as a plain FEN record.
+//! Empty lines and lines starting with `#` are ignored.
+//!
+//! Each line is loaded in a [TestPosition], with the position converted to a
+//! complete FEN string (using the `hmvc` and `fmvn` operations for the move
+//! counters, if present), the `id` of the position and the expected best
+//! (`bm`) and avoid (`am`) moves, kept in SAN format.
+//!
+//! A suite is run with [run_test_suite], that asks a caller-supplied search
+//! for the move to play in each position and reports the score of the search
+//! in a [SuiteReport].
+//!
+//! # Example
+//! ```
+//! # use abbadingo::epd::*;
+//! let suite = parse_test_suite(
+//!     "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+//! )
+//! .unwrap();
+//! assert_eq!(suite.len(), 1);
+//! assert_eq!(suite[0].id.as_deref(), Some("WAC.001"));
+//! assert_eq!(suite[0].best_moves, vec!["Qg6"]);
+//! assert_eq!(
+//!     suite[0].fen,
+//!     "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1"
+//! );
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::chessboard::ChessBoard;
+use crate::chessmove::ChessMove;
+use crate::error::AbbaDingoError;
+use crate::fenrecord::FenRecord;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// A position of a test suite, with its expected results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestPosition {
+    /// The identifier of the position (`id` operation), if any.
+    pub id: Option<String>,
+    /// The position, as a complete FEN string.
+    pub fen: String,
+    /// The best moves in SAN format (`bm` operation).
+    pub best_moves: Vec<String>,
+    /// The moves to be avoided in SAN format (`am` operation).
+    pub avoid_moves: Vec<String>,
+    /// All the operations of the EPD record, as opcode and list of operands
+    /// (with the quotes removed from string operands).
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+/// The score of a search on a test suite, returned by [run_test_suite].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SuiteReport {
+    /// The number of positions scored (the ones with best or avoid moves).
+    pub total: usize,
+    /// The number of positions solved.
+    pub solved: usize,
+    /// The positions not solved, as index in the suite, identifier and the
+    /// move played by the search in SAN format.
+    pub failed: Vec<(usize, Option<String>, String)>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Parses a test suite, one position per line.
+///
+/// Empty lines and lines starting with `#` are skipped; if a line cannot be
+/// parsed Err(AbbaDingoError::IllegalEpdRecord) is returned.
+///
+pub fn parse_test_suite(text: &str) -> Result<Vec<TestPosition>, AbbaDingoError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(TestPosition::try_from)
+        .collect()
+}
+
+/// Loads a test suite from a file (see [parse_test_suite]).
+///
+/// Err(AbbaDingoError::CannotReadFile) is returned if the file cannot be read.
+///
+#[cfg(feature = "std")]
+pub fn load_test_suite<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<Vec<TestPosition>, AbbaDingoError> {
+    let text = std::fs::read_to_string(path).map_err(|_| AbbaDingoError::CannotReadFile)?;
+    parse_test_suite(&text)
+}
+
+/// Runs a test suite, asking `search` for the move to play in each position.
+///
+/// A position is solved if the move played is one of its best moves (if any)
+/// and none of its moves to be avoided. The positions without best and avoid
+/// moves are not scored (the search is not called for them). If a position or
+/// one of its expected moves is not valid, the error met is returned.
+///
+/// # Example
+/// ```
+/// # use abbadingo::epd::*;
+/// let suite = parse_test_suite(
+///     "6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"mate\";\n\
+///      6k1/5ppp/8/8/8/8/8/R5K1 w - - am Ra7; id \"avoid\";",
+/// )
+/// .unwrap();
+/// // A (poor) search playing always the first legal move
+/// let report = run_test_suite(&suite, |board| board.legal_moves()[0]).unwrap();
+/// assert_eq!((report.solved, report.total), (1, 2));
+/// assert_eq!(report.failed, vec![(0, Some(String::from("mate")), String::from("Kf1"))]);
+/// assert_eq!(format!("{}", report), "1/2 solved (50.0%), failed: mate (Kf1)");
+/// ```
+pub fn run_test_suite<F>(
+    suite: &[TestPosition],
+    mut search: F,
+) -> Result<SuiteReport, AbbaDingoError>
+where
+    F: FnMut(&ChessBoard) -> ChessMove,
+{
+    let mut report = SuiteReport::default();
+    for (ndx, tp) in suite.iter().enumerate() {
+        if tp.best_moves.is_empty() && tp.avoid_moves.is_empty() {
+            continue;
+        }
+        let board = FenRecord::try_from(tp.fen.as_str())?.board();
+        let resolve = |moves: &[String]| -> Result<Vec<ChessMove>, AbbaDingoError> {
+            moves
+                .iter()
+                .map(|san| ChessMove::from_san(san, &board))
+                .collect()
+        };
+        let (best, avoid) = (resolve(&tp.best_moves)?, resolve(&tp.avoid_moves)?);
+        let m = search(&board);
+        report.total += 1;
+        if (best.is_empty() || best.contains(&m)) && !avoid.contains(&m) {
+            report.solved += 1;
+        } else {
+            report.failed.push((ndx, tp.id.clone(), m.to_san(&board)));
+        }
+    }
+    Ok(report)
+}
+
+/// Splits the operations part of an EPD record into its operations,
+/// each one made of the opcode and the list of operands.
+///
+fn parse_operations(ops: &str) -> Result<Vec<(String, Vec<String>)>, AbbaDingoError> {
+    let mut operations = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut quoted = false;
+    for ch in ops.chars() {
+        match ch {
+            '"' => {
+                in_string = !in_string;
+                quoted = true;
+            }
+            _ if in_string => current.push(ch),
+            ' ' | '\t' | ';' => {
+                if !current.is_empty() || quoted {
+                    tokens.push(current);
+                    current = String::new();
+                    quoted = false;
+                }
+                if ch == ';' {
+                    if tokens.is_empty() {
+                        return Err(AbbaDingoError::IllegalEpdRecord);
+                    }
+                    let opcode = tokens.remove(0);
+                    operations.push((opcode, tokens));
+                    tokens = Vec::new();
+                }
+            }
+            _ => current.push(ch),
+        }
+    }
+    if in_string || !current.is_empty() || quoted || !tokens.is_empty() {
+        // Unterminated string or operation without the final ';'
+        return Err(AbbaDingoError::IllegalEpdRecord);
+    }
+    Ok(operations)
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for TestPosition and SuiteReport structures
+
+/// Shows the score of a [SuiteReport] and the positions not solved, by
+/// identifier (or index in the suite) and move played.
+///
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = if self.total == 0 {
+            0.0
+        } else {
+            100.0 * self.solved as f64 / self.total as f64
+        };
+        write!(f, "{}/{} solved ({:.1}%)", self.solved, self.total, percent)?;
+        for (n, (ndx, id, m)) in self.failed.iter().enumerate() {
+            let sep = if n == 0 { ", failed: " } else { ", " };
+            match id {
+                Some(id) => write!(f, "{}{} ({})", sep, id, m)?,
+                None => write!(f, "{}#{} ({})", sep, ndx, m)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tentatively convert a &str with an EPD (or FEN) record to a [TestPosition].
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::epd::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// let tp = TestPosition::try_from(
+///     "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 0 7",
+/// )
+/// .unwrap();
+/// assert_eq!(tp.id, None);
+/// assert!(tp.best_moves.is_empty());
+/// assert_eq!(tp.fen, "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 0 7");
+/// assert_eq!(
+///     TestPosition::try_from("8/8/8/8/8/8/8/8 x - -"),
+///     Err(AbbaDingoError::IllegalEpdRecord)
+/// );
+/// ```
+///
+impl TryFrom<&str> for TestPosition {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut fields = value.trim_start().splitn(5, char::is_whitespace);
+        let mut position = Vec::new();
+        for _ in 0..4 {
+            match fields.next() {
+                Some(f) if !f.is_empty() => position.push(f),
+                _ => return Err(AbbaDingoError::IllegalEpdRecord),
+            }
+        }
+        if position[0].split('/').count() != 8 || (position[1] != "w" && position[1] != "b") {
+            return Err(AbbaDingoError::IllegalEpdRecord);
+        }
+        let mut rest = fields.next().unwrap_or("").trim();
+
+        // Plain FEN record: the move counters follow the first 4 fields
+        let mut counters = None;
+        let mut tokens = rest.splitn(3, char::is_whitespace);
+        if let (Some(h), Some(f)) = (tokens.next(), tokens.next()) {
+            if h.parse::<u32>().is_ok() && f.parse::<u32>().is_ok() {
+                counters = Some((h.to_string(), f.to_string()));
+                rest = tokens.next().unwrap_or("").trim();
+            }
+        }
+
+        let operations = parse_operations(rest)?;
+        let operand = |opcode: &str| {
+            operations
+                .iter()
+                .find(|(o, _)| o == opcode)
+                .map(|(_, operands)| operands.clone())
+        };
+        let (halfmove, fullmove) = counters.unwrap_or_else(|| {
+            (
+                operand("hmvc")
+                    .and_then(|v| v.first().cloned())
+                    .unwrap_or_else(|| "0".to_string()),
+                operand("fmvn")
+                    .and_then(|v| v.first().cloned())
+                    .unwrap_or_else(|| "1".to_string()),
+            )
+        });
+        // The counters taken from the operations must be numbers, too
+        if halfmove.parse::<u32>().is_err() || fullmove.parse::<u32>().is_err() {
+            return Err(AbbaDingoError::IllegalEpdRecord);
+        }
+
+        Ok(TestPosition {
+            id: operand("id").and_then(|v| v.first().cloned()),
+            fen: format!("{} {} {}", position.join(" "), halfmove, fullmove),
+            best_moves: operand("bm").unwrap_or_default(),
+            avoid_moves: operand("am").unwrap_or_default(),
+            operations,
+        })
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_suite_runs_are_scored() {
+        let suite = parse_test_suite(
+            "# Mate in one\n\
+             6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"mate\";\n\
+             6k1/5ppp/8/8/8/8/8/R5K1 w - - am Ra8#;\n\
+             6k1/5ppp/8/8/8/8/8/R5K1 w - - id \"not scored\";\n",
+        )
+        .unwrap();
+        // Plays the mate in one, if any
+        let mate_search = |board: &ChessBoard| {
+            let moves = board.legal_moves();
+            moves
+                .iter()
+                .copied()
+                .find(|m| {
+                    let after = board.make_move_copy(*m);
+                    after.is_in_check() && after.legal_moves().is_empty()
+                })
+                .unwrap_or(moves[0])
+        };
+        let report = run_test_suite(&suite, mate_search).unwrap();
+        assert_eq!((report.solved, report.total), (1, 2));
+        assert_eq!(report.failed, vec![(1, None, String::from("Ra8#"))]);
+        assert_eq!(
+            format!("{}", report),
+            "1/2 solved (50.0%), failed: #1 (Ra8#)"
+        );
+        assert_eq!(
+            format!("{}", run_test_suite(&[], mate_search).unwrap()),
+            "0/0 solved (0.0%)"
+        );
+
+        let wrong_bm = parse_test_suite("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Rb8;").unwrap();
+        assert_eq!(
+            run_test_suite(&wrong_bm, mate_search),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+    }
+
+    #[test]
+    fn epd_record_with_several_operations() {
+        let tp = TestPosition::try_from(
+            "r1bqk1r1/1p1p1n2/p1n2pN1/2p1b2Q/2P1Pp2/1PN5/PB4PP/R4RK1 w q - \
+             bm Rxf4 Nd5; am Qxh7; id \"ECM 001; quiet\"; hmvc 3; fmvn 21;",
+        )
+        .unwrap();
+        assert_eq!(tp.id.as_deref(), Some("ECM 001; quiet"));
+        assert_eq!(tp.best_moves, vec!["Rxf4", "Nd5"]);
+        assert_eq!(tp.avoid_moves, vec!["Qxh7"]);
+        assert_eq!(
+            tp.fen,
+            "r1bqk1r1/1p1p1n2/p1n2pN1/2p1b2Q/2P1Pp2/1PN5/PB4PP/R4RK1 w q - 3 21"
+        );
+        assert_eq!(tp.operations.len(), 5);
+        assert_eq!(
+            tp.operations[3],
+            ("hmvc".to_string(), vec!["3".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_suite_skips_comments_and_empty_lines() {
+        let suite = parse_test_suite(
+            "# A small test suite\n\
+             \n\
+             rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+             6k1/8/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"mate\";\n",
+        )
+        .unwrap();
+        assert_eq!(suite.len(), 2);
+        assert_eq!(suite[0].id, None);
+        assert_eq!(suite[1].best_moves, vec!["Ra8#"]);
+    }
+
+    #[test]
+    fn illegal_epd_records() {
+        for r in [
+            "",
+            "8/8/8/8/8/8/8/8 w -",
+            "8/8/8/8/8/8/8 w - -",
+            "8/8/8/8/8/8/8/8 w - - bm Qg6",
+            "8/8/8/8/8/8/8/8 w - - id \"unterminated;",
+            "8/8/8/8/8/8/8/8 w - - ;",
+            "8/8/8/8/8/8/8/8 w - - hmvc x;",
+            "8/8/8/8/8/8/8/8 w - - fmvn \"1 2\";",
+        ] {
+            assert_eq!(
+                TestPosition::try_from(r),
+                Err(AbbaDingoError::IllegalEpdRecord)
+            );
+        }
+        assert_eq!(
+            parse_test_suite("8/8/8/8/8/8/8/8 w - - 0 1\nnot a position"),
+            Err(AbbaDingoError::IllegalEpdRecord)
+        );
+    }
+}
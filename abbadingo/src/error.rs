@@ -24,4 +24,65 @@ pub enum AbbaDingoError {
     /// Illegal conversion to [ChessPiece](crate::chessdefines::ChessPiece).
     #[error("Illegal conversion to ChessPiece")]
     IllegalConversionToChessPiece,
+    /// Illegal conversion to [ArmyColour](crate::chessdefines::ArmyColour).
+    #[error("Illegal conversion to ArmyColour")]
+    IllegalConversionToArmyColour,
+    /// Illegal conversion to [CastlingRights](crate::chessdefines::CastlingRights).
+    #[error("Illegal conversion to CastlingRights")]
+    IllegalConversionToCastlingRights,
+    /// Illegal conversion to [GameResult](crate::chessdefines::GameResult).
+    #[error("Illegal conversion to GameResult")]
+    IllegalConversionToGameResult,
+    /// Illegal move in coordinate notation (see [coordinate](crate::notation::coordinate)).
+    #[error("Illegal move in coordinate notation")]
+    IllegalCoordinateNotationMove,
+    /// FEN record without the six fields (see [FenRecord](crate::fenrecord::FenRecord)).
+    #[error("Illegal FEN record")]
+    IllegalFenRecord,
+    /// Illegal piece placement field in a FEN record.
+    #[error("Illegal piece placement in FEN record")]
+    IllegalFenPiecePlacement,
+    /// Illegal active colour field in a FEN record.
+    #[error("Illegal active colour in FEN record")]
+    IllegalFenActiveColour,
+    /// Illegal castling availability field in a FEN record.
+    #[error("Illegal castling availability in FEN record")]
+    IllegalFenCastlingAvailability,
+    /// Illegal en-passant target cell field in a FEN record.
+    #[error("Illegal en-passant target cell in FEN record")]
+    IllegalFenEnPassantCell,
+    /// Illegal halfmove clock or fullmove number field in a FEN record.
+    #[error("Illegal move counter in FEN record")]
+    IllegalFenMoveCounter,
+    /// Position without exactly one King per army in a FEN record.
+    #[error("Illegal number of Kings in FEN record")]
+    IllegalFenKings,
+    /// Illegal position in a FEN record, e.g. with the army not to move in check
+    /// (see [validate_position](crate::chessboard::validate_position)).
+    #[error("Illegal position in FEN record")]
+    IllegalFenPosition,
+    /// Illegal record in an EPD test suite (see [epd](crate::epd)).
+    #[error("Illegal EPD record")]
+    IllegalEpdRecord,
+    /// Illegal game in PGN format (see [pgn](crate::pgn)).
+    #[error("Illegal PGN game")]
+    IllegalPgnGame,
+    /// Move in Standard Algebraic Notation not legal or ambiguous in the position.
+    #[error("Illegal SAN move")]
+    IllegalSanMove,
+    /// Move in UCI long algebraic notation not legal in the position.
+    #[error("Illegal UCI move")]
+    IllegalUciMove,
+    /// Illegal data for a game in binary format (see [binarygame](crate::binarygame)).
+    #[error("Illegal binary game data")]
+    IllegalBinaryGame,
+    /// Illegal data for a distance-to-mate table (see [dtm](crate::dtm)).
+    #[error("Illegal DTM table data")]
+    IllegalDtmTable,
+    /// The requested file cannot be read.
+    #[error("Cannot read file")]
+    CannotReadFile,
+    /// The requested file cannot be written.
+    #[error("Cannot write file")]
+    CannotWriteFile,
 }
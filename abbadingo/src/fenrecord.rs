@@ -2,10 +2,15 @@
 //! in the [Forsyth–Edwards Notation](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation) (FEN).
 //!
 
-// These will be useful later...
-//use crate::chessdefines::{ArmyColour};
-//use crate::bitboard::BitBoard;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
 
+use crate::bbdefines::*;
+use crate::chessboard::{validate_position, ChessBoard, PositionError};
+use crate::chessdefines::*;
+use crate::error::AbbaDingoError;
 
 // From Wikipedia (https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation):
 // Forsyth–Edwards Notation (FEN) is a standard notation for describing a particular board
@@ -53,11 +58,16 @@
 //    after Black's move.
 //
 
-// This will be useful later...
-// const DELIM: char = ' ';
-pub const INITIAL_STANDARD_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-pub const EMPTY_CHESS_BOARD: &str = "8/8/8/8/8/8/8/8 - - - 0 1";
+const DELIM: char = ' ';
+pub const INITIAL_STANDARD_POSITION: &str =
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+pub const EMPTY_CHESS_BOARD: &str = "8/8/8/8/8/8/8/8 w - - 0 1";
 
+/// A chess position in Forsyth–Edwards Notation.
+///
+/// The records built with [TryFrom] are validated, so they can always be
+/// expanded into a [ChessBoard] with [board()](FenRecord::board).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FenRecord {
     fen: String,
 }
@@ -65,7 +75,9 @@ pub struct FenRecord {
 impl FenRecord {
     /// Default constructor for the [FenRecord] struct: instantiate the initial chess standard position
     pub fn new() -> FenRecord {
-        FenRecord { fen: INITIAL_STANDARD_POSITION.to_string() }
+        FenRecord {
+            fen: INITIAL_STANDARD_POSITION.to_string(),
+        }
     }
 
     /// Return the current value of the FEN record as a string
@@ -78,6 +90,237 @@ impl FenRecord {
     pub fn fen(&self) -> &str {
         &self.fen
     }
+
+    /// Builds the [FenRecord] of the position of a [ChessBoard], serializing the
+    /// pieces placement and the position state in the six FEN fields.
+    ///
+    /// # Example:
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// # use abbadingo::fenrecord::*;
+    /// let mut board = ChessBoard::initial();
+    /// assert_eq!(FenRecord::from_board(&board).fen(), INITIAL_STANDARD_POSITION);
+    /// board.make_move(ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None));
+    /// assert_eq!(
+    ///     FenRecord::from_board(&board).fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    /// );
+    ///```
+    pub fn from_board(board: &ChessBoard) -> FenRecord {
+        let mut placement = [None; NUM_CELLS];
+        for (c, colour, cp) in board.pieces() {
+            placement[c as usize] = Some(cp.to_char(colour));
+        }
+        let mut ranks = Vec::new();
+        // The ranks are listed from the 8th to the 1st
+        for r in (0..NUM_RANKS).rev() {
+            let mut pieces = String::new();
+            let mut empty = 0;
+            for f in 0..NUM_FILES {
+                // We can unwrap safely here... f and r are always valid
+                let c = to_cell(
+                    num::FromPrimitive::from_usize(f).unwrap(),
+                    num::FromPrimitive::from_usize(r).unwrap(),
+                );
+                match placement[c as usize] {
+                    Some(ch) => {
+                        if empty > 0 {
+                            pieces.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        pieces.push(ch);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                pieces.push_str(&empty.to_string());
+            }
+            ranks.push(pieces);
+        }
+        let en_passant = match board.en_passant {
+            Some(c) => c.to_string(),
+            None => "-".to_string(),
+        };
+        FenRecord {
+            fen: format!(
+                "{}{}{}{}{}{}{}{}{}{}{}",
+                ranks.join("/"),
+                DELIM,
+                board.to_move,
+                DELIM,
+                board.castling_rights,
+                DELIM,
+                en_passant,
+                DELIM,
+                board.half_move_clock,
+                DELIM,
+                board.full_move_number
+            ),
+        }
+    }
+
+    /// Expands the FEN record into the [ChessBoard] with the pieces placement
+    /// and the position state.
+    ///
+    /// # Example:
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::fenrecord::FenRecord;
+    /// let fr = FenRecord::try_from("rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2").unwrap();
+    /// let board = fr.board();
+    /// assert_eq!(board.to_move, ArmyColour::White);
+    /// assert_eq!(board.en_passant, Some(Cell::C6));
+    /// assert_eq!(board.piece_at(Cell::C5), Some((ChessPiece::Pawn, ArmyColour::Black)));
+    /// assert_eq!(board.full_move_number, 2);
+    ///```
+    pub fn board(&self) -> ChessBoard {
+        // We can unwrap safely here... the record is always valid
+        parse_fen(&self.fen).unwrap()
+    }
+}
+
+impl Default for FenRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tentatively convert a &str with a FEN record to a [FenRecord].
+///
+/// The six fields of the record are validated and a dedicated [AbbaDingoError]
+/// is returned for each malformed field; then the position is checked with
+/// [validate_position], and the first error found is returned:
+/// Err(AbbaDingoError::IllegalFenKings) if an army has not exactly one King,
+/// Err(AbbaDingoError::IllegalFenEnPassantCell) or
+/// Err(AbbaDingoError::IllegalFenCastlingAvailability) if the en-passant target
+/// cell or the castling rights are not consistent with the pieces placement,
+/// Err(AbbaDingoError::IllegalFenPosition) otherwise. The fields can be
+/// separated by any amount of whitespace, and are stored separated by a single space.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::fenrecord::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// let fr = FenRecord::try_from(" 8/8/8/4k3/8/8/8/4K3   b - -  12 40").unwrap();
+/// assert_eq!(fr.fen(), "8/8/8/4k3/8/8/8/4K3 b - - 12 40");
+/// assert_eq!(
+///     FenRecord::try_from("8/8/8/4k3/8/8/8/4K3 b - -"),
+///     Err(AbbaDingoError::IllegalFenRecord)
+/// );
+/// assert_eq!(
+///     FenRecord::try_from("8/8/8/4k3/8/8/8/4K4 b - - 0 1"),
+///     Err(AbbaDingoError::IllegalFenPiecePlacement)
+/// );
+/// assert_eq!(
+///     FenRecord::try_from("8/8/8/4k3/8/8/8/4KK2 b - - 0 1"),
+///     Err(AbbaDingoError::IllegalFenKings)
+/// );
+/// assert_eq!(
+///     FenRecord::try_from("4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1"),
+///     Err(AbbaDingoError::IllegalFenCastlingAvailability)
+/// );
+/// assert_eq!(
+///     FenRecord::try_from("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1"),
+///     Err(AbbaDingoError::IllegalFenPosition)
+/// );
+/// ```
+///
+impl TryFrom<&str> for FenRecord {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        parse_fen(value)?;
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        Ok(FenRecord {
+            fen: fields.join(&DELIM.to_string()),
+        })
+    }
+}
+
+/// Parses a FEN record, returning the corresponding [ChessBoard].
+///
+fn parse_fen(fen: &str) -> Result<ChessBoard, AbbaDingoError> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() != 6 {
+        return Err(AbbaDingoError::IllegalFenRecord);
+    }
+    let mut board = ChessBoard::new();
+    parse_piece_placement(&mut board, fields[0])?;
+
+    let mut colour = fields[1].chars();
+    board.to_move = match (colour.next(), colour.next()) {
+        (Some(c), None) => {
+            ArmyColour::try_from(c).map_err(|_| AbbaDingoError::IllegalFenActiveColour)?
+        }
+        _ => return Err(AbbaDingoError::IllegalFenActiveColour),
+    };
+    board.castling_rights = CastlingRights::try_from(fields[2])
+        .map_err(|_| AbbaDingoError::IllegalFenCastlingAvailability)?;
+    if fields[3] != "-" {
+        let c = Cell::try_from(fields[3]).map_err(|_| AbbaDingoError::IllegalFenEnPassantCell)?;
+        board.en_passant = Some(c);
+    }
+    board.half_move_clock = fields[4]
+        .parse::<u32>()
+        .map_err(|_| AbbaDingoError::IllegalFenMoveCounter)?;
+    board.full_move_number = match fields[5].parse::<u32>() {
+        Ok(n) if n > 0 => n,
+        _ => return Err(AbbaDingoError::IllegalFenMoveCounter),
+    };
+    validate_position(&board).map_err(|errors| match errors[0] {
+        PositionError::KingsNumber(_) => AbbaDingoError::IllegalFenKings,
+        PositionError::EnPassantCell => AbbaDingoError::IllegalFenEnPassantCell,
+        PositionError::CastlingRights(_, _) => AbbaDingoError::IllegalFenCastlingAvailability,
+        _ => AbbaDingoError::IllegalFenPosition,
+    })?;
+    Ok(board)
+}
+
+/// Places in the [ChessBoard] the pieces of the piece placement field of a FEN record.
+///
+fn parse_piece_placement(board: &mut ChessBoard, placement: &str) -> Result<(), AbbaDingoError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != NUM_RANKS {
+        return Err(AbbaDingoError::IllegalFenPiecePlacement);
+    }
+    // The ranks are listed from the 8th to the 1st
+    for (ndx, pieces) in ranks.iter().enumerate() {
+        // We can unwrap safely here... ndx is always a valid rank
+        let r: Rank = num::FromPrimitive::from_usize(NUM_RANKS - 1 - ndx).unwrap();
+        let mut f = 0;
+        let mut last_was_digit = false;
+        for ch in pieces.chars() {
+            if let Some(d) = ch.to_digit(10) {
+                // Consecutive digits are not allowed
+                if d == 0 || last_was_digit {
+                    return Err(AbbaDingoError::IllegalFenPiecePlacement);
+                }
+                f += d as usize;
+                last_was_digit = true;
+                continue;
+            }
+            last_was_digit = false;
+            match ChessPiece::from_char(ch) {
+                Some((cp, colour)) if f < NUM_FILES => {
+                    // We can unwrap safely here... f is always a valid file
+                    let c = to_cell(num::FromPrimitive::from_usize(f).unwrap(), r);
+                    board.army_mut(colour).place_pieces(cp, &[c]);
+                    f += 1;
+                }
+                _ => return Err(AbbaDingoError::IllegalFenPiecePlacement),
+            }
+        }
+        if f != NUM_FILES {
+            return Err(AbbaDingoError::IllegalFenPiecePlacement);
+        }
+    }
+    Ok(())
 }
 
 // ****************************************************************************
@@ -86,10 +329,190 @@ impl FenRecord {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testpositions::ALL_PERFT_POSITIONS;
 
     #[test]
     fn default_constructor_instantiate_initial_chess_standard_position() {
         let fr = FenRecord::new();
         assert_eq!(fr.fen(), INITIAL_STANDARD_POSITION)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn standard_positions_are_expanded() {
+        assert_eq!(FenRecord::new().board(), ChessBoard::initial());
+        for p in ALL_PERFT_POSITIONS.iter() {
+            let board = FenRecord::try_from(p.fen).unwrap().board();
+            assert_eq!(
+                board
+                    .army(ArmyColour::White)
+                    .get_pieces(ChessPiece::King)
+                    .pop_count(),
+                1
+            );
+            assert_eq!(
+                board
+                    .army(ArmyColour::Black)
+                    .get_pieces(ChessPiece::King)
+                    .pop_count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn generated_records_round_trip() {
+        for p in ALL_PERFT_POSITIONS.iter() {
+            let fr = FenRecord::try_from(p.fen).unwrap();
+            assert_eq!(FenRecord::from_board(&fr.board()), fr);
+        }
+        let mut board = ChessBoard::new();
+        board
+            .army_mut(ArmyColour::White)
+            .place_pieces(ChessPiece::King, &[Cell::A1]);
+        board
+            .army_mut(ArmyColour::Black)
+            .place_pieces(ChessPiece::Queen, &[Cell::H8, Cell::D4]);
+        board.to_move = ArmyColour::Black;
+        board.half_move_clock = 9;
+        board.full_move_number = 51;
+        assert_eq!(
+            FenRecord::from_board(&board).fen(),
+            "7q/8/8/8/3q4/8/8/K7 b - - 9 51"
+        );
+    }
+
+    #[test]
+    fn malformed_fields_are_rejected() {
+        for (fen, err) in [
+            ("", AbbaDingoError::IllegalFenRecord),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 1 x",
+                AbbaDingoError::IllegalFenRecord,
+            ),
+            (
+                "8/8/8/8/8/8/8 w - - 0 1",
+                AbbaDingoError::IllegalFenPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/9 w - - 0 1",
+                AbbaDingoError::IllegalFenPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/44 w - - 0 1",
+                AbbaDingoError::IllegalFenPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/7kK w - - 0 1",
+                AbbaDingoError::IllegalFenPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/7x w - - 0 1",
+                AbbaDingoError::IllegalFenPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 - - - 0 1",
+                AbbaDingoError::IllegalFenActiveColour,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 wb - - 0 1",
+                AbbaDingoError::IllegalFenActiveColour,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w qk - 0 1",
+                AbbaDingoError::IllegalFenCastlingAvailability,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - e9 0 1",
+                AbbaDingoError::IllegalFenEnPassantCell,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e3 0 1",
+                AbbaDingoError::IllegalFenEnPassantCell,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - - -1 1",
+                AbbaDingoError::IllegalFenMoveCounter,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 0",
+                AbbaDingoError::IllegalFenMoveCounter,
+            ),
+        ] {
+            assert_eq!(FenRecord::try_from(fen), Err(err), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn each_army_shall_have_one_king() {
+        for fen in [
+            EMPTY_CHESS_BOARD,
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/8 b - - 0 1",
+            "4k3/8/8/8/8/8/8/3QKK2 w - - 0 1",
+            "KKKK4/8/8/8/8/8/8/kkk5 w - - 0 1",
+        ] {
+            assert_eq!(
+                FenRecord::try_from(fen),
+                Err(AbbaDingoError::IllegalFenKings),
+                "{}",
+                fen
+            );
+        }
+        assert_eq!(
+            crate::perft::verify("KKKK4/8/8/8/8/8/8/kkk5 w - - 0 1", &[1]),
+            Err(AbbaDingoError::IllegalFenKings)
+        );
+    }
+
+    #[test]
+    fn illegal_positions_are_rejected() {
+        for (fen, err) in [
+            (
+                "4k3/8/8/8/8/8/8/4K2R w Kk - 0 1",
+                AbbaDingoError::IllegalFenCastlingAvailability,
+            ),
+            (
+                "r4k2/8/8/8/8/8/8/4K3 b q - 0 1",
+                AbbaDingoError::IllegalFenCastlingAvailability,
+            ),
+            (
+                "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1",
+                AbbaDingoError::IllegalFenPosition,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+                AbbaDingoError::IllegalFenPosition,
+            ),
+            (
+                "p3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                AbbaDingoError::IllegalFenPosition,
+            ),
+        ] {
+            assert_eq!(FenRecord::try_from(fen), Err(err), "{}", fen);
+        }
+        assert!(FenRecord::try_from("r3k3/8/8/8/8/8/8/4K2R b Kq - 0 1").is_ok());
+    }
+
+    #[test]
+    fn en_passant_target_shall_follow_a_pawn_double_step() {
+        assert!(FenRecord::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").is_ok());
+        for fen in [
+            // No pawn in front of the target cell
+            "4k3/8/8/8/3p4/8/8/4K3 b - e3 0 1",
+            // The pawn in front of the target cell belongs to the army to move
+            "4k3/8/8/8/3pp3/8/8/4K3 b - e3 0 1",
+            // The target cell is occupied
+            "4k3/8/8/8/3pP3/4N3/8/4K3 b - e3 0 1",
+            // The start cell of the pawn is occupied
+            "4k3/8/8/8/3pP3/8/4N3/4K3 b - e3 0 1",
+            "4k3/3r4/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            assert_eq!(
+                FenRecord::try_from(fen),
+                Err(AbbaDingoError::IllegalFenEnPassantCell),
+                "{}",
+                fen
+            );
+        }
+    }
+}
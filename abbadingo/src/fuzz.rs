@@ -0,0 +1,288 @@
+//! Deterministic entry points for fuzz testing.
+//!
+//! Each function of the module interprets an arbitrary sequence of bytes as
+//! the input of some component of the library and panics (with `assert!`) if
+//! an internal invariant is violated; invalid inputs are simply ignored. The
+//! functions can be called directly from the targets of `cargo fuzz`:
+//!
+//! ```ignore
+//! #![no_main]
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|data: &[u8]| abbadingo::fuzz::fuzz_fen_roundtrip(data));
+//! ```
+//!
+//! The module requires the `fuzz` feature.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::bbdefines::Cell;
+use crate::binarygame::GameRecord;
+use crate::chessdefines::{ArmyColour, ChessPiece, GameResult};
+use crate::chessmove::ChessMove;
+use crate::epd::TestPosition;
+use crate::fenrecord::{FenRecord, INITIAL_STANDARD_POSITION};
+use crate::notation::coordinate::CoordinateMoveList;
+use crate::testpositions::ALL_PERFT_POSITIONS;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// Number of bytes of the header and of each move in the input of fuzz_move_sequence
+const SEQUENCE_HEADER_LEN: usize = 3;
+const SEQUENCE_MOVE_LEN: usize = 4;
+
+const RESULTS: [GameResult; 4] = [
+    GameResult::Ongoing,
+    GameResult::WhiteWins,
+    GameResult::BlackWins,
+    GameResult::Draw,
+];
+const PROMOTIONS: [ChessPiece; 4] = [
+    ChessPiece::Queen,
+    ChessPiece::Rook,
+    ChessPiece::Bishop,
+    ChessPiece::Knight,
+];
+
+// The characters used to mutate the FEN strings: the ones of the FEN syntax,
+// plus the ones needed to build EPD operations
+const FEN_ALPHABET: &[u8; 64] =
+    b"pnbrqkPNBRQK0123456789/-wb abcdefgh;\"hmvcfmnid \t+.xyzABCDEFGHS_!";
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Interprets the input as a sequence of moves and checks their encodings.
+///
+/// The first 3 bytes are a header (bit 0 of the first byte is the army that
+/// plays the first move, bits 1-2 the result of the game, the following two
+/// bytes the first move number); then each group of 4 bytes is a move (moved
+/// piece, start cell, destination cell, taken and promoted pieces).
+///
+/// The function checks that:
+///
+///  - the fields of each [ChessMove] are the ones used to build it;
+///  - the binary encoding of the game ([GameRecord]) is decoded to the same game;
+///  - the coordinate notation of the moves ([CoordinateMoveList]) is parsed
+///    to the same list.
+///
+pub fn fuzz_move_sequence(data: &[u8]) {
+    if data.len() < SEQUENCE_HEADER_LEN {
+        return;
+    }
+    let first_to_move = if data[0] & 0x01 == 0 {
+        ArmyColour::White
+    } else {
+        ArmyColour::Black
+    };
+    let result = RESULTS[((data[0] >> 1) & 0x03) as usize];
+    let first_move_number = u16::from_le_bytes([data[1], data[2]]) as u32 + 1;
+
+    let mut moves = Vec::new();
+    for bytes in data[SEQUENCE_HEADER_LEN..].chunks_exact(SEQUENCE_MOVE_LEN) {
+        let piece: ChessPiece = num::FromPrimitive::from_u8(bytes[0] % 6).unwrap();
+        let start: Cell = num::FromPrimitive::from_u8(bytes[1] & 0x3F).unwrap();
+        let dest: Cell = num::FromPrimitive::from_u8(bytes[2] & 0x3F).unwrap();
+        let taken: Option<ChessPiece> = num::FromPrimitive::from_u8((bytes[3] & 0x0F) % 7);
+        let promoted = PROMOTIONS.get((bytes[3] >> 4) as usize % 5).copied();
+
+        let m = ChessMove::new(piece, start, dest, taken, promoted);
+        assert_eq!(m.moved_piece(), piece);
+        assert_eq!(m.start_cell(), start);
+        assert_eq!(m.destination_cell(), dest);
+        assert_eq!(m.taken_piece(), taken);
+        assert_eq!(m.promoted_piece(), promoted);
+        moves.push(m);
+    }
+
+    let ml = CoordinateMoveList::from_chess_moves(&moves, first_move_number, first_to_move);
+    let text = format!("{}", ml);
+    let parsed = CoordinateMoveList::try_from(text.as_str());
+    if moves.is_empty() {
+        // An empty list is rendered as an empty string, without move numbers
+        assert_eq!(parsed, Ok(CoordinateMoveList::new(1, ArmyColour::White)));
+    } else {
+        assert_eq!(parsed, Ok(ml), "coordinate notation: {}", text);
+    }
+
+    let game = GameRecord {
+        initial_fen: String::from(INITIAL_STANDARD_POSITION),
+        moves,
+        result,
+    };
+    assert_eq!(GameRecord::decode(&game.encode()), Ok(game));
+}
+
+/// Interprets the input as a list of mutations of a known FEN string and
+/// checks that:
+///
+///  - if the mutated string can be loaded as an EPD/FEN record, the complete
+///    FEN string obtained is loaded again to the same FEN string;
+///  - if the mutated string can be loaded as a [FenRecord], the legal moves
+///    of its position can be generated, and the record generated from the
+///    position ([FenRecord::from_board]) is loaded again to the same position.
+///
+/// The first byte selects the base position among the
+/// [ALL_PERFT_POSITIONS]; then each pair
+/// of bytes is a mutation: the first byte is the position in the string, the
+/// second one selects the character and (with its two most significant bits)
+/// the operation: replace, insert or delete the character at the position.
+///
+pub fn fuzz_fen_roundtrip(data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    let base = ALL_PERFT_POSITIONS[data[0] as usize % ALL_PERFT_POSITIONS.len()].fen;
+    let mut fen = Vec::from(base);
+    for mutation in data[1..].chunks_exact(2) {
+        let pos = mutation[0] as usize % (fen.len() + 1);
+        let c = FEN_ALPHABET[(mutation[1] & 0x3F) as usize];
+        match mutation[1] >> 6 {
+            3 => {
+                if pos < fen.len() {
+                    fen.remove(pos);
+                }
+            }
+            op if op == 2 || pos == fen.len() => fen.insert(pos, c),
+            _ => fen[pos] = c,
+        }
+    }
+    // All the characters of the alphabet are ASCII
+    let fen = String::from_utf8(fen).unwrap();
+
+    if let Ok(tp) = TestPosition::try_from(fen.as_str()) {
+        let reloaded = TestPosition::try_from(tp.fen.as_str());
+        assert!(
+            reloaded.is_ok(),
+            "cannot reload {:?} (from {:?})",
+            tp.fen,
+            fen
+        );
+        assert_eq!(reloaded.unwrap().fen, tp.fen, "from {:?}", fen);
+    }
+
+    if let Ok(record) = FenRecord::try_from(fen.as_str()) {
+        let board = record.board();
+        board.legal_moves();
+        let generated = FenRecord::from_board(&board);
+        let reloaded = FenRecord::try_from(generated.fen());
+        assert!(
+            reloaded.is_ok(),
+            "cannot reload {:?} (from {:?})",
+            generated.fen(),
+            fen
+        );
+        assert_eq!(reloaded.unwrap().board(), board, "from {:?}", fen);
+    }
+}
+
+/// Interprets the input as a sequence of legal moves played from a known
+/// position, and checks that the moves are taken back correctly.
+///
+/// The first byte selects the base position among the [ALL_PERFT_POSITIONS];
+/// then each byte selects one of the legal moves of the current position (the
+/// sequence stops if there are no legal moves).
+///
+/// The function checks that:
+///
+///  - the FEN record of each position reached ([FenRecord::from_board]) is
+///    loaded again to the same position;
+///  - unmaking the moves in reverse order restores each previous position.
+///
+pub fn fuzz_make_unmake(data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    let base = ALL_PERFT_POSITIONS[data[0] as usize % ALL_PERFT_POSITIONS.len()].fen;
+    // We can unwrap safely here... the perft positions are valid
+    let mut board = FenRecord::try_from(base).unwrap().board();
+    let mut history = Vec::new();
+    for b in &data[1..] {
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let m = moves[*b as usize % moves.len()];
+        let before = board;
+        let undo = board.make_move(m);
+        history.push((before, m, undo));
+
+        let record = FenRecord::from_board(&board);
+        let reloaded = FenRecord::try_from(record.fen());
+        assert!(
+            reloaded.is_ok(),
+            "cannot reload {:?} (after {})",
+            record.fen(),
+            m.to_uci()
+        );
+        assert_eq!(reloaded.unwrap().board(), board, "{:?}", record.fen());
+    }
+    while let Some((before, m, undo)) = history.pop() {
+        board.unmake_move(m, undo);
+        assert_eq!(board, before, "unmaking {}", m.to_uci());
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::training::CellSampler;
+    use alloc::vec;
+
+    // Runs a fuzz function on inputs generated with a deterministic sampler
+    fn run_on_pseudo_random_inputs(f: fn(&[u8]), seed: u64) {
+        let mut sampler = CellSampler::new(seed);
+        for len in 0..200 {
+            // Each cell index is 6 random bits, enough to build random bytes
+            let data: Vec<u8> = (0..len)
+                .map(|_| ((sampler.next_cell() as u8) << 2) | (sampler.next_cell() as u8 >> 4))
+                .collect();
+            f(&data);
+        }
+    }
+
+    #[test]
+    fn move_sequences_keep_their_encodings() {
+        fuzz_move_sequence(b"");
+        fuzz_move_sequence(b"\xFF\xFF\xFF");
+        fuzz_move_sequence(b"\x01\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF");
+        run_on_pseudo_random_inputs(fuzz_move_sequence, 1);
+    }
+
+    #[test]
+    fn mutated_fens_are_reloaded() {
+        fuzz_fen_roundtrip(b"");
+        // Replace the move counters with a non-numeric hmvc operation
+        let mut data = vec![0];
+        for (ndx, c) in b"hmvc x;".iter().enumerate() {
+            let c = FEN_ALPHABET.iter().position(|a| a == c).unwrap() as u8;
+            data.extend_from_slice(&[(53 + ndx) as u8, c]);
+        }
+        fuzz_fen_roundtrip(&data);
+        // A second white King replacing the b8 Knight
+        fuzz_fen_roundtrip(&[0, 1, 11]);
+        run_on_pseudo_random_inputs(fuzz_fen_roundtrip, 2);
+    }
+
+    #[test]
+    fn legal_moves_are_taken_back() {
+        fuzz_make_unmake(b"");
+        // Always the first legal move, from the initial position
+        fuzz_make_unmake(&[0; 100]);
+        run_on_pseudo_random_inputs(fuzz_make_unmake, 3);
+    }
+}
@@ -0,0 +1,39 @@
+//! Generic traits for two-players, turn-based board games.
+//!
+//! The [Position] and [MoveGen] traits describe a game in the minimal way
+//! needed by game-independent algorithms (e.g. the Monte Carlo Tree Search
+//! of the `mcts` module or the [perft](crate::perft) node counts): a position where moves can be played, that knows
+//! when the game is over, and a generator of the moves.
+//!
+//! The players are assumed to alternate: games where a player can be forced
+//! to skip its turn (e.g. Othello) shall model the pass as a move.
+
+use alloc::vec::Vec;
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// A position of a two-players, turn-based game.
+pub trait Position: Clone {
+    /// The type of the moves of the game.
+    type Move: Copy + PartialEq;
+
+    /// Plays a move in the position. The move shall be one of the moves
+    /// returned by the [MoveGen] of the position.
+    fn make_move(&mut self, m: Self::Move);
+
+    /// Returns the final score of the game for the player to move if the game
+    /// is over (1.0 for a win, 0.5 for a draw, 0.0 for a loss), `None` otherwise.
+    fn terminal_score(&self) -> Option<f64>;
+}
+
+/// The generator of the moves of a [Position].
+pub trait MoveGen: Position {
+    /// Appends to `moves` all the moves that can be played in the position.
+    ///
+    /// No moves shall be generated only if the game is over.
+    fn generate_moves(&self, moves: &mut Vec<Self::Move>);
+}
@@ -40,12 +40,31 @@
 //     So a position in the hexboard is defined by the following trio:
 //        (q, r, -q-r)
 
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::cmp::Reverse;
+#[cfg(feature = "std")]
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+/// The number of neighbours of an [HexCell].
+pub const NUM_HEX_NEIGHBOURS: usize = 6;
+
+/// The (q, r) offsets of the neighbours of an [HexCell], starting from the
+/// East neighbour (+q) and proceeding counter-clockwise.
+pub const HEX_DIRECTIONS: [(i32, i32); NUM_HEX_NEIGHBOURS] =
+    [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+// Scale factor of the fixed point coordinates used to draw the lines: the
+// coordinates are nudged by a few units to break consistently the ties
+// between two cells when the line runs along their common edge.
+const LINE_SCALE: i64 = 1000;
+
 /// A cell inside an hexagons board.
 ///
 /// The position of the cell inside the board is defined using the three coordinates
 /// (q,r,s) as defined in the [Red Blob Games](https://www.redblobgames.com/grids/hexagons/implementation.html)
 /// article.
-#[derive(Default, Debug, PartialEq, Eq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct HexCell {
     pub q: i32,
     pub r: i32,
@@ -77,6 +96,309 @@ impl HexCell {
         self.r = r;
         self.s = -q - r;
     }
+
+    /// Constructor of an [HexCell] in position (q, r, -q-r).
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::hexboard::HexCell;
+    /// assert_eq!(HexCell::from_coords(3, -1), HexCell {q: 3, r: -1, s: -2});
+    /// ```
+    pub fn from_coords(q: i32, r: i32) -> HexCell {
+        HexCell { q, r, s: -q - r }
+    }
+
+    /// Returns the six neighbours of the [HexCell], in the order
+    /// defined by [HEX_DIRECTIONS].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::hexboard::HexCell;
+    /// let n = HexCell::new().neighbours();
+    /// assert_eq!(n[0], HexCell::from_coords(1, 0));
+    /// assert_eq!(n[3], HexCell::from_coords(-1, 0));
+    /// ```
+    pub fn neighbours(&self) -> [HexCell; NUM_HEX_NEIGHBOURS] {
+        let mut n = [*self; NUM_HEX_NEIGHBOURS];
+        for (c, (dq, dr)) in n.iter_mut().zip(HEX_DIRECTIONS.iter()) {
+            c.set(self.q + dq, self.r + dr);
+        }
+        n
+    }
+
+    /// Returns the distance from another [HexCell], i.e. the minimum number
+    /// of steps between adjacent cells needed to reach it.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::hexboard::HexCell;
+    /// assert_eq!(HexCell::new().distance(&HexCell::from_coords(3, -1)), 3);
+    /// assert_eq!(HexCell::from_coords(-2, 2).distance(&HexCell::from_coords(1, 0)), 3);
+    /// ```
+    pub fn distance(&self, other: &HexCell) -> i32 {
+        ((self.q - other.q).abs() + (self.r - other.r).abs() + (self.s - other.s).abs()) / 2
+    }
+}
+
+/// An axial rectangle of the hex board: the cells with the q coordinate in
+/// `q_min..=q_max` and the r coordinate in `r_min..=r_max`.
+///
+/// On screen the rectangle is a parallelogram; it is the natural bounding box
+/// of a set of cells of an "infinite" board, see [bounds].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct HexRect {
+    pub q_min: i32,
+    pub q_max: i32,
+    pub r_min: i32,
+    pub r_max: i32,
+}
+
+impl HexRect {
+    /// Returns true if the [HexCell] is inside the rectangle.
+    pub fn contains(&self, c: &HexCell) -> bool {
+        (self.q_min..=self.q_max).contains(&c.q) && (self.r_min..=self.r_max).contains(&c.r)
+    }
+
+    /// Returns the rectangle enlarged by `margin` cells on each side.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::hexboard::*;
+    /// let rect = HexRect { q_min: 0, q_max: 2, r_min: -1, r_max: -1 };
+    /// assert_eq!(
+    ///     rect.with_margin(1),
+    ///     HexRect { q_min: -1, q_max: 3, r_min: -2, r_max: 0 }
+    /// );
+    /// ```
+    pub fn with_margin(&self, margin: i32) -> HexRect {
+        HexRect {
+            q_min: self.q_min - margin,
+            q_max: self.q_max + margin,
+            r_min: self.r_min - margin,
+            r_max: self.r_max + margin,
+        }
+    }
+
+    /// Returns the number of cells inside the rectangle.
+    pub fn num_cells(&self) -> usize {
+        let side = |min: i32, max: i32| (max - min + 1).max(0) as usize;
+        side(self.q_min, self.q_max) * side(self.r_min, self.r_max)
+    }
+}
+
+/// Returns the cells of the line from the `from` [HexCell] to the `to` [HexCell]
+/// (both included), i.e. the cells crossed by the straight segment joining their
+/// centers. Consecutive cells of the line are adjacent.
+///
+/// When the segment runs along the edge between two cells, the tie is always
+/// broken in the same direction, so the line is deterministic.
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// let l = line(HexCell::new(), HexCell::from_coords(3, -1));
+/// assert_eq!(l.len(), 4);
+/// assert_eq!(l[0], HexCell::new());
+/// assert_eq!(l[3], HexCell::from_coords(3, -1));
+/// ```
+pub fn line(from: HexCell, to: HexCell) -> Vec<HexCell> {
+    let n = from.distance(&to) as i64;
+    if n == 0 {
+        return alloc::vec![from];
+    }
+    let d = n * LINE_SCALE;
+    (0..=n)
+        .map(|i| {
+            // The coordinates of the i-th point of the segment, multiplied by d
+            let lerp =
+                |a: i32, b: i32, nudge: i64| a as i64 * d + (b - a) as i64 * i * LINE_SCALE + nudge;
+            cube_round(
+                lerp(from.q, to.q, 1),
+                lerp(from.r, to.r, 2),
+                lerp(from.s, to.s, -3),
+                d,
+            )
+        })
+        .collect()
+}
+
+/// Returns true if the `to` [HexCell] can be seen from the `from` [HexCell],
+/// i.e. if none of the cells of the [line] between them (the two ends excluded)
+/// is blocked.
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// let wall = |c: &HexCell| c.q == 1 && c.r >= -1;
+/// assert!(!visible_along_line(HexCell::new(), HexCell::from_coords(3, -1), wall));
+/// assert!(visible_along_line(HexCell::new(), HexCell::from_coords(0, -3), wall));
+/// // The blocked ends do not hide each other
+/// assert!(visible_along_line(HexCell::new(), HexCell::from_coords(1, 0), wall));
+/// ```
+pub fn visible_along_line(from: HexCell, to: HexCell, blocked: impl Fn(&HexCell) -> bool) -> bool {
+    let cells = line(from, to);
+    cells.len() < 3 || !cells[1..cells.len() - 1].iter().any(blocked)
+}
+
+/// Rounds fixed point cube coordinates (multiplied by `d`) to the nearest
+/// [HexCell].
+///
+fn cube_round(q: i64, r: i64, s: i64, d: i64) -> HexCell {
+    // Nearest integer of x / d
+    let round = |x: i64| (2 * x + d).div_euclid(2 * d);
+    let (mut rq, mut rr, rs) = (round(q), round(r), round(s));
+    let dq = (rq * d - q).abs();
+    let dr = (rr * d - r).abs();
+    let ds = (rs * d - s).abs();
+    // The coordinate with the largest rounding error is recomputed from the others
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    HexCell::from_coords(rq as i32, rr as i32)
+}
+
+/// Returns the region connected to the `start` [HexCell], i.e. the set of the
+/// cells that can be reached from `start` moving between adjacent passable cells.
+///
+/// The board is defined by the `passable` closure, that shall return `true` for
+/// the cells belonging to the region; since the hex board is infinite, the
+/// closure shall limit the passable cells to a finite set. If `start` is not
+/// passable the empty set is returned.
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// // The cells at distance 1 from the origin, excluding the origin itself
+/// let ring = |c: &HexCell| (c.q.abs() + c.r.abs() + c.s.abs()) / 2 == 1;
+/// assert_eq!(region(HexCell::from_coords(1, 0), ring).len(), 6);
+/// assert!(region(HexCell::new(), ring).is_empty());
+/// ```
+#[cfg(feature = "std")]
+pub fn region(start: HexCell, passable: impl Fn(&HexCell) -> bool) -> HashSet<HexCell> {
+    let mut visited = HashSet::new();
+    if !passable(&start) {
+        return visited;
+    }
+    let mut frontier = VecDeque::new();
+    visited.insert(start);
+    frontier.push_back(start);
+    while let Some(c) = frontier.pop_front() {
+        for n in c.neighbours().iter() {
+            if !visited.contains(n) && passable(n) {
+                visited.insert(*n);
+                frontier.push_back(*n);
+            }
+        }
+    }
+    visited
+}
+
+/// Returns the cells that a unit placed in the `start` [HexCell] can reach
+/// spending at most `budget` movement points, with the minimum cost needed to
+/// reach each of them (the `start` cell is included, with cost 0).
+///
+/// The terrain is defined by the `cost` closure, returning the movement points
+/// needed to enter a cell, or `None` if the cell cannot be entered; the costs
+/// are summed along the path (weighted Dijkstra search).
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// // Entering a cell costs 1, or 3 in the forest (q = 1), and the lake (q = -1) is impassable
+/// let terrain = |c: &HexCell| match c.q {
+///     -1 => None,
+///     1 => Some(3),
+///     _ => Some(1),
+/// };
+/// let range = movement_range(HexCell::new(), 2, terrain);
+/// assert_eq!(range.get(&HexCell::from_coords(0, -2)), Some(&2));
+/// assert_eq!(range.get(&HexCell::from_coords(1, 0)), None);
+/// assert_eq!(range.len(), 5);
+/// ```
+#[cfg(feature = "std")]
+pub fn movement_range(
+    start: HexCell,
+    budget: u32,
+    cost: impl Fn(&HexCell) -> Option<u32>,
+) -> HashMap<HexCell, u32> {
+    let mut reached = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    reached.insert(start, 0);
+    frontier.push(Reverse((0_u32, start.q, start.r)));
+    while let Some(Reverse((spent, q, r))) = frontier.pop() {
+        let c = HexCell::from_coords(q, r);
+        // Skip the stale entries, the cell has already been reached with a lower cost
+        if reached[&c] < spent {
+            continue;
+        }
+        for n in c.neighbours().iter() {
+            if let Some(step) = cost(n) {
+                let total = spent.saturating_add(step);
+                if total <= budget && reached.get(n).is_none_or(|&best| total < best) {
+                    reached.insert(*n, total);
+                    frontier.push(Reverse((total, n.q, n.r)));
+                }
+            }
+        }
+    }
+    reached
+}
+
+/// Returns the smallest [HexRect] containing all the given cells, or `None`
+/// if there are no cells.
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// let cells = [HexCell::from_coords(2, -1), HexCell::from_coords(-1, 3), HexCell::new()];
+/// assert_eq!(
+///     bounds(cells.iter()),
+///     Some(HexRect { q_min: -1, q_max: 2, r_min: -1, r_max: 3 })
+/// );
+/// assert_eq!(bounds([].iter()), None);
+/// ```
+pub fn bounds<'a>(cells: impl IntoIterator<Item = &'a HexCell>) -> Option<HexRect> {
+    cells.into_iter().fold(None, |rect, c| {
+        Some(match rect {
+            None => HexRect {
+                q_min: c.q,
+                q_max: c.q,
+                r_min: c.r,
+                r_max: c.r,
+            },
+            Some(rect) => HexRect {
+                q_min: rect.q_min.min(c.q),
+                q_max: rect.q_max.max(c.q),
+                r_min: rect.r_min.min(c.r),
+                r_max: rect.r_max.max(c.r),
+            },
+        })
+    })
+}
+
+/// Returns an iterator over the cells of the given [HexRect], row by row:
+/// by increasing r coordinate, and by increasing q coordinate inside a row.
+///
+/// # Example
+/// ```
+/// # use abbadingo::hexboard::*;
+/// let rect = HexRect { q_min: 0, q_max: 1, r_min: -1, r_max: 0 };
+/// let cells: Vec<HexCell> = iter_rect(rect).collect();
+/// assert_eq!(
+///     cells,
+///     vec![
+///         HexCell::from_coords(0, -1),
+///         HexCell::from_coords(1, -1),
+///         HexCell::from_coords(0, 0),
+///         HexCell::from_coords(1, 0),
+///     ]
+/// );
+/// ```
+pub fn iter_rect(bounds: HexRect) -> impl Iterator<Item = HexCell> {
+    (bounds.r_min..=bounds.r_max)
+        .flat_map(move |r| (bounds.q_min..=bounds.q_max).map(move |q| HexCell::from_coords(q, r)))
 }
 
 // ****************************************************************************
@@ -87,9 +409,125 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn populated_region_with_margin_is_enumerated() {
+        let populated = [
+            HexCell::from_coords(0, 0),
+            HexCell::from_coords(1, -1),
+            HexCell::from_coords(-1, 2),
+        ];
+        let rect = bounds(populated.iter()).unwrap().with_margin(1);
+        assert_eq!(rect.num_cells(), 5 * 6);
+        let cells: Vec<HexCell> = iter_rect(rect).collect();
+        assert_eq!(cells.len(), rect.num_cells());
+        assert!(cells.iter().all(|c| rect.contains(c)));
+        assert!(populated.iter().all(|c| cells.contains(c)));
+        assert!(populated
+            .iter()
+            .flat_map(|c| c.neighbours())
+            .all(|n| cells.contains(&n)));
+        assert_eq!(cells[0], HexCell::from_coords(-2, -2));
+        assert_eq!(rect.with_margin(-3).num_cells(), 0);
+        assert_eq!(iter_rect(rect.with_margin(-3)).count(), 0);
+    }
+
     #[test]
     fn by_default_a_new_hexcell_is_at_zero_coordinates() {
         let xc = HexCell::new();
         assert_eq!((xc.q, xc.r, xc.s), (0, 0, 0));
     }
+
+    #[test]
+    fn neighbours_are_at_distance_one() {
+        let c = HexCell::from_coords(2, -5);
+        for n in c.neighbours().iter() {
+            assert_eq!(n.q + n.r + n.s, 0);
+            let d = ((n.q - c.q).abs() + (n.r - c.r).abs() + (n.s - c.s).abs()) / 2;
+            assert_eq!(d, 1);
+        }
+    }
+
+    #[test]
+    fn lines_are_made_of_adjacent_cells() {
+        let from = HexCell::from_coords(-3, 1);
+        for q in -4..=4 {
+            for r in -4..=4 {
+                let to = HexCell::from_coords(q, r);
+                let l = line(from, to);
+                assert_eq!(l.len() as i32, from.distance(&to) + 1);
+                assert_eq!((l[0], l[l.len() - 1]), (from, to));
+                for (i, w) in l.windows(2).enumerate() {
+                    assert_eq!(w[0].distance(&w[1]), 1);
+                    assert_eq!(from.distance(&w[1]), i as i32 + 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blockers_hide_the_cells_behind_them() {
+        let blocker = HexCell::from_coords(0, -1);
+        let blocked = |c: &HexCell| *c == blocker;
+        let origin = HexCell::new();
+        assert!(!visible_along_line(
+            origin,
+            HexCell::from_coords(0, -3),
+            blocked
+        ));
+        assert!(visible_along_line(origin, blocker, blocked));
+        assert!(visible_along_line(
+            origin,
+            HexCell::from_coords(2, -3),
+            blocked
+        ));
+        assert!(visible_along_line(origin, origin, |_| true));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn movement_range_uses_the_cheapest_paths() {
+        // On a flat terrain the range is the hexagon with radius equal to the budget
+        let flat = movement_range(HexCell::new(), 3, |_| Some(1));
+        assert_eq!(flat.len(), 37);
+        assert!(flat
+            .iter()
+            .all(|(c, &cost)| c.distance(&HexCell::new()) == cost as i32));
+        assert_eq!(movement_range(HexCell::new(), 0, |_| Some(1)).len(), 1);
+
+        // A road (r = 0) costs 1, the rest of the terrain costs 2
+        let terrain = |c: &HexCell| Some(if c.r == 0 { 1 } else { 2 });
+        let range = movement_range(HexCell::new(), 4, terrain);
+        assert_eq!(range[&HexCell::from_coords(4, 0)], 4);
+        assert_eq!(range[&HexCell::from_coords(2, -1)], 3);
+        assert_eq!(range[&HexCell::from_coords(0, 2)], 4);
+        assert!(!range.contains_key(&HexCell::from_coords(0, 3)));
+        assert_eq!(range[&HexCell::from_coords(3, -1)], 4);
+
+        // The cost is the one of the cheapest path, not of the shortest one:
+        // the only 2 steps path to (2, 0) crosses the swamp in (1, 0)
+        let swamp = |c: &HexCell| {
+            Some(if *c == HexCell::from_coords(1, 0) {
+                5
+            } else {
+                1
+            })
+        };
+        let range = movement_range(HexCell::new(), 5, swamp);
+        assert_eq!(range[&HexCell::from_coords(2, 0)], 3);
+        assert_eq!(range[&HexCell::from_coords(1, 0)], 5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn region_is_limited_by_walls() {
+        // Hexagonal board of radius 3, split in two by a wall along q = 0
+        let board = |c: &HexCell| c.q.abs() <= 3 && c.r.abs() <= 3 && c.s.abs() <= 3;
+        let passable = |c: &HexCell| board(c) && c.q != 0;
+        let east = region(HexCell::from_coords(2, 0), passable);
+        assert_eq!(east.len(), 15);
+        assert!(east.iter().all(|c| c.q > 0));
+        // Opening a hole in the wall joins the two halves
+        let passable = |c: &HexCell| board(c) && (c.q != 0 || c.r == 0);
+        assert_eq!(region(HexCell::from_coords(2, 0), passable).len(), 31);
+    }
 }
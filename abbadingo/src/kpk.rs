@@ -0,0 +1,359 @@
+//! King and Pawn vs King (KPK) endgame bitbase.
+//!
+//! The [KpkBitbase] stores, for every legal KPK position, whether the side
+//! with the pawn wins (1 bit per position). The bitbase is generated by
+//! retrograde analysis starting from the positions with a known result
+//! (promotions, stalemates, pawn captured) and propagating the results until
+//! no more positions change.
+//!
+//! The generation takes a noticeable time (especially in debug builds), so
+//! it should be performed only once; with the `std` feature the [probe_kpk]
+//! function uses a lazily generated shared instance.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::kpk::*;
+//! let kpk = KpkBitbase::generate();
+//! let (w, b) = (ArmyColour::White, ArmyColour::Black);
+//! // King in front of its pawn on the 6th rank: wins whoever is to move
+//! assert_eq!(kpk.probe(w, b, Cell::E6, Cell::E5, Cell::E8), Some(true));
+//! // Rook pawn with the defending king in the corner: draw
+//! assert_eq!(kpk.probe(b, b, Cell::C6, Cell::A5, Cell::A1), Some(false));
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::attacktables::*;
+use crate::bbdefines::*;
+use crate::chessdefines::ArmyColour;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// Number of positions in the bitbase: side to move (2) x weak king cell (64) x
+// strong king cell (64) x pawn file (a to d, 4) x pawn rank (2 to 7, 6).
+const KPK_SIZE: usize = 2 * NUM_CELLS * NUM_CELLS * 4 * 6;
+
+// Classification of the positions during the generation.
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+// The shared bitbase used by probe_kpk(), generated at the first use.
+#[cfg(feature = "std")]
+static KPK: std::sync::OnceLock<KpkBitbase> = std::sync::OnceLock::new();
+
+/// The KPK bitbase.
+///
+/// Positions are stored normalized, with the strong side (the one with the pawn)
+/// playing White and the pawn on the files a to d.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KpkBitbase {
+    bits: Vec<u64>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl KpkBitbase {
+    /// Generates the KPK bitbase.
+    pub fn generate() -> KpkBitbase {
+        let mut db: Vec<u8> = (0..KPK_SIZE).map(initial_classification).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for ndx in 0..KPK_SIZE {
+                if db[ndx] == UNKNOWN {
+                    let r = classify(&db, ndx);
+                    if r != UNKNOWN {
+                        db[ndx] = r;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let mut bits = vec![0; KPK_SIZE / 64];
+        for (ndx, r) in db.iter().enumerate() {
+            if *r == WIN {
+                bits[ndx / 64] |= 1 << (ndx % 64);
+            }
+        }
+        KpkBitbase { bits }
+    }
+
+    /// Probes the bitbase, returning `Some(true)` if the `strong_side` (the army
+    /// with the pawn) wins the position, `Some(false)` if the position is a draw,
+    /// `None` if the pawn is on the first or last rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `strong_side`: The [ArmyColour] of the army with the pawn
+    /// * `to_move`: The [ArmyColour] of the army to move
+    /// * `strong_king`: The [Cell] of the king of the strong side
+    /// * `pawn`: The [Cell] of the pawn
+    /// * `weak_king`: The [Cell] of the king of the weak side
+    ///
+    /// For the other illegal positions (e.g. adjacent kings) the result is
+    /// meaningless.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::kpk::*;
+    /// let kpk = KpkBitbase::generate();
+    /// let w = ArmyColour::White;
+    /// assert_eq!(kpk.probe(w, w, Cell::E6, Cell::E5, Cell::E8), Some(true));
+    /// assert_eq!(kpk.probe(w, w, Cell::E6, Cell::E8, Cell::A8), None);
+    /// ```
+    ///
+    pub fn probe(
+        &self,
+        strong_side: ArmyColour,
+        to_move: ArmyColour,
+        strong_king: Cell,
+        pawn: Cell,
+        weak_king: Cell,
+    ) -> Option<bool> {
+        let (mut sk, mut p, mut wk) = (strong_king as usize, pawn as usize, weak_king as usize);
+        if strong_side == ArmyColour::Black {
+            // Flip the board vertically so that the pawn moves north
+            sk ^= 56;
+            p ^= 56;
+            wk ^= 56;
+        }
+        if p % NUM_FILES >= 4 {
+            // Mirror the board horizontally to have the pawn on files a to d
+            sk ^= 7;
+            p ^= 7;
+            wk ^= 7;
+        }
+        if !(NUM_FILES..NUM_CELLS - NUM_FILES).contains(&p) {
+            return None;
+        }
+        let ndx = index(to_move != strong_side, wk, sk, p);
+        Some(self.bits[ndx / 64] & (1 << (ndx % 64)) != 0)
+    }
+}
+
+/// Probes the shared KPK bitbase (generated at the first call), see
+/// [KpkBitbase::probe] for the meaning of the arguments and of the result.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::kpk::*;
+/// // Pawn e4, kings in opposition on e5 and e7: the side to move loses the opposition
+/// let w = ArmyColour::White;
+/// assert_eq!(probe_kpk(w, ArmyColour::Black, Cell::E5, Cell::E4, Cell::E7), Some(true));
+/// assert_eq!(probe_kpk(w, w, Cell::E5, Cell::E4, Cell::E7), Some(false));
+/// ```
+#[cfg(feature = "std")]
+pub fn probe_kpk(
+    strong_side: ArmyColour,
+    to_move: ArmyColour,
+    strong_king: Cell,
+    pawn: Cell,
+    weak_king: Cell,
+) -> Option<bool> {
+    KPK.get_or_init(KpkBitbase::generate)
+        .probe(strong_side, to_move, strong_king, pawn, weak_king)
+}
+
+/// Generates the shared KPK bitbase used by [probe_kpk], if not already done.
+///
+/// The bitbase is generated anyway at the first call of [probe_kpk]: this
+/// function can be used to pay the generation cost in advance.
+#[cfg(feature = "std")]
+pub fn init_kpk() {
+    KPK.get_or_init(KpkBitbase::generate);
+}
+
+/// Returns true if the shared KPK bitbase used by [probe_kpk] has already been generated.
+#[cfg(feature = "std")]
+pub fn kpk_is_initialized() -> bool {
+    KPK.get().is_some()
+}
+
+// ---------------------------------------------------------------------------
+// PRIVATE FUNCTIONS (bitbase generation)
+// ---------------------------------------------------------------------------
+
+/// Computes the index in the bitbase of a normalized position.
+///
+/// # Arguments
+///
+/// * `weak_to_move`: true if the weak side (the one without pawn) is to move
+/// * `wk`, `sk`, `p`: the indexes of the cells of the weak king, of the strong
+///   king and of the pawn (that has to be on the files a to d, ranks 2 to 7)
+///
+fn index(weak_to_move: bool, wk: usize, sk: usize, p: usize) -> usize {
+    weak_to_move as usize
+        | wk << 1
+        | sk << 7
+        | (p % NUM_FILES) << 13
+        | (NUM_RANKS - 2 - p / NUM_FILES) << 15
+}
+
+/// Decodes an index of the bitbase into its components
+/// (weak side to move, weak king, strong king, pawn).
+///
+fn decode(ndx: usize) -> (bool, usize, usize, usize) {
+    let p_file = (ndx >> 13) & 0x3;
+    let p_rank = NUM_RANKS - 2 - (ndx >> 15);
+    (
+        ndx & 1 != 0,
+        (ndx >> 1) & 0x3F,
+        (ndx >> 7) & 0x3F,
+        p_rank * NUM_FILES + p_file,
+    )
+}
+
+/// Classifies a position using only the position itself (without looking at
+/// the successors): illegal positions, immediate promotions and immediate
+/// draws (stalemate or pawn captured).
+///
+fn initial_classification(ndx: usize) -> u8 {
+    let (weak_to_move, wk, sk, p) = decode(ndx);
+    let pawn_attacks = PAWN_ATTACKS[ArmyColour::White as usize][p];
+
+    if wk == sk
+        || wk == p
+        || sk == p
+        || KING_ATTACKS[sk] & (1 << wk) != 0
+        || (!weak_to_move && pawn_attacks & (1 << wk) != 0)
+    {
+        return INVALID;
+    }
+
+    if !weak_to_move && p / NUM_FILES == NUM_RANKS - 2 {
+        // The pawn can promote: the promotion wins if the new queen
+        // cannot be captured
+        let promotion = p + NUM_FILES;
+        if promotion != sk
+            && promotion != wk
+            && (KING_ATTACKS[wk] & (1 << promotion) == 0
+                || KING_ATTACKS[sk] & (1 << promotion) != 0)
+        {
+            return WIN;
+        }
+    }
+
+    if weak_to_move {
+        let safe_moves = KING_ATTACKS[wk] & !(KING_ATTACKS[sk] | pawn_attacks);
+        if safe_moves == EMPTY_STATE {
+            // Stalemate
+            return DRAW;
+        }
+        if KING_ATTACKS[wk] & !KING_ATTACKS[sk] & (1 << p) != 0 {
+            // The pawn can be captured
+            return DRAW;
+        }
+    }
+
+    UNKNOWN
+}
+
+/// Classifies a position looking at the classification of its successors.
+///
+/// If the strong side is to move, the position is won if at least one of the
+/// successors is won, drawn if all of them are drawn; if the weak side is to
+/// move, the position is drawn if at least one of the successors is drawn,
+/// won if all of them are won. Invalid successors (illegal moves) are ignored.
+///
+fn classify(db: &[u8], ndx: usize) -> u8 {
+    let (weak_to_move, wk, sk, p) = decode(ndx);
+    let mut r = INVALID;
+    if weak_to_move {
+        let mut moves = KING_ATTACKS[wk];
+        while moves != EMPTY_STATE {
+            let dest = moves.trailing_zeros() as usize;
+            moves &= moves - 1;
+            r |= db[index(false, dest, sk, p)];
+        }
+    } else {
+        let mut moves = KING_ATTACKS[sk];
+        while moves != EMPTY_STATE {
+            let dest = moves.trailing_zeros() as usize;
+            moves &= moves - 1;
+            r |= db[index(true, wk, dest, p)];
+        }
+        let p_rank = p / NUM_FILES;
+        if p_rank < NUM_RANKS - 2 {
+            r |= db[index(true, wk, sk, p + NUM_FILES)];
+        }
+        if p_rank == 1 && p + NUM_FILES != wk && p + NUM_FILES != sk {
+            r |= db[index(true, wk, sk, p + 2 * NUM_FILES)];
+        }
+    }
+
+    let (good, bad) = if weak_to_move {
+        (DRAW, WIN)
+    } else {
+        (WIN, DRAW)
+    };
+    if r & good != 0 {
+        good
+    } else if r & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn index_and_decode_are_consistent() {
+        for ndx in [0, 1, 12345, 98765, KPK_SIZE - 1] {
+            let (weak_to_move, wk, sk, p) = decode(ndx);
+            assert_eq!(index(weak_to_move, wk, sk, p), ndx);
+        }
+    }
+
+    #[test]
+    fn kpk_bitbase_probes() {
+        let kpk = KpkBitbase::generate();
+        let w = ArmyColour::White;
+        let b = ArmyColour::Black;
+
+        // Key square occupied by the strong king: win
+        assert_eq!(kpk.probe(w, w, Cell::E6, Cell::E4, Cell::E8), Some(true));
+        assert_eq!(kpk.probe(w, b, Cell::E6, Cell::E4, Cell::E8), Some(true));
+        // Opposition
+        assert_eq!(kpk.probe(w, b, Cell::E5, Cell::E4, Cell::E7), Some(true));
+        assert_eq!(kpk.probe(w, w, Cell::E5, Cell::E4, Cell::E7), Some(false));
+        // Same positions with colours swapped and mirrored files
+        assert_eq!(kpk.probe(b, w, Cell::D4, Cell::D5, Cell::D2), Some(true));
+        assert_eq!(kpk.probe(b, b, Cell::D4, Cell::D5, Cell::D2), Some(false));
+        // Pawn outside the square of the defending king
+        assert_eq!(kpk.probe(w, b, Cell::H1, Cell::A4, Cell::H8), Some(true));
+        // Pawn that can be captured
+        assert_eq!(kpk.probe(w, b, Cell::H1, Cell::E4, Cell::E5), Some(false));
+        // Rook pawn with the defending king in front of it
+        assert_eq!(kpk.probe(w, w, Cell::C3, Cell::A4, Cell::A8), Some(false));
+        assert_eq!(kpk.probe(w, w, Cell::F6, Cell::H5, Cell::H8), Some(false));
+        // Pawn on the first or last rank
+        assert_eq!(kpk.probe(w, w, Cell::E3, Cell::B1, Cell::H8), None);
+        assert_eq!(kpk.probe(b, b, Cell::E3, Cell::G8, Cell::A1), None);
+        assert_eq!(kpk.probe(b, w, Cell::E3, Cell::C1, Cell::A8), None);
+    }
+}
@@ -2,16 +2,48 @@
 //! implement board games related software (e.g. chess). The library for example
 //! defines structures that can be used represent a chessboard, but also other types
 //! of boards, using a bitboard-based approach.
+//!
+//! The crate is `no_std` + `alloc` compatible: the terminal rendering of the
+//! boards (based on `ansi_term`) and everything requiring I/O is available only
+//! with the `std` feature, enabled by default.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 extern crate num;
 #[macro_use]
 extern crate num_derive;
 
+pub mod attacktables;
+#[cfg(feature = "std")]
+pub mod batch;
 pub mod bbdefines;
+pub mod binarygame;
 pub mod bitboard;
 pub mod chessarmy;
+pub mod chessboard;
 pub mod chessdefines;
 pub mod chessmove;
+pub mod clock;
+pub mod dtm;
+pub mod epd;
 pub mod error;
 pub mod fenrecord;
+#[cfg(any(feature = "fuzz", test))]
+pub mod fuzz;
+pub mod game;
 pub mod hexboard;
+pub mod kpk;
+#[cfg(feature = "std")]
+pub mod magics;
+#[cfg(feature = "std")]
+pub mod mcts;
+pub mod notation;
+pub mod perft;
+pub mod pgn;
+pub mod slidingattacks;
+pub mod testpositions;
+pub mod training;
+#[cfg(feature = "std")]
+pub mod uci;
+pub mod variants;
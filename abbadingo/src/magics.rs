@@ -0,0 +1,324 @@
+//! Magic bitboards for the computation of the attacks of sliding pieces.
+//!
+//! For each [Cell] the occupancy of the cells relevant for the attacks of a
+//! sliding piece (its lines, without the edges of the board) is mapped onto an
+//! index in a table of precomputed attacks, multiplying it by a "magic" number
+//! and keeping the most significant bits of the product. See the
+//! [Magic Bitboards](https://www.chessprogramming.org/Magic_Bitboards) page
+//! in the chess programming wiki for details.
+//!
+//! The magic numbers are searched with a deterministic pseudo-random sequence
+//! when the [MagicTables] are generated. The tables shared by the [MagicAttacks]
+//! backend are generated at the first use (or by [init_magics]).
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::magics::*;
+//! # use abbadingo::slidingattacks::*;
+//! let occupancy = single_cell(Cell::D6) | single_cell(Cell::F4) | single_cell(Cell::B2);
+//! assert_eq!(
+//!     MagicAttacks::queen_attacks(Cell::D4, occupancy),
+//!     ClassicalAttacks::queen_attacks(Cell::D4, occupancy)
+//! );
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::attacktables::RAYS;
+use crate::bbdefines::*;
+use crate::slidingattacks::{ClassicalAttacks, SlidingAttacks};
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+const MAGICS_SEED: u64 = 0x5DEE_CE66_D1CE_4E5B;
+
+// The shared tables used by MagicAttacks, generated at the first use.
+static MAGICS: std::sync::OnceLock<MagicTables> = std::sync::OnceLock::new();
+
+/// The magic lookup data of a [Cell] for a sliding piece.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Magic {
+    /// The cells whose occupancy changes the attacks.
+    pub mask: BitBoardState,
+    /// The magic multiplier.
+    pub magic: u64,
+    /// The shift applied to the product, 64 minus the number of index bits.
+    pub shift: u32,
+    offset: usize,
+}
+
+/// The magic numbers and the attacks tables for bishops and rooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagicTables {
+    bishops: [Magic; NUM_CELLS],
+    rooks: [Magic; NUM_CELLS],
+    attacks: Vec<BitBoardState>,
+}
+
+/// Sliding attacks computed using the shared [MagicTables].
+pub struct MagicAttacks;
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl Magic {
+    /// Returns the index in the attacks table for the given occupancy.
+    ///
+    fn index(&self, occupancy: BitBoardState) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+impl MagicTables {
+    /// Searches the magic numbers and fills the attacks tables.
+    ///
+    /// The search is deterministic: the same tables are generated at each call.
+    pub fn generate() -> MagicTables {
+        let mut tables = MagicTables {
+            bishops: [Magic::default(); NUM_CELLS],
+            rooks: [Magic::default(); NUM_CELLS],
+            attacks: Vec::new(),
+        };
+        let mut seed = MAGICS_SEED;
+        for ndx in 0..NUM_CELLS {
+            // We can unwrap safely here... ndx is always a valid cell
+            let c: Cell = num::FromPrimitive::from_usize(ndx).unwrap();
+            tables.bishops[ndx] = MagicTables::find_magic(
+                &mut tables.attacks,
+                bishop_relevant_cells(c),
+                |occ| ClassicalAttacks::bishop_attacks(c, occ),
+                &mut seed,
+            );
+            tables.rooks[ndx] = MagicTables::find_magic(
+                &mut tables.attacks,
+                rook_relevant_cells(c),
+                |occ| ClassicalAttacks::rook_attacks(c, occ),
+                &mut seed,
+            );
+        }
+        tables
+    }
+
+    /// Returns the [Magic] used for the bishops placed in the given [Cell].
+    pub fn bishop_magic(&self, c: Cell) -> Magic {
+        self.bishops[c as usize]
+    }
+
+    /// Returns the [Magic] used for the rooks placed in the given [Cell].
+    pub fn rook_magic(&self, c: Cell) -> Magic {
+        self.rooks[c as usize]
+    }
+
+    /// Returns the cells attacked by a bishop placed in `c` with the given board occupancy.
+    pub fn bishop_attacks(&self, c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        self.attacks[self.bishops[c as usize].index(occupancy)]
+    }
+
+    /// Returns the cells attacked by a rook placed in `c` with the given board occupancy.
+    pub fn rook_attacks(&self, c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        self.attacks[self.rooks[c as usize].index(occupancy)]
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Searches a magic number for the given relevant cells mask, and appends
+    /// the corresponding attacks to the `attacks` table.
+    ///
+    /// # Arguments
+    ///
+    /// * `attacks`: The attacks table
+    /// * `mask`: The relevant cells mask
+    /// * `reference`: Function computing the attacks for an occupancy
+    /// * `seed`: The state of the pseudo-random sequence
+    ///
+    fn find_magic<F: Fn(BitBoardState) -> BitBoardState>(
+        attacks: &mut Vec<BitBoardState>,
+        mask: BitBoardState,
+        reference: F,
+        seed: &mut u64,
+    ) -> Magic {
+        let bits = mask.count_ones();
+        // All the subsets of the mask (Carry-Rippler enumeration) and their attacks
+        let mut occupancies = Vec::with_capacity(1 << bits);
+        let mut occ = EMPTY_STATE;
+        loop {
+            occupancies.push((occ, reference(occ)));
+            occ = occ.wrapping_sub(mask) & mask;
+            if occ == EMPTY_STATE {
+                break;
+            }
+        }
+
+        let mut table = vec![EMPTY_STATE; 1 << bits];
+        let mut epoch = vec![0_u32; 1 << bits];
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            // Magics with few active bits are more likely to work
+            let magic = next_random(seed) & next_random(seed) & next_random(seed);
+            // Discard the magics not mapping the mask bits on the index bits
+            if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+                continue;
+            }
+            let candidate = Magic {
+                mask,
+                magic,
+                shift: 64 - bits,
+                offset: 0,
+            };
+            // A collision is allowed only between occupancies with the same attacks
+            let fits = occupancies.iter().all(|&(occ, att)| {
+                let ndx = candidate.index(occ);
+                if epoch[ndx] != attempt {
+                    epoch[ndx] = attempt;
+                    table[ndx] = att;
+                    true
+                } else {
+                    table[ndx] == att
+                }
+            });
+            if fits {
+                let magic = Magic {
+                    offset: attacks.len(),
+                    ..candidate
+                };
+                attacks.extend_from_slice(&table);
+                return magic;
+            }
+        }
+    }
+}
+
+/// Returns the cells whose occupancy changes the attacks of a bishop placed
+/// in the given [Cell]: its diagonals, without the edges of the board.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::magics::*;
+/// assert_eq!(bishop_relevant_cells(Cell::A1).count_ones(), 6);
+/// assert_eq!(bishop_relevant_cells(Cell::E4).count_ones(), 9);
+/// ```
+pub fn bishop_relevant_cells(c: Cell) -> BitBoardState {
+    let edges = RANKS_BBS[Rank::Rank1 as usize]
+        | RANKS_BBS[Rank::Rank8 as usize]
+        | FILES_BBS[File::FileA as usize]
+        | FILES_BBS[File::FileH as usize];
+    (RAYS[Direction::NorthEast as usize][c as usize]
+        | RAYS[Direction::SouthEast as usize][c as usize]
+        | RAYS[Direction::SouthWest as usize][c as usize]
+        | RAYS[Direction::NorthWest as usize][c as usize])
+        & !edges
+}
+
+/// Returns the cells whose occupancy changes the attacks of a rook placed
+/// in the given [Cell]: its rank and file, without the last cell of each ray.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::magics::*;
+/// assert_eq!(rook_relevant_cells(Cell::A1).count_ones(), 12);
+/// assert_eq!(rook_relevant_cells(Cell::E4).count_ones(), 10);
+/// ```
+pub fn rook_relevant_cells(c: Cell) -> BitBoardState {
+    (RAYS[Direction::North as usize][c as usize] & !RANKS_BBS[Rank::Rank8 as usize])
+        | (RAYS[Direction::South as usize][c as usize] & !RANKS_BBS[Rank::Rank1 as usize])
+        | (RAYS[Direction::East as usize][c as usize] & !FILES_BBS[File::FileH as usize])
+        | (RAYS[Direction::West as usize][c as usize] & !FILES_BBS[File::FileA as usize])
+}
+
+/// Generates the shared [MagicTables] used by [MagicAttacks], if not already done.
+///
+/// The tables are generated anyway at the first use of [MagicAttacks]: this
+/// function can be used to pay the generation cost in advance.
+pub fn init_magics() {
+    MAGICS.get_or_init(MagicTables::generate);
+}
+
+/// Returns true if the shared [MagicTables] used by [MagicAttacks] have already been generated.
+pub fn magics_are_initialized() -> bool {
+    MAGICS.get().is_some()
+}
+
+/// Returns the next value of the xorshift64* pseudo-random sequence.
+///
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for MagicAttacks structure
+
+impl SlidingAttacks for MagicAttacks {
+    fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        MAGICS
+            .get_or_init(MagicTables::generate)
+            .bishop_attacks(c, occupancy)
+    }
+
+    fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        MAGICS
+            .get_or_init(MagicTables::generate)
+            .rook_attacks(c, occupancy)
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn magic_attacks_match_the_classical_ones() {
+        let tables = MagicTables::generate();
+        let mut seed = 0x1234_5678_9ABC_DEF0;
+        for _ in 0..64 {
+            let occupancy = next_random(&mut seed) & next_random(&mut seed);
+            for ndx in 0..NUM_CELLS {
+                let c: Cell = num::FromPrimitive::from_usize(ndx).unwrap();
+                assert_eq!(
+                    tables.bishop_attacks(c, occupancy),
+                    ClassicalAttacks::bishop_attacks(c, occupancy)
+                );
+                assert_eq!(
+                    tables.rook_attacks(c, occupancy),
+                    ClassicalAttacks::rook_attacks(c, occupancy)
+                );
+            }
+        }
+        // The whole table is used: 5248 entries for bishops, 102400 for rooks
+        assert_eq!(tables.attacks.len(), 5248 + 102400);
+        assert_eq!(tables, MagicTables::generate());
+    }
+
+    #[test]
+    fn shared_tables_are_generated_at_first_use() {
+        assert_eq!(
+            MagicAttacks::rook_attacks(Cell::A1, single_cell(Cell::A4)),
+            RAYS[Direction::East as usize][Cell::A1 as usize]
+                | single_cell(Cell::A2)
+                | single_cell(Cell::A3)
+                | single_cell(Cell::A4)
+        );
+        assert!(magics_are_initialized());
+        assert_eq!(MAGICS.get().unwrap().rook_magic(Cell::H8).shift, 64 - 12);
+    }
+}
@@ -0,0 +1,424 @@
+//! Monte Carlo Tree Search over the generic game traits.
+//!
+//! [Mcts] implements the UCT algorithm (Upper Confidence bounds applied to
+//! Trees) for any game implementing the [Position](crate::game::Position) and
+//! [MoveGen] traits: the tree is grown one node per iteration, and each new
+//! node is evaluated with a playout, i.e. a sequence of moves chosen by a
+//! [PlayoutPolicy] supplied by the caller until the game is over (or a maximum
+//! number of moves is reached).
+//!
+//! The module requires the `std` feature.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::game::*;
+//! # use abbadingo::mcts::*;
+//! // Two players alternate taking 1 or 2 stones: who takes the last one wins.
+//! #[derive(Clone)]
+//! struct Nim(u32);
+//! impl Position for Nim {
+//!     type Move = u32;
+//!     fn make_move(&mut self, m: u32) {
+//!         self.0 -= m;
+//!     }
+//!     fn terminal_score(&self) -> Option<f64> {
+//!         // The opponent took the last stone
+//!         if self.0 == 0 { Some(0.0) } else { None }
+//!     }
+//! }
+//! impl MoveGen for Nim {
+//!     fn generate_moves(&self, moves: &mut Vec<u32>) {
+//!         moves.extend((1..=2).filter(|m| *m <= self.0));
+//!     }
+//! }
+//!
+//! let mut mcts = Mcts::new(MctsConfig::default(), RandomPlayout::new(7));
+//! // Leave a multiple of 3 stones to the opponent
+//! assert_eq!(mcts.search(&Nim(7), 2000), Some(1));
+//! ```
+
+use crate::game::MoveGen;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+// Score of the playouts ended without a result
+const DRAW_SCORE: f64 = 0.5;
+
+/// The parameters of the [Mcts] search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// The exploration constant of the UCT formula (the theoretical value
+    /// is the square root of 2).
+    pub exploration: f64,
+    /// The maximum number of moves of each playout; when it is reached the
+    /// position is scored with [PlayoutPolicy::evaluate].
+    pub max_playout_moves: usize,
+}
+
+/// The statistics of a move of the root position after a search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveStats<M> {
+    pub m: M,
+    /// The number of iterations that explored the move.
+    pub visits: u32,
+    /// The mean score of the move for the player to move in the root position.
+    pub score: f64,
+}
+
+/// A node of the search tree.
+#[derive(Debug, Clone)]
+struct Node<M> {
+    parent: Option<usize>,
+    m: Option<M>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    visits: u32,
+    // Sum of the scores for the player that played the move leading to the node
+    score: f64,
+}
+
+/// The policy used to choose the moves of the playouts.
+pub trait PlayoutPolicy<P: MoveGen> {
+    /// Chooses one of the `moves` that can be played in `position`, returning
+    /// its index. The `moves` are never empty.
+    fn choose(&mut self, position: &P, moves: &[P::Move]) -> usize;
+
+    /// Returns the score for the player to move of a position where a playout
+    /// has been stopped before the end of the game (see
+    /// [MctsConfig::max_playout_moves]). By default the position is a draw.
+    fn evaluate(&mut self, _position: &P) -> f64 {
+        DRAW_SCORE
+    }
+}
+
+/// A [PlayoutPolicy] choosing the moves uniformly at random.
+///
+/// A `xorshift64*` generator is used: the playouts only depend on the seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomPlayout {
+    state: u64,
+}
+
+/// The Monte Carlo Tree Search.
+///
+/// The tree of the last search is kept until the next one, to get the
+/// statistics of the moves with [Mcts::root_statistics].
+#[derive(Debug, Clone)]
+pub struct Mcts<P: MoveGen, R> {
+    config: MctsConfig,
+    policy: R,
+    nodes: Vec<Node<P::Move>>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            exploration: std::f64::consts::SQRT_2,
+            max_playout_moves: 1000,
+        }
+    }
+}
+
+impl RandomPlayout {
+    /// Creates a new random policy with the given seed.
+    pub fn new(seed: u64) -> RandomPlayout {
+        // The xorshift state cannot be zero
+        RandomPlayout {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    /// Returns a random number in the range `0..n`.
+    ///
+    fn next_index(&mut self, n: usize) -> usize {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        // The high bits of the xorshift64* output are the best ones
+        ((self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32) as usize) % n
+    }
+}
+
+impl<P: MoveGen> PlayoutPolicy<P> for RandomPlayout {
+    fn choose(&mut self, _position: &P, moves: &[P::Move]) -> usize {
+        self.next_index(moves.len())
+    }
+}
+
+impl<P: MoveGen, R: PlayoutPolicy<P>> Mcts<P, R> {
+    /// Creates a new search with the given configuration and playout policy.
+    pub fn new(config: MctsConfig, policy: R) -> Mcts<P, R> {
+        Mcts {
+            config,
+            policy,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Searches the `root` position for the given number of iterations and returns
+    /// the most explored move, or `None` if the game is over.
+    pub fn search(&mut self, root: &P, iterations: usize) -> Option<P::Move> {
+        self.nodes.clear();
+        self.nodes.push(Node::new(None, None, root));
+        for _ in 0..iterations {
+            self.iterate(root);
+        }
+        self.root_statistics()
+            .iter()
+            .max_by_key(|s| s.visits)
+            .map(|s| s.m)
+    }
+
+    /// Returns the statistics of the moves of the root position explored by
+    /// the last search.
+    pub fn root_statistics(&self) -> Vec<MoveStats<P::Move>> {
+        match self.nodes.first() {
+            Some(root) => root
+                .children
+                .iter()
+                .map(|ndx| {
+                    let child = &self.nodes[*ndx];
+                    MoveStats {
+                        // We can unwrap safely here... only the root has no move
+                        m: child.m.unwrap(),
+                        visits: child.visits,
+                        score: child.score / child.visits as f64,
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Performs an iteration of the search: selection, expansion, playout and
+    /// backpropagation of the result.
+    ///
+    fn iterate(&mut self, root: &P) {
+        let mut position = root.clone();
+        let mut ndx = 0;
+
+        // Selection: descend the fully expanded nodes
+        while self.nodes[ndx].untried.is_empty() && !self.nodes[ndx].children.is_empty() {
+            ndx = self.best_child(ndx);
+            // We can unwrap safely here... only the root has no move
+            position.make_move(self.nodes[ndx].m.unwrap());
+        }
+
+        // Expansion: add a node for one of the moves not tried yet
+        if !self.nodes[ndx].untried.is_empty() {
+            let untried = &mut self.nodes[ndx].untried;
+            let m = untried.swap_remove(self.policy.choose(&position, untried));
+            position.make_move(m);
+            let child = Node::new(Some(ndx), Some(m), &position);
+            self.nodes.push(child);
+            let child_ndx = self.nodes.len() - 1;
+            self.nodes[ndx].children.push(child_ndx);
+            ndx = child_ndx;
+        }
+
+        // Playout and backpropagation: the score of a node is for the player
+        // that moved into the node, i.e. the opponent of the one to move there
+        let mut score = 1.0 - self.playout(position);
+        let mut node = Some(ndx);
+        while let Some(n) = node {
+            self.nodes[n].visits += 1;
+            self.nodes[n].score += score;
+            score = 1.0 - score;
+            node = self.nodes[n].parent;
+        }
+    }
+
+    /// Returns the child of a node with the best UCT value.
+    ///
+    fn best_child(&self, ndx: usize) -> usize {
+        let log_visits = (self.nodes[ndx].visits as f64).ln();
+        let uct = |child: &usize| {
+            let c = &self.nodes[*child];
+            let visits = c.visits as f64;
+            c.score / visits + self.config.exploration * (log_visits / visits).sqrt()
+        };
+        // We can unwrap safely here... the node has children and the values are not NaN
+        *self.nodes[ndx]
+            .children
+            .iter()
+            .max_by(|a, b| uct(a).partial_cmp(&uct(b)).unwrap())
+            .unwrap()
+    }
+
+    /// Plays a playout from the given position, returning the final score
+    /// for the player to move in the position.
+    ///
+    fn playout(&mut self, mut position: P) -> f64 {
+        let mut moves = Vec::new();
+        for played in 0..=self.config.max_playout_moves {
+            let score = match position.terminal_score() {
+                Some(score) => Some(score),
+                None if played == self.config.max_playout_moves => {
+                    Some(self.policy.evaluate(&position))
+                }
+                None => {
+                    moves.clear();
+                    position.generate_moves(&mut moves);
+                    if moves.is_empty() {
+                        Some(DRAW_SCORE)
+                    } else {
+                        position.make_move(moves[self.policy.choose(&position, &moves)]);
+                        None
+                    }
+                }
+            };
+            if let Some(score) = score {
+                // The score is for the player to move after `played` moves
+                return if played % 2 == 0 { score } else { 1.0 - score };
+            }
+        }
+        unreachable!("the playout always ends within max_playout_moves")
+    }
+}
+
+impl<M> Node<M> {
+    /// Creates a node for a position, with all its moves still to be tried.
+    ///
+    fn new<P: MoveGen<Move = M>>(parent: Option<usize>, m: Option<M>, position: &P) -> Node<M> {
+        let mut untried = Vec::new();
+        if position.terminal_score().is_none() {
+            position.generate_moves(&mut untried);
+        }
+        Node {
+            parent,
+            m,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            score: 0.0,
+        }
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::game::Position;
+
+    // Tic-tac-toe: the cells are numbered from 0 to 8, the players are 1 and 2
+    #[derive(Clone)]
+    struct TicTacToe {
+        cells: [u8; 9],
+        to_move: u8,
+    }
+
+    const LINES: [[usize; 3]; 8] = [
+        [0, 1, 2],
+        [3, 4, 5],
+        [6, 7, 8],
+        [0, 3, 6],
+        [1, 4, 7],
+        [2, 5, 8],
+        [0, 4, 8],
+        [2, 4, 6],
+    ];
+
+    impl TicTacToe {
+        fn from(cells: &str, to_move: u8) -> TicTacToe {
+            let mut t = TicTacToe {
+                cells: [0; 9],
+                to_move,
+            };
+            for (ndx, c) in cells.chars().enumerate() {
+                t.cells[ndx] = c.to_digit(10).unwrap() as u8;
+            }
+            t
+        }
+    }
+
+    impl Position for TicTacToe {
+        type Move = usize;
+        fn make_move(&mut self, m: usize) {
+            self.cells[m] = self.to_move;
+            self.to_move = 3 - self.to_move;
+        }
+        fn terminal_score(&self) -> Option<f64> {
+            let opponent = 3 - self.to_move;
+            if LINES
+                .iter()
+                .any(|l| l.iter().all(|ndx| self.cells[*ndx] == opponent))
+            {
+                Some(0.0)
+            } else if self.cells.iter().all(|c| *c != 0) {
+                Some(0.5)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl MoveGen for TicTacToe {
+        fn generate_moves(&self, moves: &mut Vec<usize>) {
+            moves.extend((0..9).filter(|ndx| self.cells[*ndx] == 0));
+        }
+    }
+
+    #[test]
+    fn mcts_finds_the_winning_move_and_the_defence() {
+        let mut mcts = Mcts::new(MctsConfig::default(), RandomPlayout::new(1));
+        // X to move wins at once
+        assert_eq!(mcts.search(&TicTacToe::from("110220000", 1), 1000), Some(2));
+        // O to move must block the X row
+        assert_eq!(mcts.search(&TicTacToe::from("110000200", 2), 3000), Some(2));
+        let stats = mcts.root_statistics();
+        assert_eq!(stats.len(), 6);
+        assert_eq!(stats.iter().map(|s| s.visits).sum::<u32>(), 3000);
+    }
+
+    #[test]
+    fn no_moves_when_the_game_is_over() {
+        let mut mcts = Mcts::new(MctsConfig::default(), RandomPlayout::new(1));
+        assert_eq!(mcts.search(&TicTacToe::from("111220000", 2), 10), None);
+        assert!(mcts.root_statistics().is_empty());
+    }
+
+    #[test]
+    fn truncated_playouts_use_the_policy_evaluation() {
+        struct Pessimist(RandomPlayout);
+        impl PlayoutPolicy<TicTacToe> for Pessimist {
+            fn choose(&mut self, position: &TicTacToe, moves: &[usize]) -> usize {
+                PlayoutPolicy::<TicTacToe>::choose(&mut self.0, position, moves)
+            }
+            fn evaluate(&mut self, _position: &TicTacToe) -> f64 {
+                0.0
+            }
+        }
+        let config = MctsConfig {
+            max_playout_moves: 0,
+            ..MctsConfig::default()
+        };
+        let mut mcts = Mcts::new(config, Pessimist(RandomPlayout::new(3)));
+        mcts.search(&TicTacToe::from("000000000", 1), 9);
+        // Each move is tried once and scored as a win (a loss for the opponent)
+        for s in mcts.root_statistics() {
+            assert_eq!((s.visits, s.score), (1, 1.0));
+        }
+    }
+}
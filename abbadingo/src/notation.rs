@@ -0,0 +1,6 @@
+//! Textual notations for the chess moves.
+//!
+//! Each notation is implemented in its own submodule:
+//! - [coordinate]: the plain coordinate notation ("1. e2-e4 e7-e5 2. g1-f3 ...").
+
+pub mod coordinate;
@@ -0,0 +1,517 @@
+//! Coordinate notation for chess moves.
+//!
+//! In coordinate notation each move is identified only by its start and
+//! destination [Cell]s, plus the piece chosen in case of promotion, e.g.
+//! `e2-e4`, `g1-f3`, `e7-e8Q`. A sequence of moves is rendered numbering the
+//! moves as in the usual chess notation: `1. e2-e4 e7-e5 2. g1-f3 b8-c6`.
+//! If the sequence starts with a move of the Black army, the first move
+//! number is followed by an ellipsis: `12... d7-d5 13. e4-d5`.
+//!
+//! The notation does not carry the moved piece, so converting a
+//! [CoordinateMove] back to a [ChessMove] requires the knowledge of the
+//! position where the move is played: see [CoordinateMove::to_chess_move]
+//! and [CoordinateMoveList::to_chess_moves].
+//!
+//! # Example
+//! ```
+//! # use std::convert::TryFrom;
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::notation::coordinate::*;
+//! let ml = CoordinateMoveList::try_from("1. e2-e4 e7-e5 2. g1-f3").unwrap();
+//! assert_eq!(ml.first_move_number, 1);
+//! assert_eq!(ml.first_to_move, ArmyColour::White);
+//! assert_eq!(ml.moves.len(), 3);
+//! assert_eq!(ml.moves[2], CoordinateMove::new(Cell::G1, Cell::F3, None));
+//! assert_eq!(format!("{}", ml), "1. e2-e4 e7-e5 2. g1-f3");
+//! ```
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::bbdefines::*;
+use crate::chessboard::ChessBoard;
+use crate::chessdefines::*;
+use crate::chessmove::ChessMove;
+use crate::error::AbbaDingoError;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// A chess move in coordinate notation: start [Cell], destination [Cell]
+/// and, in case of promotion, the [ChessPiece] chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoordinateMove {
+    pub start: Cell,
+    pub dest: Cell,
+    pub promotion: Option<ChessPiece>,
+}
+
+/// A numbered sequence of moves in coordinate notation.
+///
+/// The sequence is played from a position where the move `first_move_number`
+/// has to be played by the `first_to_move` army.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinateMoveList {
+    pub first_move_number: u32,
+    pub first_to_move: ArmyColour,
+    pub moves: Vec<CoordinateMove>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl CoordinateMove {
+    /// Default constructor of the [CoordinateMove] structure
+    ///
+    /// # Arguments
+    ///
+    /// * `start`: The starting [Cell] of the moved piece
+    /// * `dest`: The destination [Cell] of the moved piece
+    /// * `promotion`: The type of the [ChessPiece] the pawn is promoted to if any (None otherwise)
+    ///
+    pub fn new(start: Cell, dest: Cell, promotion: Option<ChessPiece>) -> CoordinateMove {
+        CoordinateMove {
+            start,
+            dest,
+            promotion,
+        }
+    }
+
+    /// Resolves the move into the corresponding legal [ChessMove] in the
+    /// given position, finding the moved and taken pieces among the legal
+    /// moves of the position. If the move is not legal in the position,
+    /// Err(AbbaDingoError::IllegalCoordinateNotationMove) is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::error::AbbaDingoError;
+    /// # use abbadingo::notation::coordinate::*;
+    /// let board = ChessBoard::initial();
+    /// let m = CoordinateMove::new(Cell::G1, Cell::F3, None).to_chess_move(&board).unwrap();
+    /// assert_eq!(m.moved_piece(), ChessPiece::Knight);
+    /// assert_eq!(
+    ///     CoordinateMove::new(Cell::G1, Cell::G3, None).to_chess_move(&board),
+    ///     Err(AbbaDingoError::IllegalCoordinateNotationMove)
+    /// );
+    /// ```
+    pub fn to_chess_move(&self, board: &ChessBoard) -> Result<ChessMove, AbbaDingoError> {
+        board
+            .legal_moves()
+            .into_iter()
+            .find(|m| CoordinateMove::from(m) == *self)
+            .ok_or(AbbaDingoError::IllegalCoordinateNotationMove)
+    }
+}
+
+impl CoordinateMoveList {
+    /// Creates an empty move list starting from the given move number and army.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::notation::coordinate::*;
+    /// let ml = CoordinateMoveList::new(1, ArmyColour::White);
+    /// assert!(ml.moves.is_empty());
+    /// assert_eq!(format!("{}", ml), "");
+    /// ```
+    pub fn new(first_move_number: u32, first_to_move: ArmyColour) -> CoordinateMoveList {
+        CoordinateMoveList {
+            first_move_number,
+            first_to_move,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Creates a move list from a sequence of [ChessMove]s, played starting from
+    /// the given move number and army.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// # use abbadingo::notation::coordinate::*;
+    /// let ml = CoordinateMoveList::from_chess_moves(
+    ///     &[
+    ///         ChessMove::new(ChessPiece::Pawn, Cell::D7, Cell::D5, None, None),
+    ///         ChessMove::new(ChessPiece::Pawn, Cell::E4, Cell::D5, Some(ChessPiece::Pawn), None),
+    ///     ],
+    ///     12,
+    ///     ArmyColour::Black,
+    /// );
+    /// assert_eq!(format!("{}", ml), "12... d7-d5 13. e4-d5");
+    /// ```
+    pub fn from_chess_moves(
+        moves: &[ChessMove],
+        first_move_number: u32,
+        first_to_move: ArmyColour,
+    ) -> CoordinateMoveList {
+        CoordinateMoveList {
+            first_move_number,
+            first_to_move,
+            moves: moves.iter().map(CoordinateMove::from).collect(),
+        }
+    }
+
+    /// Resolves the moves of the list into the corresponding legal
+    /// [ChessMove]s, playing them from the given position.
+    ///
+    /// Err(AbbaDingoError::IllegalCoordinateNotationMove) is returned if the
+    /// army to move in the position is not the first army to move of the list
+    /// or if a move is not legal in the position where it is played.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::error::AbbaDingoError;
+    /// # use abbadingo::notation::coordinate::*;
+    /// let board = ChessBoard::initial();
+    /// let ml = CoordinateMoveList::try_from("1. e2-e4 d7-d5 2. e4-d5").unwrap();
+    /// let moves = ml.to_chess_moves(&board).unwrap();
+    /// assert_eq!(moves[2].taken_piece(), Some(ChessPiece::Pawn));
+    /// let ml = CoordinateMoveList::try_from("1... e7-e5").unwrap();
+    /// assert_eq!(
+    ///     ml.to_chess_moves(&board),
+    ///     Err(AbbaDingoError::IllegalCoordinateNotationMove)
+    /// );
+    /// ```
+    pub fn to_chess_moves(&self, board: &ChessBoard) -> Result<Vec<ChessMove>, AbbaDingoError> {
+        if board.to_move != self.first_to_move {
+            return Err(AbbaDingoError::IllegalCoordinateNotationMove);
+        }
+        let mut board = *board;
+        let mut moves = Vec::with_capacity(self.moves.len());
+        for cm in &self.moves {
+            let m = cm.to_chess_move(&board)?;
+            board.make_move(m);
+            moves.push(m);
+        }
+        Ok(moves)
+    }
+
+    /// Returns the move number and the army to move for the move in position
+    /// `ndx` of the sequence.
+    ///
+    fn move_number_and_colour(&self, ndx: usize) -> (u32, ArmyColour) {
+        let plies = ndx as u32 + (self.first_to_move == ArmyColour::Black) as u32;
+        let colour = if plies.is_multiple_of(2) {
+            ArmyColour::White
+        } else {
+            ArmyColour::Black
+        };
+        (self.first_move_number + plies / 2, colour)
+    }
+}
+
+/// Parses a single move number marker ("12." or "12...").
+///
+/// Returns the move number and the remaining part of the token (that can
+/// contain a move if there are no spaces after the marker).
+///
+fn parse_move_number(token: &str) -> Option<(u32, bool, &str)> {
+    let digits = token.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || !token[digits..].starts_with('.') {
+        return None;
+    }
+    let n = token[..digits].parse::<u32>().ok()?;
+    let rest = &token[digits..];
+    if let Some(rest) = rest.strip_prefix("...") {
+        Some((n, true, rest))
+    } else {
+        Some((n, false, &rest[1..]))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for CoordinateMove and CoordinateMoveList structures
+
+/// Creates a [CoordinateMove] from a [ChessMove].
+///
+impl From<&ChessMove> for CoordinateMove {
+    fn from(cm: &ChessMove) -> Self {
+        CoordinateMove::new(cm.start_cell(), cm.destination_cell(), cm.promoted_piece())
+    }
+}
+
+/// Display trait for [CoordinateMove] structure.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::notation::coordinate::*;
+/// assert_eq!(format!("{}", CoordinateMove::new(Cell::E2, Cell::E4, None)), "e2-e4");
+/// assert_eq!(
+///     format!("{}", CoordinateMove::new(Cell::A7, Cell::B8, Some(ChessPiece::Knight))),
+///     "a7-b8N"
+/// );
+/// ```
+///
+impl fmt::Display for CoordinateMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.dest)?;
+        if let Some(p) = self.promotion {
+            write!(f, "{}", p.to_char(ArmyColour::White))?;
+        }
+        Ok(())
+    }
+}
+
+/// Tentatively convert a &str with a move in coordinate notation
+/// to the corresponding [CoordinateMove].
+///
+/// The start and destination cells can be separated by `-`, by `x` or
+/// by nothing at all; the promotion piece (`Q`, `R`, `B` or `N`) is accepted
+/// both in upper and lower case. For invalid values
+/// Err(AbbaDingoError::IllegalCoordinateNotationMove) is returned.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// # use abbadingo::notation::coordinate::*;
+/// assert_eq!(CoordinateMove::try_from("e2-e4"), Ok(CoordinateMove::new(Cell::E2, Cell::E4, None)));
+/// assert_eq!(
+///     CoordinateMove::try_from("g2h1q"),
+///     Ok(CoordinateMove::new(Cell::G2, Cell::H1, Some(ChessPiece::Queen)))
+/// );
+/// assert_eq!(CoordinateMove::try_from("e2-e9"), Err(AbbaDingoError::IllegalCoordinateNotationMove));
+/// ```
+///
+impl TryFrom<&str> for CoordinateMove {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let err = || AbbaDingoError::IllegalCoordinateNotationMove;
+        if !value.is_ascii() || value.len() < 4 {
+            return Err(err());
+        }
+        let start = Cell::try_from(&value[0..2]).map_err(|_| err())?;
+        let rest = &value[2..];
+        let rest = rest
+            .strip_prefix('-')
+            .or_else(|| rest.strip_prefix('x'))
+            .unwrap_or(rest);
+        if rest.len() < 2 || rest.len() > 3 {
+            return Err(err());
+        }
+        let dest = Cell::try_from(&rest[0..2]).map_err(|_| err())?;
+        let promotion = match &rest[2..] {
+            "" => None,
+            p => match ChessPiece::try_from(p.to_ascii_uppercase().as_str()) {
+                Ok(ChessPiece::King) | Err(_) => return Err(err()),
+                Ok(p) => Some(p),
+            },
+        };
+        Ok(CoordinateMove::new(start, dest, promotion))
+    }
+}
+
+/// Display trait for [CoordinateMoveList] structure.
+///
+/// Renders the moves as numbered coordinate-notation text.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::notation::coordinate::*;
+/// let mut ml = CoordinateMoveList::new(1, ArmyColour::White);
+/// ml.moves.push(CoordinateMove::new(Cell::E2, Cell::E4, None));
+/// ml.moves.push(CoordinateMove::new(Cell::E7, Cell::E5, None));
+/// ml.moves.push(CoordinateMove::new(Cell::G1, Cell::F3, None));
+/// assert_eq!(format!("{}", ml), "1. e2-e4 e7-e5 2. g1-f3");
+/// ```
+///
+impl fmt::Display for CoordinateMoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ndx, m) in self.moves.iter().enumerate() {
+            if ndx > 0 {
+                write!(f, " ")?;
+            }
+            match self.move_number_and_colour(ndx) {
+                (n, ArmyColour::White) => write!(f, "{}. ", n)?,
+                (n, ArmyColour::Black) if ndx == 0 => write!(f, "{}... ", n)?,
+                _ => {}
+            }
+            write!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tentatively convert a &str with a numbered sequence of moves in coordinate
+/// notation to the corresponding [CoordinateMoveList].
+///
+/// The move numbers are optional, but when present they must be consistent
+/// with the position of the moves in the sequence; the first move number
+/// (if any) determines the `first_move_number` and `first_to_move` fields,
+/// that otherwise default to 1 and White.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::error::AbbaDingoError;
+/// # use abbadingo::notation::coordinate::*;
+/// let ml = CoordinateMoveList::try_from("12... d7-d5 13. e4-d5").unwrap();
+/// assert_eq!(ml.first_move_number, 12);
+/// assert_eq!(ml.first_to_move, ArmyColour::Black);
+/// assert_eq!(
+///     CoordinateMoveList::try_from("1. e2-e4 e7-e5 3. g1-f3"),
+///     Err(AbbaDingoError::IllegalCoordinateNotationMove)
+/// );
+/// ```
+///
+impl TryFrom<&str> for CoordinateMoveList {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut ml = CoordinateMoveList::new(1, ArmyColour::White);
+        for token in value.split_whitespace() {
+            let mv = match parse_move_number(token) {
+                Some((n, black, rest)) => {
+                    let colour = if black {
+                        ArmyColour::Black
+                    } else {
+                        ArmyColour::White
+                    };
+                    if ml.moves.is_empty() {
+                        ml.first_move_number = n;
+                        ml.first_to_move = colour;
+                    } else if ml.move_number_and_colour(ml.moves.len()) != (n, colour) {
+                        return Err(AbbaDingoError::IllegalCoordinateNotationMove);
+                    }
+                    rest
+                }
+                None => token,
+            };
+            if !mv.is_empty() {
+                ml.moves.push(CoordinateMove::try_from(mv)?);
+            }
+        }
+        Ok(ml)
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn coordinate_moves_are_resolved_in_their_position() {
+        let board = crate::fenrecord::FenRecord::try_from("4k3/1P6/8/3pP3/8/8/8/4K2R w K d6 0 1")
+            .unwrap()
+            .board();
+        let resolve = |s| CoordinateMove::try_from(s).unwrap().to_chess_move(&board);
+        let ep = resolve("e5-d6").unwrap();
+        assert_eq!(ep.taken_piece(), Some(ChessPiece::Pawn));
+        let castling = resolve("e1-g1").unwrap();
+        assert!(castling.is_a_castling_move());
+        let promotion = resolve("b7-b8N").unwrap();
+        assert_eq!(promotion.promoted_piece(), Some(ChessPiece::Knight));
+        // The promotion piece is mandatory
+        assert_eq!(
+            resolve("b7-b8"),
+            Err(AbbaDingoError::IllegalCoordinateNotationMove)
+        );
+
+        let ml = CoordinateMoveList::from_chess_moves(&[castling, ep], 1, ArmyColour::White);
+        assert_eq!(
+            ml.to_chess_moves(&board),
+            Err(AbbaDingoError::IllegalCoordinateNotationMove)
+        );
+        let ml = CoordinateMoveList::from_chess_moves(&[ep, castling], 1, ArmyColour::White);
+        assert_eq!(
+            ml.to_chess_moves(&board),
+            Err(AbbaDingoError::IllegalCoordinateNotationMove)
+        );
+        let ke7 = CoordinateMove::new(Cell::E8, Cell::E7, None);
+        let ml = CoordinateMoveList {
+            first_move_number: 1,
+            first_to_move: ArmyColour::White,
+            moves: vec![
+                CoordinateMove::from(&promotion),
+                ke7,
+                CoordinateMove::from(&castling),
+            ],
+        };
+        let moves = ml.to_chess_moves(&board).unwrap();
+        assert_eq!(moves[0], promotion);
+        assert_eq!(moves[1].moved_piece(), ChessPiece::King);
+        assert_eq!(moves[2], castling);
+    }
+
+    #[test]
+    fn chess_moves_are_rendered_in_coordinate_notation() {
+        let ml = CoordinateMoveList::from_chess_moves(
+            &[
+                ChessMove::new(ChessPiece::Pawn, Cell::E2, Cell::E4, None, None),
+                ChessMove::new(ChessPiece::Pawn, Cell::E7, Cell::E5, None, None),
+                ChessMove::new(ChessPiece::Knight, Cell::G1, Cell::F3, None, None),
+                ChessMove::new(ChessPiece::Knight, Cell::B8, Cell::C6, None, None),
+            ],
+            1,
+            ArmyColour::White,
+        );
+        assert_eq!(format!("{}", ml), "1. e2-e4 e7-e5 2. g1-f3 b8-c6");
+    }
+
+    #[test]
+    fn move_list_parse_and_format_round_trip() {
+        for text in [
+            "1. e2-e4 e7-e5 2. g1-f3 b8-c6",
+            "34... a2-a1Q 35. h7-h8N",
+            "7. c7-d8R",
+            "",
+        ] {
+            let ml = CoordinateMoveList::try_from(text).unwrap();
+            assert_eq!(format!("{}", ml), text);
+        }
+    }
+
+    #[test]
+    fn move_list_parse_with_attached_numbers_and_no_numbers() {
+        let ml = CoordinateMoveList::try_from("1.e2e4 e7xe5\n2.g1-f3").unwrap();
+        assert_eq!(format!("{}", ml), "1. e2-e4 e7-e5 2. g1-f3");
+        let ml = CoordinateMoveList::try_from("e2-e4 e7-e5").unwrap();
+        assert_eq!(ml.first_move_number, 1);
+        assert_eq!(ml.first_to_move, ArmyColour::White);
+        assert_eq!(ml.moves.len(), 2);
+    }
+
+    #[test]
+    fn move_list_parse_errors() {
+        for text in [
+            "1. e2-e4 2. e7-e5",
+            "1. e2-e4 e7-e5 2... g1-f3",
+            "1. e2-e4 Nf3",
+            "1. e7-e8K",
+            "1. e7-e8Qx",
+            "1. ée-e4",
+        ] {
+            assert_eq!(
+                CoordinateMoveList::try_from(text),
+                Err(AbbaDingoError::IllegalCoordinateNotationMove)
+            );
+        }
+    }
+}
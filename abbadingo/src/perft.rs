@@ -0,0 +1,290 @@
+//! The [perft](https://www.chessprogramming.org/Perft) function: the number of
+//! leaf nodes of the moves tree of a position, up to a given depth.
+//!
+//! The functions work on any game implementing the [Position](crate::game::Position) and [MoveGen]
+//! traits (see [game](crate::game)): comparing the results with known node
+//! counts validates the move generator of the game. The reference counts are
+//! supplied by each game, for chess in the [testpositions](crate::testpositions)
+//! module. [perft_divide] splits the count by root move, to find the move whose
+//! subtree differs from the one of a reference implementation; [verify] checks
+//! a chess position given as FEN string with a single call. With the `std`
+//! feature, `perft_parallel` splits the root moves among several threads.
+//!
+//! The positions where the game is over before the given depth is reached
+//! (no moves can be generated) are not leaf nodes and are not counted.
+//!
+//! # Example
+//! ```
+//! # use std::convert::TryFrom;
+//! # use abbadingo::chessboard::ChessBoard;
+//! # use abbadingo::fenrecord::FenRecord;
+//! # use abbadingo::perft::*;
+//! # use abbadingo::testpositions::KIWIPETE;
+//! let board = FenRecord::try_from(KIWIPETE.fen).unwrap().board();
+//! assert_eq!(perft(&board, 1), KIWIPETE.perft_at(1).unwrap());
+//!
+//! let divided = perft_divide(&ChessBoard::initial(), 2);
+//! assert_eq!(divided.len(), 20);
+//! assert!(divided.iter().all(|&(_, nodes)| nodes == 20));
+//! ```
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::error::AbbaDingoError;
+use crate::fenrecord::FenRecord;
+use crate::game::MoveGen;
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Returns the number of leaf nodes of the moves tree of the given position,
+/// explored up to the given depth.
+///
+/// At depth 0 the position itself is the only leaf node; at depth 1 the moves
+/// are counted without playing them ("bulk counting").
+///
+/// # Example
+/// ```
+/// # use abbadingo::chessboard::ChessBoard;
+/// # use abbadingo::perft::*;
+/// assert_eq!(perft(&ChessBoard::initial(), 0), 1);
+/// assert_eq!(perft(&ChessBoard::initial(), 2), 400);
+/// ```
+pub fn perft<P: MoveGen>(position: &P, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut moves = Vec::new();
+    position.generate_moves(&mut moves);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .into_iter()
+        .map(|m| perft(&child(position, m), depth - 1))
+        .sum()
+}
+
+/// Returns the [perft] of the given position, splitting the root moves among
+/// `threads` threads (a single one if `threads` is 0 or 1).
+///
+/// Each thread explores the subtrees of its root moves with [perft], so the
+/// result is the same of [perft] in a fraction of the time on multi-core
+/// machines. The function requires the `std` feature.
+///
+/// # Example
+/// ```
+/// # use abbadingo::chessboard::ChessBoard;
+/// # use abbadingo::perft::*;
+/// assert_eq!(perft_parallel(&ChessBoard::initial(), 3, 4), 8_902);
+/// ```
+#[cfg(feature = "std")]
+pub fn perft_parallel<P>(position: &P, depth: u32, threads: usize) -> u64
+where
+    P: MoveGen + Sync,
+    P::Move: Sync,
+{
+    if depth < 2 || threads < 2 {
+        return perft(position, depth);
+    }
+    let mut moves = Vec::new();
+    position.generate_moves(&mut moves);
+    if moves.is_empty() {
+        return 0;
+    }
+    let chunk_size = moves.len().div_ceil(threads);
+    std::thread::scope(|s| {
+        let handles: Vec<_> = moves
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&m| perft(&child(position, m), depth - 1))
+                        .sum::<u64>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("perft thread panicked"))
+            .sum()
+    })
+}
+
+/// Returns the moves of the given position, each with the number of leaf
+/// nodes of its subtree explored up to `depth - 1`.
+///
+/// The sum of the node counts is the [perft] of the position at `depth`.
+/// At depth 0 there are no moves to divide the count by, and the empty
+/// vector is returned.
+pub fn perft_divide<P: MoveGen>(position: &P, depth: u32) -> Vec<(P::Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let mut moves = Vec::new();
+    position.generate_moves(&mut moves);
+    moves
+        .into_iter()
+        .map(|m| (m, perft(&child(position, m), depth - 1)))
+        .collect()
+}
+
+/// Returns the first entry of a reference table, as (depth, expected nodes)
+/// pairs, whose count is not matched by the [perft] of the given position,
+/// together with the count found. Only the entries with at most `max_nodes`
+/// expected nodes are checked.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::fenrecord::FenRecord;
+/// # use abbadingo::perft::*;
+/// # use abbadingo::testpositions::CPW_POSITION_4;
+/// let board = FenRecord::try_from(CPW_POSITION_4.fen).unwrap().board();
+/// assert_eq!(perft_mismatch(&board, CPW_POSITION_4.perft, 10_000), None);
+/// assert_eq!(perft_mismatch(&board, &[(1, 6), (2, 265)], 10_000), Some((2, 265, 264)));
+/// ```
+pub fn perft_mismatch<P: MoveGen>(
+    position: &P,
+    reference: &[(u32, u64)],
+    max_nodes: u64,
+) -> Option<(u32, u64, u64)> {
+    reference
+        .iter()
+        .filter(|&&(_, expected)| expected <= max_nodes)
+        .map(|&(depth, expected)| (depth, expected, perft(position, depth)))
+        .find(|&(_, expected, found)| expected != found)
+}
+
+/// Checks the [perft] of the chess position given as FEN string against the
+/// expected node counts, starting from depth 1, returning the first mismatch
+/// as (depth, expected nodes, nodes found) like [perft_mismatch].
+///
+/// If the FEN string is not valid, the error of the FEN parsing is returned.
+///
+/// # Example
+/// ```
+/// # use abbadingo::error::AbbaDingoError;
+/// # use abbadingo::perft::*;
+/// # use abbadingo::testpositions::KIWIPETE;
+/// assert_eq!(verify(KIWIPETE.fen, &[48, 2_039]), Ok(None));
+/// assert_eq!(verify(KIWIPETE.fen, &[48, 2_040]), Ok(Some((2, 2_040, 2_039))));
+/// assert_eq!(verify("8/8/8", &[1]), Err(AbbaDingoError::IllegalFenRecord));
+/// ```
+pub fn verify(fen: &str, expected: &[u64]) -> Result<Option<(u32, u64, u64)>, AbbaDingoError> {
+    let board = FenRecord::try_from(fen)?.board();
+    let reference: Vec<(u32, u64)> = expected
+        .iter()
+        .enumerate()
+        .map(|(ndx, &nodes)| (ndx as u32 + 1, nodes))
+        .collect();
+    Ok(perft_mismatch(&board, &reference, u64::MAX))
+}
+
+/// Returns the position reached playing the given move.
+///
+fn child<P: MoveGen>(position: &P, m: P::Move) -> P {
+    let mut child = position.clone();
+    child.make_move(m);
+    child
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::fenrecord::FenRecord;
+    use crate::game::Position;
+    use crate::testpositions::*;
+    use alloc::vec;
+    use core::convert::TryFrom;
+
+    // The deeper node counts are checked by the integration tests
+    const MAX_NODES: u64 = 10_000;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parallel_perft_matches_perft() {
+        for p in ALL_PERFT_POSITIONS {
+            let board = FenRecord::try_from(p.fen).unwrap().board();
+            for depth in 0..3 {
+                let nodes = perft(&board, depth);
+                for threads in [0, 1, 3, 64] {
+                    assert_eq!(perft_parallel(&board, depth, threads), nodes, "{}", p.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn known_node_counts_are_matched() {
+        for p in ALL_PERFT_POSITIONS {
+            let board = FenRecord::try_from(p.fen).unwrap().board();
+            assert_eq!(board.is_in_check(), p.in_check, "{}", p.name);
+            assert_eq!(
+                perft_mismatch(&board, p.perft, MAX_NODES),
+                None,
+                "{}",
+                p.name
+            );
+        }
+    }
+
+    // Two players alternate taking 1 or 2 stones from a heap
+    #[derive(Clone)]
+    struct Heap(u32);
+
+    impl Position for Heap {
+        type Move = u32;
+        fn make_move(&mut self, m: u32) {
+            self.0 -= m;
+        }
+        fn terminal_score(&self) -> Option<f64> {
+            if self.0 == 0 {
+                Some(0.0)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl MoveGen for Heap {
+        fn generate_moves(&self, moves: &mut Vec<u32>) {
+            moves.extend((1..=2).filter(|m| *m <= self.0));
+        }
+    }
+
+    #[test]
+    fn perft_works_on_any_game() {
+        // The sequences of 1 and 2 stones taken from a heap of 4
+        let reference = [(1, 2), (2, 4), (3, 4), (4, 1), (5, 0)];
+        assert_eq!(perft_mismatch(&Heap(4), &reference, u64::MAX), None);
+        assert_eq!(perft_divide(&Heap(4), 3), vec![(1, 3), (2, 1)]);
+    }
+
+    #[test]
+    fn verify_checks_the_counts_of_a_fen() {
+        let counts: Vec<u64> = STARTPOS.perft.iter().take(3).map(|&(_, n)| n).collect();
+        assert_eq!(verify(STARTPOS.fen, &counts), Ok(None));
+        assert_eq!(verify(STARTPOS.fen, &[]), Ok(None));
+        assert_eq!(verify(STARTPOS.fen, &[20, 401]), Ok(Some((2, 401, 400))));
+        assert!(verify("rnbqkbnr/pppppppp/8/8 w KQkq - 0 1", &[20]).is_err());
+    }
+
+    #[test]
+    fn divided_counts_sum_to_perft() {
+        let board = FenRecord::try_from(CPW_POSITION_4.fen).unwrap().board();
+        let divided = perft_divide(&board, 2);
+        assert_eq!(divided.len() as u64, perft(&board, 1));
+        assert_eq!(divided.iter().map(|&(_, n)| n).sum::<u64>(), 264);
+        assert!(perft_divide(&board, 0).is_empty());
+    }
+}
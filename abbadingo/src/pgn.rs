@@ -0,0 +1,611 @@
+//! Reader and writer of chess games in
+//! [Portable Game Notation](https://www.chessprogramming.org/Portable_Game_Notation)
+//! (PGN) format.
+//!
+//! A PGN game is made of a tag pairs section (`[Event "..."]`, `[White "..."]`,
+//! ...) followed by the movetext: the moves in Standard Algebraic Notation (SAN),
+//! optionally numbered, with comments (`{...}` or `;` up to the end of the line)
+//! and terminated by the game result (`1-0`, `0-1`, `1/2-1/2` or `*`).
+//!
+//! The moves of a [PgnGame] are resolved into [ChessMove]s playing them from the
+//! starting position of the game (the initial position or the one given by the
+//! `FEN` tag), so the move list can be played onto a [ChessBoard]. Recursive
+//! annotation variations (`(...)`) and Numeric Annotation Glyphs (`$n`) are
+//! skipped, as are the `!` and `?` suffixes of the moves.
+//!
+//! A [PgnGame] can also be built from a starting position and a list of moves
+//! with [PgnGame::new()], and exported with [PgnGame::to_pgn()] or its
+//! [Display](core::fmt::Display) implementation.
+//!
+//! # Example
+//! ```
+//! # use std::convert::TryFrom;
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::pgn::*;
+//! let game = PgnGame::try_from(
+//!     "[Event \"Casual game\"]\n\
+//!      [Result \"1-0\"]\n\
+//!      \n\
+//!      1. e4 e5 2. Qh5 {Threatening mate} Nc6 3. Bc4 Nf6?? 4. Qxf7# 1-0",
+//! )
+//! .unwrap();
+//! assert_eq!(game.tag("Event"), Some("Casual game"));
+//! assert_eq!(game.moves.len(), 7);
+//! assert_eq!(game.comments, vec![(3, "Threatening mate".to_string())]);
+//! assert_eq!(game.result, GameResult::WhiteWins);
+//!
+//! let board = game.final_board().unwrap();
+//! assert_eq!(board.piece_at(Cell::F7), Some((ChessPiece::Queen, ArmyColour::White)));
+//! assert!(board.is_in_check());
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::chessboard::ChessBoard;
+use crate::chessdefines::*;
+use crate::chessmove::ChessMove;
+use crate::error::AbbaDingoError;
+use crate::fenrecord::FenRecord;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// The names of the Seven Tag Roster, the tags that every exported PGN game
+/// contains, in the order they are exported.
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+// The maximum length of the lines of an exported movetext
+const MAX_LINE_LENGTH: usize = 79;
+
+/// A chess game, read from a PGN text or built from a list of moves to be
+/// exported in PGN format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    /// The tag pairs, as name and value, in the order they appear.
+    pub tags: Vec<(String, String)>,
+    /// The moves of the game, playable from the [start_board()](PgnGame::start_board).
+    pub moves: Vec<ChessMove>,
+    /// The comments of the movetext, each with the number of moves played
+    /// before it. The line breaks of the comments are replaced by spaces.
+    pub comments: Vec<(usize, String)>,
+    /// The result of the game, as given by the game termination marker.
+    pub result: GameResult,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl PgnGame {
+    /// Creates a game from its starting position and the list of moves played.
+    ///
+    /// If the starting position is not the initial one, the `SetUp` and `FEN`
+    /// tags are added; the other tags of the Seven Tag Roster can be added to
+    /// [tags](PgnGame::tags), otherwise they are exported with unknown values.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::chessmove::ChessMove;
+    /// # use abbadingo::pgn::*;
+    /// let mut game = PgnGame::new(
+    ///     &ChessBoard::initial(),
+    ///     vec![
+    ///         ChessMove::new(ChessPiece::Pawn, Cell::F2, Cell::F3, None, None),
+    ///         ChessMove::new(ChessPiece::Pawn, Cell::E7, Cell::E5, None, None),
+    ///         ChessMove::new(ChessPiece::Pawn, Cell::G2, Cell::G4, None, None),
+    ///         ChessMove::new(ChessPiece::Queen, Cell::D8, Cell::H4, None, None),
+    ///     ],
+    ///     GameResult::BlackWins,
+    /// );
+    /// game.tags.push(("White".to_string(), "Fool".to_string()));
+    /// assert_eq!(
+    ///     format!("{}", game),
+    ///     "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+    ///      [White \"Fool\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n\
+    ///      1. f3 e5 2. g4 Qh4# 0-1\n"
+    /// );
+    /// ```
+    pub fn new(start: &ChessBoard, moves: Vec<ChessMove>, result: GameResult) -> PgnGame {
+        let mut tags = Vec::new();
+        if *start != ChessBoard::initial() {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push((
+                "FEN".to_string(),
+                FenRecord::from_board(start).fen().to_string(),
+            ));
+        }
+        PgnGame {
+            tags,
+            moves,
+            comments: Vec::new(),
+            result,
+        }
+    }
+
+    /// Returns the value of the tag with the given name, if present.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the starting position of the game: the position of the `FEN`
+    /// tag if present, the initial position otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::chessboard::ChessBoard;
+    /// # use abbadingo::chessdefines::*;
+    /// # use abbadingo::pgn::*;
+    /// let game = PgnGame::try_from(
+    ///     "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. O-O Kd7 *",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(game.start_board().unwrap().castling_rights.bits(), CastlingRights::WHITE_KINGSIDE);
+    /// assert_eq!(game.result, GameResult::Ongoing);
+    /// ```
+    pub fn start_board(&self) -> Result<ChessBoard, AbbaDingoError> {
+        match self.tag("FEN") {
+            Some(fen) => Ok(FenRecord::try_from(fen)?.board()),
+            None => Ok(ChessBoard::initial()),
+        }
+    }
+
+    /// Returns the position reached at the end of the game, playing all the
+    /// moves from the [start_board()](PgnGame::start_board).
+    pub fn final_board(&self) -> Result<ChessBoard, AbbaDingoError> {
+        let mut board = self.start_board()?;
+        for m in &self.moves {
+            board.make_move(*m);
+        }
+        Ok(board)
+    }
+
+    /// Returns the game in PGN export format: the Seven Tag Roster first (with
+    /// `?` for the unknown values and the `Result` tag taken from the game
+    /// result), then the other tags, an empty line and the movetext, with the
+    /// moves in SAN format numbered from the starting position and the lines
+    /// wrapped before 80 characters.
+    ///
+    /// If the `FEN` tag does not contain a valid position an error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use abbadingo::error::AbbaDingoError;
+    /// # use abbadingo::pgn::*;
+    /// let text = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R b K - 3 40\"]\n\n\
+    ///             40... Kd7 {Escaping} 41. O-O Kc6 *";
+    /// let mut game = PgnGame::try_from(text).unwrap();
+    /// let exported = game.to_pgn().unwrap();
+    /// assert!(exported.starts_with("[Event \"?\"]\n"));
+    /// assert!(exported.ends_with("\n\n40... Kd7 {Escaping} 41. O-O Kc6 *\n"));
+    /// let reread = PgnGame::try_from(exported.as_str()).unwrap();
+    /// assert_eq!((&reread.moves, &reread.comments), (&game.moves, &game.comments));
+    ///
+    /// game.tags[1].1 = "4k3/8/8/8/8/8/8/4K2R".to_string();
+    /// assert_eq!(game.to_pgn(), Err(AbbaDingoError::IllegalFenRecord));
+    /// ```
+    pub fn to_pgn(&self) -> Result<String, AbbaDingoError> {
+        let start = self.start_board()?;
+        let mut pgn = String::new();
+        // We can unwrap safely here... writing to a String never fails
+        self.write_pgn(&mut pgn, Some(start)).unwrap();
+        Ok(pgn)
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Writes the game in PGN export format, with the moves in SAN format
+    /// played from the given starting position or, if it is not available,
+    /// in UCI format.
+    ///
+    fn write_pgn<W: fmt::Write>(&self, w: &mut W, mut start: Option<ChessBoard>) -> fmt::Result {
+        let result = self.result.to_string();
+        for name in SEVEN_TAG_ROSTER.iter() {
+            let value = match *name {
+                "Result" => result.as_str(),
+                "Date" => self.tag(name).unwrap_or("????.??.??"),
+                _ => self.tag(name).unwrap_or("?"),
+            };
+            write_tag_pair(w, name, value)?;
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                write_tag_pair(w, name, value)?;
+            }
+        }
+        writeln!(w)?;
+
+        // The movetext is split in tokens to wrap the lines: the comments
+        // in words, the move numbers are kept together with their moves
+        let mut tokens = Vec::new();
+        let mut comments = self.comments.iter().peekable();
+        let mut show_number = true;
+        for (ndx, m) in self.moves.iter().enumerate() {
+            while let Some((_, text)) = comments.next_if(|(n, _)| *n == ndx) {
+                push_comment_tokens(&mut tokens, text);
+                show_number = true;
+            }
+            let board = match start.as_mut() {
+                Some(board) => board,
+                None => {
+                    tokens.push(m.to_uci());
+                    continue;
+                }
+            };
+            let san = m.to_san(board);
+            tokens.push(match board.to_move {
+                ArmyColour::White => format!("{}. {}", board.full_move_number, san),
+                ArmyColour::Black if show_number => {
+                    format!("{}... {}", board.full_move_number, san)
+                }
+                _ => san,
+            });
+            board.make_move(*m);
+            show_number = false;
+        }
+        for (_, text) in comments {
+            push_comment_tokens(&mut tokens, text);
+        }
+        tokens.push(result);
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(w)?;
+                line_length = 0;
+            } else if line_length > 0 {
+                write!(w, " ")?;
+                line_length += 1;
+            }
+            write!(w, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(w)
+    }
+}
+
+/// Parses the tag pair in a line of the tag pairs section (`[Name "value"]`),
+/// returning the name and the value with the escapes removed.
+///
+fn parse_tag_pair(line: &str) -> Result<(String, String), AbbaDingoError> {
+    let err = || AbbaDingoError::IllegalPgnGame;
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(err)?
+        .trim();
+    let (name, value) = inner.split_at(inner.find(char::is_whitespace).ok_or_else(err)?);
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(err)?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(err());
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next().ok_or_else(err)?),
+            '"' => return Err(err()),
+            _ => unescaped.push(c),
+        }
+    }
+    Ok((name.to_string(), unescaped))
+}
+
+/// Writes a tag pair line, escaping the quotes and backslashes of the value.
+///
+fn write_tag_pair<W: fmt::Write>(w: &mut W, name: &str, value: &str) -> fmt::Result {
+    write!(w, "[{} \"", name)?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            write!(w, "\\")?;
+        }
+        write!(w, "{}", c)?;
+    }
+    writeln!(w, "\"]")
+}
+
+/// Returns the text of a comment with the sequences of whitespace characters
+/// (line breaks included) replaced by single spaces.
+///
+fn normalize_comment(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Appends to the movetext tokens the words of a comment, the first one
+/// preceded by the opening brace and the last one followed by the closing brace.
+///
+fn push_comment_tokens(tokens: &mut Vec<String>, text: &str) {
+    let first = tokens.len();
+    tokens.extend(text.split_whitespace().map(String::from));
+    if tokens.len() == first {
+        tokens.push(String::new());
+    }
+    tokens[first].insert(0, '{');
+    // We can unwrap safely here... at least a token has been pushed
+    tokens.last_mut().unwrap().push('}');
+}
+
+/// Returns the text following the recursive annotation variation at the
+/// beginning of `text`, skipping the nested variations and the comments.
+///
+fn skip_variation(text: &str) -> Result<&str, AbbaDingoError> {
+    let mut depth = 0;
+    let mut in_comment = false;
+    for (ndx, c) in text.char_indices() {
+        match c {
+            '}' if in_comment => in_comment = false,
+            _ if in_comment => {}
+            '{' => in_comment = true,
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(&text[ndx + 1..]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(AbbaDingoError::IllegalPgnGame)
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for PgnGame structure
+
+/// Display trait for [PgnGame] structure.
+///
+/// Renders the game in PGN export format, as [to_pgn()](PgnGame::to_pgn).
+/// If the `FEN` tag does not contain a valid position the moves cannot be
+/// converted to SAN, and they are written in UCI format instead.
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::pgn::*;
+/// let mut game = PgnGame::try_from("[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. O-O *").unwrap();
+/// assert_eq!(format!("{}", game), game.to_pgn().unwrap());
+/// assert!(format!("{}", game).ends_with("\n\n1. O-O *\n"));
+///
+/// game.tags[0].1 = "Not a FEN".to_string();
+/// assert!(format!("{}", game).ends_with("\n\ne1g1 *\n"));
+/// ```
+///
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_pgn(f, self.start_board().ok())
+    }
+}
+
+/// Tentatively convert a &str with a game in PGN format to the corresponding
+/// [PgnGame].
+///
+/// Malformed tag pairs, comments or variations return
+/// Err(AbbaDingoError::IllegalPgnGame); moves that are not legal (or are
+/// ambiguous) in the position where they are played return
+/// Err(AbbaDingoError::IllegalSanMove). If the game termination marker is
+/// missing, the result is [GameResult::Ongoing].
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use abbadingo::error::AbbaDingoError;
+/// # use abbadingo::pgn::*;
+/// let game = PgnGame::try_from("1. d4 (1. e4 e5) 1... d5 $1 2. c4 dxc4 1/2-1/2").unwrap();
+/// assert_eq!(game.moves.len(), 4);
+/// assert_eq!(PgnGame::try_from("1. e4 e5 2. Ke3"), Err(AbbaDingoError::IllegalSanMove));
+/// assert_eq!(PgnGame::try_from("1. e4 {unterminated"), Err(AbbaDingoError::IllegalPgnGame));
+/// ```
+///
+impl TryFrom<&str> for PgnGame {
+    type Error = AbbaDingoError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let err = || AbbaDingoError::IllegalPgnGame;
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            comments: Vec::new(),
+            result: GameResult::Ongoing,
+        };
+
+        // Tag pairs section, up to the first line not starting with '['
+        let mut rest = value.trim_start();
+        while rest.starts_with('[') {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            game.tags.push(parse_tag_pair(rest[..end].trim())?);
+            rest = rest[end..].trim_start();
+        }
+
+        // Movetext section
+        let mut board = game.start_board()?;
+        let mut result = None;
+        loop {
+            rest = rest.trim_start();
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => break,
+            };
+            if result.is_some() {
+                // Nothing can follow the game termination marker
+                return Err(err());
+            }
+            match c {
+                '{' => {
+                    let end = rest.find('}').ok_or_else(err)?;
+                    game.comments
+                        .push((game.moves.len(), normalize_comment(&rest[1..end])));
+                    rest = &rest[end + 1..];
+                }
+                ';' => {
+                    let end = rest.find('\n').unwrap_or(rest.len());
+                    game.comments
+                        .push((game.moves.len(), normalize_comment(&rest[1..end])));
+                    rest = &rest[end..];
+                }
+                '(' => rest = skip_variation(rest)?,
+                ')' | '}' => return Err(err()),
+                '$' => {
+                    let end = rest[1..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .map_or(rest.len(), |ndx| ndx + 1);
+                    rest = &rest[end..];
+                }
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || "{;()$".contains(c))
+                        .unwrap_or(rest.len());
+                    let token = &rest[..end];
+                    rest = &rest[end..];
+                    if let Ok(r) = GameResult::try_from(token) {
+                        result = Some(r);
+                        continue;
+                    }
+                    // Move number indications ("12." or "12...") can precede the move;
+                    // the digits not followed by a dot are part of the move (e.g. "0-0")
+                    let unnumbered = token.trim_start_matches(|c: char| c.is_ascii_digit());
+                    let san = match unnumbered.strip_prefix('.') {
+                        Some(san) if unnumbered.len() < token.len() => san.trim_start_matches('.'),
+                        _ => token,
+                    };
+                    // The en-passant suffix can be separated from the move
+                    if !san.is_empty() && san != "e.p." {
+                        let m = ChessMove::from_san(san, &board)?;
+                        board.make_move(m);
+                        game.moves.push(m);
+                    }
+                }
+            }
+        }
+        game.result = result.unwrap_or(GameResult::Ongoing);
+        Ok(game)
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bbdefines::*;
+    use alloc::format;
+
+    // Paul Morphy vs Duke Karl / Count Isouard, Paris 1858
+    const OPERA_GAME: &str = "[Event \"Paris\"]
+[Site \"Paris FRA\"]
+[Date \"1858.??.??\"]
+[Round \"?\"]
+[White \"Paul Morphy\"]
+[Black \"Duke Karl / Count Isouard\"]
+[Result \"1-0\"]
+
+1.e4 e5 2.Nf3 d6 3.d4 Bg4 {This is a weak move already.} 4.dxe5 Bxf3 5.Qxf3 dxe5
+6.Bc4 Nf6 7.Qb3 Qe7 8.Nc3 c6 9.Bg5 {Black is in what's like a zugzwang position
+here.} b5 10.Nxb5! cxb5 11.Bxb5+ Nbd7 12.O-O-O Rd8 13.Rxd7 Rxd7 14.Rd1 Qe6
+15.Bxd7+ Nxd7 16.Qb8+! Nxb8 17.Rd8# 1-0
+";
+
+    #[test]
+    fn opera_game_is_read_and_played() {
+        let game = PgnGame::try_from(OPERA_GAME).unwrap();
+        assert_eq!(game.tags.len(), 7);
+        assert_eq!(game.tag("White"), Some("Paul Morphy"));
+        assert_eq!(game.tag("Annotator"), None);
+        assert_eq!(game.moves.len(), 33);
+        assert_eq!(game.comments.len(), 2);
+        assert_eq!(
+            game.comments[1],
+            (
+                17,
+                "Black is in what's like a zugzwang position here.".to_string()
+            )
+        );
+        assert_eq!(game.result, GameResult::WhiteWins);
+
+        let castling = game.moves[22];
+        assert!(castling.is_a_castling_move());
+        assert_eq!(castling.destination_cell(), Cell::C1);
+        let board = game.final_board().unwrap();
+        assert_eq!(
+            board.piece_at(Cell::D8),
+            Some((ChessPiece::Rook, ArmyColour::White))
+        );
+        assert!(board.is_in_check());
+        assert!(board.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn opera_game_export_round_trip() {
+        let game = PgnGame::try_from(OPERA_GAME).unwrap();
+        let exported = format!("{}", game);
+        assert!(exported.lines().all(|l| l.len() < 80));
+        assert!(exported
+            .replace('\n', " ")
+            .contains("10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8"));
+        assert!(exported.ends_with("17. Rd8# 1-0\n"));
+        assert_eq!(PgnGame::try_from(exported.as_str()).unwrap(), game);
+    }
+
+    #[test]
+    fn malformed_games_are_rejected() {
+        for text in [
+            "[Event \"Unterminated]\n1. e4 *",
+            "[Event]\n1. e4 *",
+            "1. e4 (1. d4 *",
+            "1. e4 } *",
+            "1. e4 * e5",
+        ] {
+            assert_eq!(
+                PgnGame::try_from(text),
+                Err(AbbaDingoError::IllegalPgnGame),
+                "{}",
+                text
+            );
+        }
+        assert!(PgnGame::try_from("[FEN \"8/8/8 w - - 0 1\"]\n*").is_err());
+        // Without the dot, the number is read as a move
+        assert_eq!(
+            PgnGame::try_from("1x e4 *"),
+            Err(AbbaDingoError::IllegalSanMove)
+        );
+    }
+
+    #[test]
+    fn castling_with_zeros_is_read() {
+        let game = PgnGame::try_from("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 *").unwrap();
+        assert_eq!(
+            game,
+            PgnGame::try_from("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O *").unwrap()
+        );
+        assert!(game.moves[6].is_a_castling_move());
+        let game =
+            PgnGame::try_from("[FEN \"r3k3/8/8/8/8/8/8/4K3 b q - 0 1\"]\n\n1...0-0-0 2.Kf2 *")
+                .unwrap();
+        assert_eq!(game.moves.len(), 2);
+        assert!(game.moves[0].is_a_castling_move());
+    }
+}
@@ -0,0 +1,481 @@
+//! Computation of the cells attacked by sliding pieces (bishops, rooks and queens).
+//!
+//! The attacks of a sliding piece depend on the occupancy of the board: a piece
+//! attacks all the cells along its lines until the first occupied cell, that is
+//! the last attacked one. Several techniques exist to compute these attacks,
+//! so the computation is abstracted by the [SlidingAttacks] trait, implemented
+//! by the following "backends":
+//!
+//! - [ClassicalAttacks]: uses the precomputed [RAYS] tables and a bit scan to find the
+//!   first blocker along each direction.
+//! - [KoggeStoneAttacks]: uses the Kogge-Stone parallel prefix algorithm to compute
+//!   the occluded fills along each direction. No lookup tables are needed.
+//! - [KindergartenAttacks]: maps the occupancy of each line of the piece onto the
+//!   first rank with a multiplication, and looks up the attacks in the
+//!   [FIRST_RANK_ATTACKS] table.
+//! - `MagicAttacks` (in the `magics` module, requires the `std` feature): looks up
+//!   the attacks in tables indexed by the product of the occupancy and a magic number.
+//!
+//! The backend used by the library is [DefaultSlidingAttacks], selected at compile time:
+//! the [ClassicalAttacks] is the default one, the [KoggeStoneAttacks] is used if the
+//! `kogge-stone` feature is enabled, the [KindergartenAttacks] if the `kindergarten`
+//! feature is enabled and the `MagicAttacks` if the `magic` feature is enabled (if
+//! more features are enabled, the first one in this order wins).
+//!
+//! The speed of the backends can be compared with the `sliding_attacks` benchmark
+//! (`cargo bench --bench sliding_attacks`).
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::slidingattacks::*;
+//! let occupancy = single_cell(Cell::D6) | single_cell(Cell::F4);
+//! assert_eq!(
+//!     ClassicalAttacks::rook_attacks(Cell::D4, occupancy),
+//!     KoggeStoneAttacks::rook_attacks(Cell::D4, occupancy)
+//! );
+//! ```
+
+use crate::attacktables::{rank_bits_to_file_a, FIRST_RANK_ATTACKS, RAYS};
+use crate::bbdefines::*;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+const NOT_FILE_A: BitBoardState = !FILES_BBS[File::FileA as usize];
+const NOT_FILE_H: BitBoardState = !FILES_BBS[File::FileH as usize];
+const FILE_A: BitBoardState = FILES_BBS[File::FileA as usize];
+const FILE_B: BitBoardState = FILES_BBS[File::FileB as usize];
+// The C7-H2 diagonal: multiplied by a file moved onto the file A,
+// maps the cells from A2 to A7 onto the 6 most significant bits
+const DIAG_C7_H2: BitBoardState = 0x00_04_08_10_20_40_80_00;
+
+// The FIRST_RANK_ATTACKS rotated onto the file A, indexed by the rank of the
+// sliding piece and by the occupancy of the cells from A2 to A7
+static FILE_A_ATTACKS: [[BitBoardState; 64]; NUM_RANKS] = gen_file_a_attacks();
+// The FIRST_RANK_ATTACKS replicated on all the ranks, indexed by the file of
+// the sliding piece and by the occupancy of the inner files
+static FILL_UP_ATTACKS: [[BitBoardState; 64]; NUM_FILES] = gen_fill_up_attacks();
+
+/// Trait implemented by the sliding attacks computation backends.
+///
+/// All the functions take the [Cell] where the sliding piece is placed and the
+/// occupancy of the board. The occupancy may or may not contain the cell of
+/// the sliding piece itself: the result is the same.
+pub trait SlidingAttacks {
+    /// Returns the cells attacked by a bishop placed in `c` with the given board occupancy.
+    fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState;
+
+    /// Returns the cells attacked by a rook placed in `c` with the given board occupancy.
+    fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState;
+
+    /// Returns the cells attacked by a queen placed in `c` with the given board occupancy.
+    fn queen_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        Self::bishop_attacks(c, occupancy) | Self::rook_attacks(c, occupancy)
+    }
+}
+
+/// Sliding attacks computed using the precomputed rays and a bit scan.
+pub struct ClassicalAttacks;
+
+/// Sliding attacks computed using the Kogge-Stone occluded fill algorithm.
+///
+/// See the [Kogge-Stone Algorithm](https://www.chessprogramming.org/Kogge-Stone_Algorithm)
+/// page in the chess programming wiki for details.
+pub struct KoggeStoneAttacks;
+
+/// Sliding attacks computed using the kindergarten bitboards lookups.
+///
+/// See the [Kindergarten Bitboards](https://www.chessprogramming.org/Kindergarten_Bitboards)
+/// page in the chess programming wiki for details.
+pub struct KindergartenAttacks;
+
+/// The sliding attacks backend used by the library.
+#[cfg(not(any(feature = "kogge-stone", feature = "kindergarten", feature = "magic")))]
+pub type DefaultSlidingAttacks = ClassicalAttacks;
+
+/// The sliding attacks backend used by the library.
+#[cfg(all(
+    feature = "magic",
+    not(any(feature = "kogge-stone", feature = "kindergarten"))
+))]
+pub type DefaultSlidingAttacks = crate::magics::MagicAttacks;
+
+/// The sliding attacks backend used by the library.
+#[cfg(all(feature = "kindergarten", not(feature = "kogge-stone")))]
+pub type DefaultSlidingAttacks = KindergartenAttacks;
+
+/// The sliding attacks backend used by the library.
+#[cfg(feature = "kogge-stone")]
+pub type DefaultSlidingAttacks = KoggeStoneAttacks;
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Returns the cells attacked by a bishop in `c` using the [DefaultSlidingAttacks] backend.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::bitboard::BitBoard;
+/// # use abbadingo::slidingattacks::*;
+/// assert_eq!(
+///     BitBoard::from(bishop_attacks(Cell::B2, single_cell(Cell::D4))),
+///     BitBoard::from_cells(&[Cell::A1, Cell::C3, Cell::D4, Cell::A3, Cell::C1])
+/// );
+/// ```
+pub fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+    DefaultSlidingAttacks::bishop_attacks(c, occupancy)
+}
+
+/// Returns the cells attacked by a rook in `c` using the [DefaultSlidingAttacks] backend.
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::bitboard::BitBoard;
+/// # use abbadingo::slidingattacks::*;
+/// assert_eq!(
+///     BitBoard::from(rook_attacks(Cell::A1, single_cell(Cell::A3) | single_cell(Cell::C1))),
+///     BitBoard::from_cells(&[Cell::A2, Cell::A3, Cell::B1, Cell::C1])
+/// );
+/// ```
+pub fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+    DefaultSlidingAttacks::rook_attacks(c, occupancy)
+}
+
+/// Returns the cells attacked by a queen in `c` using the [DefaultSlidingAttacks] backend.
+pub fn queen_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+    DefaultSlidingAttacks::queen_attacks(c, occupancy)
+}
+
+// ----------------------------------------------------------------------------
+// ClassicalAttacks implementation
+
+impl ClassicalAttacks {
+    /// Returns the cells attacked along a ray going towards increasing cell indexes
+    /// (north, north-east, east and north-west directions).
+    ///
+    fn positive_ray_attacks(c: Cell, occupancy: BitBoardState, d: Direction) -> BitBoardState {
+        let ray = RAYS[d as usize][c as usize];
+        let blockers = ray & occupancy;
+        if blockers == EMPTY_STATE {
+            ray
+        } else {
+            ray ^ RAYS[d as usize][blockers.trailing_zeros() as usize]
+        }
+    }
+
+    /// Returns the cells attacked along a ray going towards decreasing cell indexes
+    /// (south, south-west, west and south-east directions).
+    ///
+    fn negative_ray_attacks(c: Cell, occupancy: BitBoardState, d: Direction) -> BitBoardState {
+        let ray = RAYS[d as usize][c as usize];
+        let blockers = ray & occupancy;
+        if blockers == EMPTY_STATE {
+            ray
+        } else {
+            ray ^ RAYS[d as usize][63 - blockers.leading_zeros() as usize]
+        }
+    }
+}
+
+impl SlidingAttacks for ClassicalAttacks {
+    fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        ClassicalAttacks::positive_ray_attacks(c, occupancy, Direction::NorthEast)
+            | ClassicalAttacks::positive_ray_attacks(c, occupancy, Direction::NorthWest)
+            | ClassicalAttacks::negative_ray_attacks(c, occupancy, Direction::SouthEast)
+            | ClassicalAttacks::negative_ray_attacks(c, occupancy, Direction::SouthWest)
+    }
+
+    fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        ClassicalAttacks::positive_ray_attacks(c, occupancy, Direction::North)
+            | ClassicalAttacks::positive_ray_attacks(c, occupancy, Direction::East)
+            | ClassicalAttacks::negative_ray_attacks(c, occupancy, Direction::South)
+            | ClassicalAttacks::negative_ray_attacks(c, occupancy, Direction::West)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// KoggeStoneAttacks implementation
+
+impl KoggeStoneAttacks {
+    /// Shifts all the cells of a bitboard state by one step in the given [Direction],
+    /// discarding the cells going outside the board.
+    ///
+    fn shift_one(bbs: BitBoardState, d: Direction) -> BitBoardState {
+        match d {
+            Direction::North => bbs << 8,
+            Direction::NorthEast => (bbs << 9) & NOT_FILE_A,
+            Direction::East => (bbs << 1) & NOT_FILE_A,
+            Direction::SouthEast => (bbs >> 7) & NOT_FILE_A,
+            Direction::South => bbs >> 8,
+            Direction::SouthWest => (bbs >> 9) & NOT_FILE_H,
+            Direction::West => (bbs >> 1) & NOT_FILE_H,
+            Direction::NorthWest => (bbs << 7) & NOT_FILE_H,
+        }
+    }
+
+    /// Computes the occluded fill of the `generator` cells in the given [Direction],
+    /// propagating only through the `propagator` (i.e. empty) cells.
+    ///
+    fn occluded_fill(
+        mut generator: BitBoardState,
+        mut propagator: BitBoardState,
+        d: Direction,
+    ) -> BitBoardState {
+        let shift: u32 = match d {
+            Direction::North | Direction::South => 8,
+            Direction::NorthEast | Direction::SouthWest => 9,
+            Direction::East | Direction::West => 1,
+            Direction::SouthEast | Direction::NorthWest => 7,
+        };
+        // Cells wrapping from a board side to the other are removed
+        // from the propagator, for all the directions but north and south
+        propagator &= match d {
+            Direction::North | Direction::South => !EMPTY_STATE,
+            Direction::NorthEast | Direction::East | Direction::SouthEast => NOT_FILE_A,
+            Direction::SouthWest | Direction::West | Direction::NorthWest => NOT_FILE_H,
+        };
+        let towards_msb = matches!(
+            d,
+            Direction::North | Direction::NorthEast | Direction::East | Direction::NorthWest
+        );
+        for step in [shift, shift * 2, shift * 4].iter() {
+            if towards_msb {
+                generator |= propagator & (generator << step);
+                propagator &= propagator << step;
+            } else {
+                generator |= propagator & (generator >> step);
+                propagator &= propagator >> step;
+            }
+        }
+        generator
+    }
+
+    /// Returns the cells attacked by a slider in `c` along the given [Direction].
+    ///
+    fn direction_attacks(c: Cell, occupancy: BitBoardState, d: Direction) -> BitBoardState {
+        KoggeStoneAttacks::shift_one(
+            KoggeStoneAttacks::occluded_fill(single_cell(c), !occupancy, d),
+            d,
+        )
+    }
+}
+
+impl SlidingAttacks for KoggeStoneAttacks {
+    fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::NorthEast)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::NorthWest)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::SouthEast)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::SouthWest)
+    }
+
+    fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::North)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::East)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::South)
+            | KoggeStoneAttacks::direction_attacks(c, occupancy, Direction::West)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// KindergartenAttacks implementation
+
+impl KindergartenAttacks {
+    /// Returns the cells attacked by a slider in `c` along its [Rank].
+    ///
+    fn rank_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        let shift = rank(c) as usize * NUM_FILES;
+        let inner = ((occupancy >> (shift + 1)) & 0x3F) as usize;
+        (FIRST_RANK_ATTACKS[file(c) as usize][inner] as BitBoardState) << shift
+    }
+
+    /// Returns the cells attacked by a slider in `c` along its [File].
+    ///
+    fn file_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        let f = file(c) as usize;
+        let inner = (FILE_A & (occupancy >> f)).wrapping_mul(DIAG_C7_H2) >> 58;
+        FILE_A_ATTACKS[rank(c) as usize][inner as usize] << f
+    }
+
+    /// Returns the cells attacked by a slider in `c` along a diagonal or
+    /// antidiagonal line (given its mask).
+    ///
+    fn diagonal_attacks(c: Cell, occupancy: BitBoardState, line: BitBoardState) -> BitBoardState {
+        let line = line ^ single_cell(c);
+        // Each file has a single cell of the line: the multiplication
+        // maps them onto the 8th rank, in the file order
+        let inner = (line & occupancy).wrapping_mul(FILE_B) >> 58;
+        line & FILL_UP_ATTACKS[file(c) as usize][inner as usize]
+    }
+}
+
+impl SlidingAttacks for KindergartenAttacks {
+    fn bishop_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        KindergartenAttacks::diagonal_attacks(c, occupancy, diag_mask(c))
+            | KindergartenAttacks::diagonal_attacks(c, occupancy, antidiag_mask(c))
+    }
+
+    fn rook_attacks(c: Cell, occupancy: BitBoardState) -> BitBoardState {
+        KindergartenAttacks::rank_attacks(c, occupancy)
+            | KindergartenAttacks::file_attacks(c, occupancy)
+    }
+}
+
+/// Generates the first rank attacks rotated onto the file A.
+///
+const fn gen_file_a_attacks() -> [[BitBoardState; 64]; NUM_RANKS] {
+    let mut table = [[EMPTY_STATE; 64]; NUM_RANKS];
+    let mut r = 0;
+    while r < NUM_RANKS {
+        let mut inner = 0;
+        while inner < 64 {
+            table[r][inner] = rank_bits_to_file_a(FIRST_RANK_ATTACKS[r][inner]);
+            inner += 1;
+        }
+        r += 1;
+    }
+    table
+}
+
+/// Generates the first rank attacks replicated on all the ranks.
+///
+const fn gen_fill_up_attacks() -> [[BitBoardState; 64]; NUM_FILES] {
+    let mut table = [[EMPTY_STATE; 64]; NUM_FILES];
+    let mut f = 0;
+    while f < NUM_FILES {
+        let mut inner = 0;
+        while inner < 64 {
+            table[f][inner] = (FIRST_RANK_ATTACKS[f][inner] as BitBoardState).wrapping_mul(FILE_A);
+            inner += 1;
+        }
+        f += 1;
+    }
+    table
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Reference implementation: walks the board cell by cell
+    // until the edge of the board or the first busy cell
+    fn walk_attacks(c: Cell, occupancy: BitBoardState, dirs: &[Direction]) -> BitBoardState {
+        let mut bbs = EMPTY_STATE;
+        for d in dirs {
+            let (step_north, step_east) = d.steps();
+            let mut cur = calc_cell_after_steps(c, step_north, step_east);
+            while let Some(next) = cur {
+                bbs |= single_cell(next);
+                if occupancy & single_cell(next) != EMPTY_STATE {
+                    break;
+                }
+                cur = calc_cell_after_steps(next, step_north, step_east);
+            }
+        }
+        bbs
+    }
+
+    const BISHOP_DIRS: [Direction; 4] = [
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+    const ROOK_DIRS: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    // Simple xorshift generator used to obtain reproducible occupancies
+    fn next_occupancy(seed: &mut u64) -> BitBoardState {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        // sparse occupancy, more similar to a real game position
+        *seed & (*seed >> 3) & (*seed >> 11)
+    }
+
+    fn check_backend<T: SlidingAttacks>() {
+        let mut seed = 0x9E37_79B9_7F4A_7C15_u64;
+        for _ in 0..64 {
+            let occupancy = next_occupancy(&mut seed);
+            for ndx in 0..NUM_CELLS {
+                let c: Cell = num::FromPrimitive::from_usize(ndx).unwrap();
+                assert_eq!(
+                    T::bishop_attacks(c, occupancy),
+                    walk_attacks(c, occupancy, &BISHOP_DIRS)
+                );
+                assert_eq!(
+                    T::rook_attacks(c, occupancy),
+                    walk_attacks(c, occupancy, &ROOK_DIRS)
+                );
+                assert_eq!(
+                    T::queen_attacks(c, occupancy),
+                    T::bishop_attacks(c, occupancy) | T::rook_attacks(c, occupancy)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn classical_attacks_match_the_reference_implementation() {
+        check_backend::<ClassicalAttacks>();
+    }
+
+    #[test]
+    fn kogge_stone_attacks_match_the_reference_implementation() {
+        check_backend::<KoggeStoneAttacks>();
+    }
+
+    #[test]
+    fn kindergarten_attacks_match_the_reference_implementation() {
+        check_backend::<KindergartenAttacks>();
+    }
+
+    #[test]
+    fn attacks_on_an_empty_board_are_the_masks() {
+        assert_eq!(
+            ClassicalAttacks::queen_attacks(Cell::D4, EMPTY_STATE),
+            queen_mask(Cell::D4) ^ single_cell(Cell::D4)
+        );
+        assert_eq!(
+            KoggeStoneAttacks::queen_attacks(Cell::H1, EMPTY_STATE),
+            queen_mask(Cell::H1) ^ single_cell(Cell::H1)
+        );
+        assert_eq!(
+            KindergartenAttacks::queen_attacks(Cell::A8, EMPTY_STATE),
+            queen_mask(Cell::A8) ^ single_cell(Cell::A8)
+        );
+    }
+
+    #[test]
+    fn slider_cell_in_the_occupancy_does_not_change_the_result() {
+        let occupancy = single_cell(Cell::E7) | single_cell(Cell::B4);
+        assert_eq!(
+            ClassicalAttacks::rook_attacks(Cell::E4, occupancy),
+            ClassicalAttacks::rook_attacks(Cell::E4, occupancy | single_cell(Cell::E4))
+        );
+        assert_eq!(
+            KoggeStoneAttacks::rook_attacks(Cell::E4, occupancy),
+            KoggeStoneAttacks::rook_attacks(Cell::E4, occupancy | single_cell(Cell::E4))
+        );
+        assert_eq!(
+            KindergartenAttacks::bishop_attacks(Cell::E4, occupancy),
+            KindergartenAttacks::bishop_attacks(Cell::E4, occupancy | single_cell(Cell::E4))
+        );
+    }
+}
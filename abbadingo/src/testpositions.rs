@@ -0,0 +1,337 @@
+//! A catalog of well known chess positions useful to test move generators and
+//! other position related code.
+//!
+//! Each [PerftPosition] contains the FEN string of the position, a short
+//! description of what makes the position interesting and the expected results
+//! of the [perft](https://www.chessprogramming.org/Perft) function (the number
+//! of leaf nodes of the legal moves tree) at some depths.
+//!
+//! The catalog contains the positions of the
+//! [Perft Results](https://www.chessprogramming.org/Perft_Results) page of the
+//! Chess Programming Wiki and a selection of tricky cases (illegal en-passant
+//! captures, castling and promotions giving check, stalemates) collected by
+//! Martin Sedlak.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::testpositions::*;
+//! assert_eq!(KIWIPETE.perft_at(1), Some(48));
+//! assert_eq!(KIWIPETE.perft_at(7), None);
+//! assert!(ALL_PERFT_POSITIONS.iter().any(|p| p.perft_at(1) == Some(0)));
+//! ```
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// A test position with the expected perft results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftPosition {
+    /// The name of the position.
+    pub name: &'static str,
+    /// The position, as a FEN string.
+    pub fen: &'static str,
+    /// What makes the position interesting.
+    pub description: &'static str,
+    /// True if the army to move is in check.
+    pub in_check: bool,
+    /// The expected perft results, as (depth, number of leaf nodes) pairs
+    /// sorted by depth.
+    pub perft: &'static [(u32, u64)],
+}
+
+/// The initial standard position.
+pub const STARTPOS: PerftPosition = PerftPosition {
+    name: "Initial position",
+    fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    description: "The initial standard position",
+    in_check: false,
+    perft: &[
+        (1, 20),
+        (2, 400),
+        (3, 8_902),
+        (4, 197_281),
+        (5, 4_865_609),
+        (6, 119_060_324),
+    ],
+};
+
+/// "Kiwipete", the position 2 of the Chess Programming Wiki perft results.
+pub const KIWIPETE: PerftPosition = PerftPosition {
+    name: "Kiwipete",
+    fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    description: "Castling on both sides, en-passant, promotions and many captures",
+    in_check: false,
+    perft: &[
+        (1, 48),
+        (2, 2_039),
+        (3, 97_862),
+        (4, 4_085_603),
+        (5, 193_690_690),
+    ],
+};
+
+/// Position 3 of the Chess Programming Wiki perft results.
+pub const CPW_POSITION_3: PerftPosition = PerftPosition {
+    name: "CPW position 3",
+    fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    description: "Rook endgame with en-passant captures exposing the king",
+    in_check: false,
+    perft: &[
+        (1, 14),
+        (2, 191),
+        (3, 2_812),
+        (4, 43_238),
+        (5, 674_624),
+        (6, 11_030_083),
+    ],
+};
+
+/// Position 4 of the Chess Programming Wiki perft results.
+pub const CPW_POSITION_4: PerftPosition = PerftPosition {
+    name: "CPW position 4",
+    fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    description: "Promotions, captures of promoted pieces and castling rights lost",
+    in_check: true,
+    perft: &[(1, 6), (2, 264), (3, 9_467), (4, 422_333), (5, 15_833_292)],
+};
+
+/// Position 5 of the Chess Programming Wiki perft results.
+pub const CPW_POSITION_5: PerftPosition = PerftPosition {
+    name: "CPW position 5",
+    fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    description: "Promotions with capture and a knight attacking the castling squares",
+    in_check: false,
+    perft: &[
+        (1, 44),
+        (2, 1_486),
+        (3, 62_379),
+        (4, 2_103_487),
+        (5, 89_941_194),
+    ],
+};
+
+/// Position 6 of the Chess Programming Wiki perft results.
+pub const CPW_POSITION_6: PerftPosition = PerftPosition {
+    name: "CPW position 6",
+    fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    description: "A quiet middlegame position with pins",
+    in_check: false,
+    perft: &[(1, 46), (2, 2_079), (3, 89_890), (4, 3_894_594)],
+};
+
+/// En-passant capture that would leave the king in check along the rank.
+pub const ILLEGAL_EN_PASSANT: PerftPosition = PerftPosition {
+    name: "Illegal en-passant",
+    fen: "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1",
+    description: "After d7-d5 the en-passant capture would expose the king to the rook",
+    in_check: false,
+    perft: &[(6, 1_134_888)],
+};
+
+/// En-passant capture that would leave the king in check along a diagonal.
+pub const ILLEGAL_EN_PASSANT_2: PerftPosition = PerftPosition {
+    name: "Illegal en-passant (2)",
+    fen: "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+    description: "After d2-d4 the en-passant capture would expose the king to the bishop",
+    in_check: false,
+    perft: &[(6, 1_015_133)],
+};
+
+/// En-passant capture giving check.
+pub const EN_PASSANT_GIVES_CHECK: PerftPosition = PerftPosition {
+    name: "En-passant capture gives check",
+    fen: "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+    description: "The en-passant capture discovers a check from the bishop",
+    in_check: false,
+    perft: &[(6, 1_440_467)],
+};
+
+/// Short castling giving check.
+pub const SHORT_CASTLING_GIVES_CHECK: PerftPosition = PerftPosition {
+    name: "Short castling gives check",
+    fen: "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+    description: "After O-O the rook gives check",
+    in_check: false,
+    perft: &[(6, 661_072)],
+};
+
+/// Long castling giving check.
+pub const LONG_CASTLING_GIVES_CHECK: PerftPosition = PerftPosition {
+    name: "Long castling gives check",
+    fen: "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+    description: "After O-O-O the rook gives check",
+    in_check: false,
+    perft: &[(6, 803_711)],
+};
+
+/// Castling rights lost because of rook captures.
+pub const CASTLING_RIGHTS_LOST: PerftPosition = PerftPosition {
+    name: "Castling rights lost",
+    fen: "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1",
+    description: "The rooks can be captured, removing the castling rights",
+    in_check: false,
+    perft: &[(4, 1_274_206)],
+};
+
+/// Castling prevented by attacked squares.
+pub const CASTLING_PREVENTED: PerftPosition = PerftPosition {
+    name: "Castling prevented",
+    fen: "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+    description: "The queens attack the squares crossed by the kings while castling",
+    in_check: false,
+    perft: &[(4, 1_720_476)],
+};
+
+/// Promotion to escape a check.
+pub const PROMOTE_OUT_OF_CHECK: PerftPosition = PerftPosition {
+    name: "Promote out of check",
+    fen: "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1",
+    description: "The pawn can escape the check capturing the rook and promoting",
+    in_check: true,
+    perft: &[(6, 3_821_001)],
+};
+
+/// Discovered check.
+pub const DISCOVERED_CHECK: PerftPosition = PerftPosition {
+    name: "Discovered check",
+    fen: "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1",
+    description: "Moving the knight discovers a check from the queen",
+    in_check: false,
+    perft: &[(5, 1_004_658)],
+};
+
+/// Promotion giving check.
+pub const PROMOTE_TO_GIVE_CHECK: PerftPosition = PerftPosition {
+    name: "Promote to give check",
+    fen: "4k3/1P6/8/8/8/8/K7/8 w - - 0 1",
+    description: "The promoted queen or rook gives check",
+    in_check: false,
+    perft: &[(6, 217_342)],
+};
+
+/// Under-promotion giving check.
+pub const UNDERPROMOTE_TO_GIVE_CHECK: PerftPosition = PerftPosition {
+    name: "Under-promotion gives check",
+    fen: "8/P1k5/K7/8/8/8/8/8 w - - 0 1",
+    description: "Only the promotion to knight gives check",
+    in_check: false,
+    perft: &[(6, 92_683)],
+};
+
+/// Self stalemate.
+pub const SELF_STALEMATE: PerftPosition = PerftPosition {
+    name: "Self stalemate",
+    fen: "K1k5/8/P7/8/8/8/8/8 w - - 0 1",
+    description: "Pushing the pawn stalemates the own king",
+    in_check: false,
+    perft: &[(6, 2_217)],
+};
+
+/// Stalemate and checkmate.
+pub const STALEMATE_AND_CHECKMATE: PerftPosition = PerftPosition {
+    name: "Stalemate and checkmate",
+    fen: "8/k1P5/8/1K6/8/8/8/8 w - - 0 1",
+    description: "Promotions leading either to stalemate or to checkmate",
+    in_check: false,
+    perft: &[(7, 567_584)],
+};
+
+/// Stalemate and checkmate (2).
+pub const STALEMATE_AND_CHECKMATE_2: PerftPosition = PerftPosition {
+    name: "Stalemate and checkmate (2)",
+    fen: "8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1",
+    description: "Queen and knight against the lone king: stalemates and checkmates",
+    in_check: false,
+    perft: &[(4, 23_527)],
+};
+
+/// A stalemate: the army to move has no legal moves and is not in check.
+pub const STALEMATE: PerftPosition = PerftPosition {
+    name: "Stalemate",
+    fen: "k7/8/1Q6/8/8/8/8/7K b - - 0 1",
+    description: "Black is stalemated by the queen",
+    in_check: false,
+    perft: &[(1, 0)],
+};
+
+/// A checkmate: the army to move has no legal moves and is in check.
+pub const CHECKMATE: PerftPosition = PerftPosition {
+    name: "Checkmate",
+    fen: "k7/1Q6/1K6/8/8/8/8/8 b - - 0 1",
+    description: "Black is checkmated by the queen protected by the king",
+    in_check: true,
+    perft: &[(1, 0)],
+};
+
+/// All the positions of the catalog.
+pub const ALL_PERFT_POSITIONS: &[PerftPosition] = &[
+    STARTPOS,
+    KIWIPETE,
+    CPW_POSITION_3,
+    CPW_POSITION_4,
+    CPW_POSITION_5,
+    CPW_POSITION_6,
+    ILLEGAL_EN_PASSANT,
+    ILLEGAL_EN_PASSANT_2,
+    EN_PASSANT_GIVES_CHECK,
+    SHORT_CASTLING_GIVES_CHECK,
+    LONG_CASTLING_GIVES_CHECK,
+    CASTLING_RIGHTS_LOST,
+    CASTLING_PREVENTED,
+    PROMOTE_OUT_OF_CHECK,
+    DISCOVERED_CHECK,
+    PROMOTE_TO_GIVE_CHECK,
+    UNDERPROMOTE_TO_GIVE_CHECK,
+    SELF_STALEMATE,
+    STALEMATE_AND_CHECKMATE,
+    STALEMATE_AND_CHECKMATE_2,
+    STALEMATE,
+    CHECKMATE,
+];
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl PerftPosition {
+    /// Returns the expected perft result at the given depth, if known.
+    pub fn perft_at(&self, depth: u32) -> Option<u64> {
+        self.perft
+            .iter()
+            .find(|(d, _)| *d == depth)
+            .map(|(_, nodes)| *nodes)
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::epd::TestPosition;
+    use crate::fenrecord::FenRecord;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn catalog_positions_are_well_formed() {
+        for p in ALL_PERFT_POSITIONS {
+            let tp = TestPosition::try_from(p.fen).unwrap();
+            assert_eq!(tp.fen, p.fen, "{}", p.name);
+            assert!(!p.perft.is_empty(), "{}", p.name);
+            assert!(p.perft.windows(2).all(|w| w[0].0 < w[1].0), "{}", p.name);
+            // The army not to move cannot be in check
+            let board = FenRecord::try_from(p.fen).unwrap().board();
+            let (us, them) = (board.army(board.to_move), board.army(!board.to_move));
+            assert!(us.pieces_giving_check(them).is_empty(), "{}", p.name);
+            assert_eq!(board.is_in_check(), p.in_check, "{}", p.name);
+        }
+        assert_eq!(STARTPOS.fen, crate::fenrecord::INITIAL_STANDARD_POSITION);
+    }
+}
@@ -0,0 +1,249 @@
+//! Small utilities for board-vision training applications.
+//!
+//! The module provides:
+//!
+//! - the colour of the cells of the board ([cell_colour]);
+//! - a deterministic pseudo-random [CellSampler], to extract random cells
+//!   (optionally of a given colour) without external dependencies;
+//! - the spoken name of a cell ([spoken_name]), e.g. for text-to-speech;
+//! - the [Mirror] transformations of the board, used for quizzes where the
+//!   board is shown from the point of view of the Black army or mirrored.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::training::*;
+//! let mut sampler = CellSampler::new(2024);
+//! let c = sampler.next_cell_of_colour(CellColour::Dark);
+//! assert_eq!(cell_colour(c), CellColour::Dark);
+//!
+//! // "Where is e2 for the Black player?"
+//! let q = sampler.next_quiz(Mirror::Rotate180);
+//! assert_eq!(Mirror::Rotate180.apply(q.answer), q.shown);
+//! assert_eq!(spoken_name(Cell::E2), "e two");
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::bbdefines::*;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+const SPOKEN_RANKS: [&str; NUM_RANKS] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight",
+];
+
+// Mixed with the seed of the CellSampler (the xorshift generators work badly
+// with states having few bits set, as small seeds).
+const SEED_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The colour of a cell of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellColour {
+    Light,
+    Dark,
+}
+
+/// A transformation of the board coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mirror {
+    /// Mirror the board vertically (ranks reversed): e2 becomes e7.
+    Vertical,
+    /// Mirror the board horizontally (files reversed): e2 becomes d2.
+    Horizontal,
+    /// Rotate the board by 180 degrees, i.e. the board seen from the Black
+    /// army side: e2 becomes d7.
+    Rotate180,
+}
+
+/// A question of a mirrored-coordinates quiz: the `shown` cell is the
+/// position of the `answer` cell once the board is transformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quiz {
+    pub mirror: Mirror,
+    pub shown: Cell,
+    pub answer: Cell,
+}
+
+/// A generator of pseudo-random cells.
+///
+/// The sampler uses a `xorshift64*` generator: the sequence of cells only
+/// depends on the seed, so it is reproducible (and not suitable for
+/// anything requiring real randomness).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellSampler {
+    state: u64,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Returns the [CellColour] of a [Cell] (a1 is dark, h1 is light).
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::training::*;
+/// assert_eq!(cell_colour(Cell::A1), CellColour::Dark);
+/// assert_eq!(cell_colour(Cell::D1), CellColour::Light);
+/// ```
+pub fn cell_colour(c: Cell) -> CellColour {
+    let (f, r) = coords(c);
+    if (f as usize + r as usize).is_multiple_of(2) {
+        CellColour::Dark
+    } else {
+        CellColour::Light
+    }
+}
+
+/// Returns the spoken name of a [Cell], i.e. the file letter followed
+/// by the rank number in words ("a one", ..., "h eight").
+pub fn spoken_name(c: Cell) -> String {
+    let (f, r) = coords(c);
+    format!("{} {}", f, SPOKEN_RANKS[r as usize])
+}
+
+impl Mirror {
+    /// Applies the transformation to a [Cell].
+    ///
+    /// All the transformations are involutions: applying them twice
+    /// returns the original [Cell].
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::bbdefines::*;
+    /// # use abbadingo::training::*;
+    /// assert_eq!(Mirror::Vertical.apply(Cell::B3), Cell::B6);
+    /// assert_eq!(Mirror::Horizontal.apply(Cell::B3), Cell::G3);
+    /// assert_eq!(Mirror::Rotate180.apply(Cell::B3), Cell::G6);
+    /// ```
+    pub fn apply(self, c: Cell) -> Cell {
+        let ndx = c as usize
+            ^ match self {
+                Mirror::Vertical => 0x38,
+                Mirror::Horizontal => 0x07,
+                Mirror::Rotate180 => 0x3F,
+            };
+        // We can unwrap safely here... the index is always a valid cell
+        num::FromPrimitive::from_usize(ndx).unwrap()
+    }
+}
+
+impl CellSampler {
+    /// Creates a new sampler with the given seed.
+    pub fn new(seed: u64) -> CellSampler {
+        // The xorshift state cannot be zero
+        let state = seed ^ SEED_MIX;
+        CellSampler {
+            state: if state == 0 { SEED_MIX } else { state },
+        }
+    }
+
+    /// Returns a random [Cell].
+    pub fn next_cell(&mut self) -> Cell {
+        // The high bits of the xorshift64* output are the best ones
+        let ndx = (self.next_u64() >> 58) as usize;
+        // We can unwrap safely here... the index is always a valid cell
+        num::FromPrimitive::from_usize(ndx).unwrap()
+    }
+
+    /// Returns a random [Cell] of the given [CellColour].
+    pub fn next_cell_of_colour(&mut self, colour: CellColour) -> Cell {
+        let c = self.next_cell();
+        if cell_colour(c) == colour {
+            c
+        } else {
+            // Moving along the rank changes the colour of the cell
+            Mirror::Horizontal.apply(c)
+        }
+    }
+
+    /// Returns a random question of a quiz on the given [Mirror] transformation.
+    pub fn next_quiz(&mut self, mirror: Mirror) -> Quiz {
+        let answer = self.next_cell();
+        Quiz {
+            mirror,
+            shown: mirror.apply(answer),
+            answer,
+        }
+    }
+
+    // ---------------------------------------------------------------------------
+    // PRIVATE METHODS
+    // ---------------------------------------------------------------------------
+
+    /// Returns the next value of the xorshift64* generator.
+    ///
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn cell_colours_alternate() {
+        let dark = (0..NUM_CELLS)
+            .map(|ndx| -> Cell { num::FromPrimitive::from_usize(ndx).unwrap() })
+            .filter(|c| cell_colour(*c) == CellColour::Dark)
+            .count();
+        assert_eq!(dark, NUM_CELLS / 2);
+        assert_eq!(cell_colour(Cell::H8), CellColour::Dark);
+        assert_eq!(cell_colour(Cell::A8), CellColour::Light);
+    }
+
+    #[test]
+    fn sampler_is_reproducible_and_covers_the_board() {
+        let mut s1 = CellSampler::new(42);
+        let mut s2 = CellSampler::new(42);
+        let mut seen = [false; NUM_CELLS];
+        for _ in 0..2000 {
+            let c = s1.next_cell();
+            assert_eq!(c, s2.next_cell());
+            seen[c as usize] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+
+        let mut s = CellSampler::new(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..100 {
+            assert_eq!(
+                cell_colour(s.next_cell_of_colour(CellColour::Light)),
+                CellColour::Light
+            );
+        }
+    }
+
+    #[test]
+    fn mirrors_are_involutions() {
+        for m in [Mirror::Vertical, Mirror::Horizontal, Mirror::Rotate180] {
+            for ndx in 0..NUM_CELLS {
+                let c: Cell = num::FromPrimitive::from_usize(ndx).unwrap();
+                assert_eq!(m.apply(m.apply(c)), c);
+            }
+        }
+        assert_eq!(Mirror::Rotate180.apply(Cell::E2), Cell::D7);
+    }
+
+    #[test]
+    fn spoken_names() {
+        assert_eq!(spoken_name(Cell::A1), "a one");
+        assert_eq!(spoken_name(Cell::H8), "h eight");
+    }
+}
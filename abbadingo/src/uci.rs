@@ -0,0 +1,8 @@
+//! Support for the [Universal Chess Interface](https://www.shredderchess.com/chess-features/uci-universal-chess-interface.html)
+//! (UCI) protocol.
+//!
+//! - [client]: a client to drive an external UCI engine process.
+//!
+//! The module requires the `std` feature.
+
+pub mod client;
@@ -0,0 +1,523 @@
+//! Client for an external UCI engine process.
+//!
+//! The [UciEngine] spawns the engine executable and talks with it through its
+//! standard input and output: the typical session is the `uci` handshake
+//! (performed by [UciEngine::spawn]), the setup of the position and the search,
+//! whose output is parsed into [UciInfo] lines and a final [BestMove].
+//!
+//! The engine output is read by a background thread, so a search can be started
+//! with [UciEngine::start_search], followed while it runs with
+//! [UciEngine::search_infos] and interrupted with [UciEngine::stop];
+//! [UciEngine::go] is the blocking shortcut waiting for the end of the search.
+//! The engine process is killed when the [UciEngine] is dropped.
+//!
+//! Moves are exchanged in the UCI long algebraic format (e.g. `e2e4`, `e7e8q`).
+//!
+//! # Example
+//! ```no_run
+//! # use abbadingo::uci::client::*;
+//! let mut engine = UciEngine::spawn("stockfish", &[]).unwrap();
+//! engine.set_option("MultiPV", "2").unwrap();
+//! engine.set_position(None, &["e2e4", "e7e5"]).unwrap();
+//! let (infos, best) = engine.go("depth 12").unwrap();
+//! println!("Best move: {} (score: {:?})", best.best, infos.last().and_then(|i| i.score));
+//!
+//! engine.start_search("infinite").unwrap();
+//! let infos = engine.search_infos().unwrap();
+//! engine.stop().unwrap();
+//! let (more_infos, best) = engine.wait_bestmove().unwrap();
+//! engine.quit().unwrap();
+//! ```
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+// ********************************************************************************
+// ********************************************************************************
+// CONSTs, ENUMs, STRUCTs, DEFINEs
+// ********************************************************************************
+// ********************************************************************************
+
+/// The score reported by an engine in an `info` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UciScore {
+    /// Score in centipawns, from the point of view of the side to move.
+    Centipawns(i32),
+    /// Mate in the given number of moves (negative if the side to move is mated).
+    Mate(i32),
+}
+
+/// The bound of a score reported by an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UciBound {
+    /// The score is exact.
+    Exact,
+    /// The score is only a lower bound.
+    Lower,
+    /// The score is only an upper bound.
+    Upper,
+}
+
+/// The content of an `info` line sent by the engine during the search.
+///
+/// Fields not present in the line are left to their default value (`None` or empty).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UciInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score: Option<UciScore>,
+    pub bound: Option<UciBound>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time_ms: Option<u64>,
+    pub hashfull: Option<u32>,
+    pub currmove: Option<String>,
+    /// The principal variation.
+    pub pv: Vec<String>,
+    /// Free text sent with the `string` token.
+    pub string: Option<String>,
+}
+
+/// The `bestmove` line sent by the engine at the end of the search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestMove {
+    /// The best move (`0000` if the engine has no legal moves).
+    pub best: String,
+    /// The move the engine would like to ponder on, if any.
+    pub ponder: Option<String>,
+}
+
+/// An external UCI engine process.
+pub struct UciEngine {
+    process: Child,
+    stdin: ChildStdin,
+    // The lines of the engine output, sent by the reader thread
+    lines: Receiver<io::Result<String>>,
+    // The best move of the search, if received but not yet returned
+    best_move: Option<BestMove>,
+    /// The engine name, as sent in the `id name` line.
+    pub name: Option<String>,
+    /// The engine author, as sent in the `id author` line.
+    pub author: Option<String>,
+    /// The option lines sent by the engine during the handshake (without the
+    /// `option` token).
+    pub options: Vec<String>,
+}
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+impl UciInfo {
+    /// Parses an `info` line, returning `None` if the line is not an `info` line.
+    ///
+    /// Unknown tokens are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::uci::client::*;
+    /// let info = UciInfo::parse(
+    ///     "info depth 20 seldepth 28 multipv 1 score cp 35 lowerbound nodes 1234567 pv e2e4 e7e5 g1f3",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(info.depth, Some(20));
+    /// assert_eq!(info.score, Some(UciScore::Centipawns(35)));
+    /// assert_eq!(info.bound, Some(UciBound::Lower));
+    /// assert_eq!(info.pv, vec!["e2e4", "e7e5", "g1f3"]);
+    /// assert_eq!(UciInfo::parse("bestmove e2e4"), None);
+    /// ```
+    pub fn parse(line: &str) -> Option<UciInfo> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next() != Some("info") {
+            return None;
+        }
+        let mut info = UciInfo::default();
+        while let Some(t) = tokens.next() {
+            match t {
+                "depth" => info.depth = tokens.next().and_then(|v| v.parse().ok()),
+                "seldepth" => info.seldepth = tokens.next().and_then(|v| v.parse().ok()),
+                "multipv" => info.multipv = tokens.next().and_then(|v| v.parse().ok()),
+                "nodes" => info.nodes = tokens.next().and_then(|v| v.parse().ok()),
+                "nps" => info.nps = tokens.next().and_then(|v| v.parse().ok()),
+                "time" => info.time_ms = tokens.next().and_then(|v| v.parse().ok()),
+                "hashfull" => info.hashfull = tokens.next().and_then(|v| v.parse().ok()),
+                "currmove" => info.currmove = tokens.next().map(String::from),
+                "score" => {
+                    let kind = tokens.next();
+                    let value = tokens.next().and_then(|v| v.parse().ok());
+                    info.score = match (kind, value) {
+                        (Some("cp"), Some(v)) => Some(UciScore::Centipawns(v)),
+                        (Some("mate"), Some(v)) => Some(UciScore::Mate(v)),
+                        _ => None,
+                    };
+                    info.bound = Some(UciBound::Exact);
+                }
+                "lowerbound" => info.bound = Some(UciBound::Lower),
+                "upperbound" => info.bound = Some(UciBound::Upper),
+                "pv" => {
+                    // The pv is the last token, except for a possible "string"
+                    for m in tokens.by_ref() {
+                        if m == "string" {
+                            info.string = Some(collect_string(&mut tokens));
+                            break;
+                        }
+                        info.pv.push(String::from(m));
+                    }
+                }
+                "string" => info.string = Some(collect_string(&mut tokens)),
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+}
+
+impl BestMove {
+    /// Parses a `bestmove` line, returning `None` if the line is not a valid
+    /// `bestmove` line.
+    ///
+    /// # Example
+    /// ```
+    /// # use abbadingo::uci::client::*;
+    /// assert_eq!(
+    ///     BestMove::parse("bestmove g1f3 ponder d7d5"),
+    ///     Some(BestMove { best: String::from("g1f3"), ponder: Some(String::from("d7d5")) })
+    /// );
+    /// assert_eq!(BestMove::parse("bestmove"), None);
+    /// ```
+    pub fn parse(line: &str) -> Option<BestMove> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next() != Some("bestmove") {
+            return None;
+        }
+        let best = String::from(tokens.next()?);
+        let ponder = match (tokens.next(), tokens.next()) {
+            (Some("ponder"), Some(p)) => Some(String::from(p)),
+            _ => None,
+        };
+        Some(BestMove { best, ponder })
+    }
+}
+
+impl UciEngine {
+    /// Spawns the engine executable with the given arguments and performs the
+    /// `uci` handshake, collecting the engine name, author and options.
+    ///
+    pub fn spawn(program: &str, args: &[&str]) -> io::Result<UciEngine> {
+        let mut process = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = process.stdin.take().ok_or_else(|| broken_pipe("stdin"))?;
+        let stdout = process.stdout.take().ok_or_else(|| broken_pipe("stdout"))?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut engine = UciEngine {
+            process,
+            stdin,
+            lines,
+            best_move: None,
+            name: None,
+            author: None,
+            options: Vec::new(),
+        };
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if line == "uciok" {
+                break;
+            } else if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(String::from(name));
+            } else if let Some(author) = line.strip_prefix("id author ") {
+                engine.author = Some(String::from(author));
+            } else if let Some(option) = line.strip_prefix("option ") {
+                engine.options.push(String::from(option));
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Sends a command line to the engine.
+    pub fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    /// Sends `isready` and waits for the `readyok` answer.
+    pub fn is_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        while self.read_line()? != "readyok" {}
+        Ok(())
+    }
+
+    /// Sets the value of an engine option.
+    pub fn set_option(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.send(&format!("setoption name {} value {}", name, value))
+    }
+
+    /// Sends `ucinewgame` and waits for the engine to be ready.
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Sets the position to search: the position given as FEN string
+    /// (the initial standard position if `None`) followed by the given moves.
+    ///
+    pub fn set_position(&mut self, fen: Option<&str>, moves: &[&str]) -> io::Result<()> {
+        let mut cmd = match fen {
+            Some(f) => format!("position fen {}", f),
+            None => String::from("position startpos"),
+        };
+        if !moves.is_empty() {
+            cmd.push_str(" moves ");
+            cmd.push_str(&moves.join(" "));
+        }
+        self.send(&cmd)
+    }
+
+    /// Starts a search with the given parameters (e.g. `depth 20`,
+    /// `movetime 1000`, `wtime 60000 btime 60000`) and waits for its end,
+    /// returning the `info` lines received and the best move.
+    ///
+    pub fn go(&mut self, params: &str) -> io::Result<(Vec<UciInfo>, BestMove)> {
+        self.start_search(params)?;
+        self.wait_bestmove()
+    }
+
+    /// Starts a search with the given parameters, without waiting for its end
+    /// (see [UciEngine::search_infos] and [UciEngine::wait_bestmove]).
+    pub fn start_search(&mut self, params: &str) -> io::Result<()> {
+        self.best_move = None;
+        self.send(format!("go {}", params).trim_end())
+    }
+
+    /// Returns the `info` lines received since the last call, without waiting
+    /// for new ones. After the end of the search, no more lines are returned.
+    ///
+    pub fn search_infos(&mut self) -> io::Result<Vec<UciInfo>> {
+        let mut infos = Vec::new();
+        while self.best_move.is_none() {
+            match self.lines.try_recv() {
+                Ok(line) => self.parse_search_line(&line?, &mut infos)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(closed_output()),
+            }
+        }
+        Ok(infos)
+    }
+
+    /// Waits for the end of the search, returning the `info` lines not yet
+    /// returned by [UciEngine::search_infos] and the best move.
+    ///
+    pub fn wait_bestmove(&mut self) -> io::Result<(Vec<UciInfo>, BestMove)> {
+        let mut infos = Vec::new();
+        loop {
+            if let Some(best) = self.best_move.take() {
+                return Ok((infos, best));
+            }
+            let line = self.read_line()?;
+            self.parse_search_line(&line, &mut infos)?;
+        }
+    }
+
+    /// Stops a running search (the result is returned by [UciEngine::wait_bestmove]).
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.send("stop")
+    }
+
+    /// Sends `quit` to the engine and waits for the process to terminate.
+    pub fn quit(mut self) -> io::Result<()> {
+        self.send("quit")?;
+        self.process.wait()?;
+        Ok(())
+    }
+
+    /// Reads a line from the engine output, without the line terminator.
+    ///
+    /// Returns an `UnexpectedEof` error if the engine closes its output.
+    ///
+    fn read_line(&mut self) -> io::Result<String> {
+        match self.lines.recv() {
+            Ok(line) => Ok(String::from(line?.trim_end())),
+            Err(_) => Err(closed_output()),
+        }
+    }
+
+    /// Parses a line of the engine output received during a search, adding
+    /// it to the `info` lines or storing it as the best move.
+    ///
+    fn parse_search_line(&mut self, line: &str, infos: &mut Vec<UciInfo>) -> io::Result<()> {
+        if let Some(info) = UciInfo::parse(line) {
+            infos.push(info);
+        } else if line.starts_with("bestmove") {
+            let best = BestMove::parse(line).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "invalid bestmove line")
+            })?;
+            self.best_move = Some(best);
+        }
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Traits implementation for UciEngine structure
+
+/// Drop trait for [UciEngine] structure.
+///
+/// Kills the engine process (if still running) and waits for its termination.
+///
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        // Errors are ignored here: the process may have already terminated
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Collects the remaining tokens of an `info` line as a single string.
+///
+fn collect_string<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> String {
+    tokens.collect::<Vec<_>>().join(" ")
+}
+
+/// Error returned when the engine closes its output.
+///
+fn closed_output() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "the engine closed its output")
+}
+
+/// Error returned when a standard stream of the engine process is not available.
+///
+fn broken_pipe(stream: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        format!("cannot access the engine {}", stream),
+    )
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_info_lines() {
+        let info = UciInfo::parse(
+            "info depth 5 seldepth 7 multipv 2 score mate -3 upperbound nodes 1000 \
+             nps 50000 time 20 hashfull 12 pv h7h8q g8h8",
+        )
+        .unwrap();
+        assert_eq!(info.multipv, Some(2));
+        assert_eq!(info.score, Some(UciScore::Mate(-3)));
+        assert_eq!(info.bound, Some(UciBound::Upper));
+        assert_eq!(info.nps, Some(50000));
+        assert_eq!(info.time_ms, Some(20));
+        assert_eq!(info.hashfull, Some(12));
+        assert_eq!(info.pv, vec!["h7h8q", "g8h8"]);
+
+        let info = UciInfo::parse("info currmove e2e4 currmovenumber 1").unwrap();
+        assert_eq!(info.currmove.as_deref(), Some("e2e4"));
+        assert_eq!(info.depth, None);
+
+        let info = UciInfo::parse("info string NNUE evaluation enabled").unwrap();
+        assert_eq!(info.string.as_deref(), Some("NNUE evaluation enabled"));
+        assert_eq!(UciInfo::parse("readyok"), None);
+    }
+
+    #[test]
+    fn parse_bestmove_lines() {
+        assert_eq!(
+            BestMove::parse("bestmove e7e8q"),
+            Some(BestMove {
+                best: String::from("e7e8q"),
+                ponder: None
+            })
+        );
+        assert_eq!(BestMove::parse("info depth 1"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_with_a_fake_engine() {
+        // A minimal shell script playing the role of the engine
+        let script = r#"
+            while read -r cmd; do
+                case "$cmd" in
+                    uci) echo "id name Fake Engine"; echo "id author Nobody";
+                         echo "option name Hash type spin default 16 min 1 max 1024";
+                         echo "uciok" ;;
+                    isready) echo "readyok" ;;
+                    go*) echo "info depth 1 score cp 20 pv e2e4";
+                         echo "info depth 2 score cp 15 pv e2e4 e7e5";
+                         echo "bestmove e2e4 ponder e7e5" ;;
+                    quit) exit 0 ;;
+                esac
+            done
+        "#;
+        let mut engine = UciEngine::spawn("sh", &["-c", script]).unwrap();
+        assert_eq!(engine.name.as_deref(), Some("Fake Engine"));
+        assert_eq!(engine.author.as_deref(), Some("Nobody"));
+        assert_eq!(engine.options.len(), 1);
+        engine.new_game().unwrap();
+        engine.set_position(None, &["d2d4"]).unwrap();
+        let (infos, best) = engine.go("depth 2").unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1].score, Some(UciScore::Centipawns(15)));
+        assert_eq!(best.best, "e2e4");
+        assert_eq!(best.ponder.as_deref(), Some("e7e5"));
+        engine.quit().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn search_stopped_by_the_client() {
+        // A fake engine searching until it receives the stop command
+        let script = r#"
+            while read -r cmd; do
+                case "$cmd" in
+                    uci) echo "uciok" ;;
+                    go*) echo "info depth 1 score cp 20 pv e2e4" ;;
+                    stop) echo "info depth 2 score cp 15 pv e2e4 e7e5";
+                          echo "bestmove e2e4" ;;
+                esac
+            done
+        "#;
+        let mut engine = UciEngine::spawn("sh", &["-c", script]).unwrap();
+        engine.start_search("infinite").unwrap();
+        let mut infos = Vec::new();
+        while infos.is_empty() {
+            thread::sleep(std::time::Duration::from_millis(10));
+            infos = engine.search_infos().unwrap();
+        }
+        assert_eq!(infos[0].depth, Some(1));
+        engine.stop().unwrap();
+        let (infos, best) = engine.wait_bestmove().unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].depth, Some(2));
+        assert_eq!(best.best, "e2e4");
+        assert!(engine.search_infos().unwrap().is_empty());
+        // The engine ignores quit: dropping it kills the process
+        drop(engine);
+    }
+
+    #[test]
+    fn spawning_a_missing_engine_fails() {
+        assert!(UciEngine::spawn("/this/engine/does/not/exist", &[]).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Experimental support for chess variants.
+//!
+//! The module collects the rules of some chess variants that can be expressed
+//! using the attack masks of the [ChessArmy] structures:
+//!
+//! - [Fog of War](https://en.wikipedia.org/wiki/Dark_chess): each player sees
+//!   only the cells occupied by its pieces and the cells where its pieces can
+//!   move ([fog_of_war_visible_cells], [fog_of_war_view]);
+//! - [Duck Chess](https://duckchess.com/): after each move the player places a
+//!   duck (that cannot be captured and blocks all the pieces) in an empty cell
+//!   ([duck_placements], [duck_chess_moves_for_piece_in_cell]).
+//!
+//! In both variants there is no check: the game is won capturing the King.
+//! The en-passant captures and the castling moves are not considered.
+//!
+//! # Example
+//! ```
+//! # use abbadingo::bbdefines::*;
+//! # use abbadingo::bitboard::BitBoard;
+//! # use abbadingo::chessarmy::ChessArmy;
+//! # use abbadingo::chessdefines::*;
+//! # use abbadingo::variants::*;
+//! let white = ChessArmy::initial(ArmyColour::White);
+//! let black = ChessArmy::initial(ArmyColour::Black);
+//! // At the beginning of the game White sees its half of the board
+//! assert_eq!(fog_of_war_visible_cells(&white, &black), BitBoard::from(0x00_00_00_00_FF_FF_FF_FF));
+//! assert_eq!(fog_of_war_view(&white, &black).num_pieces(), 0);
+//!
+//! // A duck in e4 blocks the double step of the e2 pawn
+//! assert_eq!(
+//!     duck_chess_moves_for_piece_in_cell(&white, ChessPiece::Pawn, Cell::E2, &black, Some(Cell::E4)),
+//!     BitBoard::from_cells(&[Cell::E3])
+//! );
+//! ```
+
+use crate::bbdefines::*;
+use crate::bitboard::BitBoard;
+use crate::chessarmy::ChessArmy;
+use crate::chessdefines::*;
+
+// ********************************************************************************
+// ********************************************************************************
+// FUNCTIONS / METHODS / TRAITS
+// ********************************************************************************
+// ********************************************************************************
+
+/// Returns the [Cell]s visible by the `army` in a Fog of War game, i.e. the cells
+/// occupied by its pieces and the cells where its pieces can move (the pawns
+/// see the diagonal cells only if they can capture there).
+///
+/// # Arguments
+///
+/// * `army`: The [ChessArmy] whose view is computed
+/// * `enemy`: The enemy [ChessArmy]
+///
+pub fn fog_of_war_visible_cells(army: &ChessArmy, enemy: &ChessArmy) -> BitBoard {
+    let mut visible = army.occupied_cells();
+    for ndx in 0..NUM_PIECES_TYPES {
+        // We can unwrap safely here... ndx is always a valid piece
+        let cp: ChessPiece = num::FromPrimitive::from_usize(ndx).unwrap();
+        let mut remaining = army.get_pieces(cp).state;
+        while remaining != EMPTY_STATE {
+            // We can unwrap safely here... trailing_zeros() is always a valid cell
+            let c = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+            remaining &= remaining - 1; // Reset LS1B
+            visible |= army.possible_moves_for_piece_in_cell(cp, c, enemy.occupied_cells());
+        }
+    }
+    visible
+}
+
+/// Returns the `enemy` [ChessArmy] as seen by the `army` in a Fog of War game,
+/// i.e. with only the pieces placed in the [Cell]s visible by the `army`
+/// (see [fog_of_war_visible_cells]).
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessarmy::ChessArmy;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::variants::*;
+/// let mut white = ChessArmy::new(ArmyColour::White);
+/// white.place_pieces(ChessPiece::King, &[Cell::E1]);
+/// white.place_pieces(ChessPiece::Rook, &[Cell::A1]);
+/// let mut black = ChessArmy::new(ArmyColour::Black);
+/// black.place_pieces(ChessPiece::King, &[Cell::E8]);
+/// black.place_pieces(ChessPiece::Knight, &[Cell::A5, Cell::B5]);
+/// // The Rook sees (and could capture) the a5 Knight, the rest is hidden
+/// let seen = fog_of_war_view(&white, &black);
+/// assert_eq!(seen.num_pieces(), 1);
+/// assert!(seen.get_pieces(ChessPiece::Knight).cell_is_active(Cell::A5));
+/// ```
+///
+pub fn fog_of_war_view(army: &ChessArmy, enemy: &ChessArmy) -> ChessArmy {
+    let visible = fog_of_war_visible_cells(army, enemy);
+    let mut view = ChessArmy::new(enemy.colour);
+    for ndx in 0..NUM_PIECES_TYPES {
+        // We can unwrap safely here... ndx is always a valid piece
+        let cp: ChessPiece = num::FromPrimitive::from_usize(ndx).unwrap();
+        let mut remaining = (enemy.get_pieces(cp) & visible).state;
+        while remaining != EMPTY_STATE {
+            // We can unwrap safely here... trailing_zeros() is always a valid cell
+            let c = num::FromPrimitive::from_u32(remaining.trailing_zeros()).unwrap();
+            remaining &= remaining - 1; // Reset LS1B
+            view.place_pieces(cp, &[c]);
+        }
+    }
+    view
+}
+
+/// Returns the [Cell]s where the duck can be placed in a Duck Chess game: all the
+/// free cells of the board, except the one currently occupied by the duck (the
+/// duck has to be moved after each move).
+///
+/// # Arguments
+///
+/// * `duck`: The [Cell] occupied by the duck, `None` if it has not been placed yet
+/// * `white`: The white [ChessArmy]
+/// * `black`: The black [ChessArmy]
+///
+/// # Example
+/// ```
+/// # use abbadingo::bbdefines::*;
+/// # use abbadingo::chessarmy::ChessArmy;
+/// # use abbadingo::chessdefines::*;
+/// # use abbadingo::variants::*;
+/// let white = ChessArmy::initial(ArmyColour::White);
+/// let black = ChessArmy::initial(ArmyColour::Black);
+/// assert_eq!(duck_placements(None, &white, &black).pop_count(), 32);
+/// let placements = duck_placements(Some(Cell::D5), &white, &black);
+/// assert_eq!(placements.pop_count(), 31);
+/// assert!(!placements.cell_is_active(Cell::D5));
+/// ```
+///
+pub fn duck_placements(duck: Option<Cell>, white: &ChessArmy, black: &ChessArmy) -> BitBoard {
+    let busy_cells = white.occupied_cells() | black.occupied_cells() | duck_bitboard(duck);
+    BitBoard::from(0xFF_FF_FF_FF_FF_FF_FF_FF) ^ busy_cells
+}
+
+/// Returns the [BitBoard] with the possible moves in a Duck Chess game of the piece
+/// of the `army` placed in the given [Cell]: the duck blocks the view of the piece
+/// and cannot be captured.
+///
+/// As for [ChessArmy::possible_moves_for_piece_in_cell], the [ChessArmy] shall have
+/// a piece of the given type in the given [Cell].
+///
+/// # Arguments
+///
+/// * `army`: The [ChessArmy] moving the piece
+/// * `cp`: the [ChessPiece] type of the piece
+/// * `c`: the [Cell] where the piece is placed
+/// * `enemy`: The enemy [ChessArmy]
+/// * `duck`: The [Cell] occupied by the duck, `None` if it has not been placed yet
+///
+pub fn duck_chess_moves_for_piece_in_cell(
+    army: &ChessArmy,
+    cp: ChessPiece,
+    c: Cell,
+    enemy: &ChessArmy,
+    duck: Option<Cell>,
+) -> BitBoard {
+    let duck = duck_bitboard(duck);
+    let moves = army.possible_moves_for_piece_in_cell(cp, c, enemy.occupied_cells() | duck);
+    (moves | duck) ^ duck
+}
+
+/// Returns the [BitBoard] with the cell occupied by the duck, if any.
+///
+fn duck_bitboard(duck: Option<Cell>) -> BitBoard {
+    match duck {
+        Some(c) => BitBoard::from_cells(&[c]),
+        None => BitBoard::new(),
+    }
+}
+
+// ****************************************************************************
+// TESTS
+// ****************************************************************************
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn fog_of_war_shows_only_reachable_enemy_pieces() {
+        let mut white = ChessArmy::new(ArmyColour::White);
+        white.place_pieces(ChessPiece::King, &[Cell::G1]);
+        white.place_pieces(ChessPiece::Pawn, &[Cell::D4]);
+        let mut black = ChessArmy::new(ArmyColour::Black);
+        black.place_pieces(ChessPiece::King, &[Cell::G8]);
+        black.place_pieces(ChessPiece::Pawn, &[Cell::D5, Cell::E5]);
+
+        // The d4 pawn is blocked but can capture in e5, while c5 is empty
+        let visible = fog_of_war_visible_cells(&white, &black);
+        assert!(visible.cell_is_active(Cell::E5));
+        assert!(!visible.cell_is_active(Cell::D5));
+        assert!(!visible.cell_is_active(Cell::C5));
+        let seen = fog_of_war_view(&white, &black);
+        assert_eq!(
+            seen.get_pieces(ChessPiece::Pawn),
+            BitBoard::from_cells(&[Cell::E5])
+        );
+        assert_eq!(seen.colour, ArmyColour::Black);
+
+        // Black sees the white pawn only from e5, that can capture it:
+        // the d5 pawn is blocked by the d4 one
+        let seen = fog_of_war_view(&black, &white);
+        assert_eq!(
+            seen.get_pieces(ChessPiece::Pawn),
+            BitBoard::from_cells(&[Cell::D4])
+        );
+        assert!(seen.get_pieces(ChessPiece::King).is_empty());
+        let mut d5_only = ChessArmy::new(ArmyColour::Black);
+        d5_only.place_pieces(ChessPiece::King, &[Cell::G8]);
+        d5_only.place_pieces(ChessPiece::Pawn, &[Cell::D5]);
+        assert!(!fog_of_war_visible_cells(&d5_only, &white).cell_is_active(Cell::D4));
+    }
+
+    #[test]
+    fn the_duck_blocks_and_cannot_be_captured() {
+        let mut white = ChessArmy::new(ArmyColour::White);
+        white.place_pieces(ChessPiece::King, &[Cell::E1]);
+        white.place_pieces(ChessPiece::Rook, &[Cell::A1]);
+        white.place_pieces(ChessPiece::Pawn, &[Cell::C2]);
+        let black = ChessArmy::new(ArmyColour::Black);
+
+        let duck = Some(Cell::A4);
+        assert_eq!(
+            duck_chess_moves_for_piece_in_cell(&white, ChessPiece::Rook, Cell::A1, &black, duck),
+            BitBoard::from_cells(&[Cell::A2, Cell::A3, Cell::B1, Cell::C1, Cell::D1])
+        );
+        // The pawn cannot capture the duck in b3
+        assert_eq!(
+            duck_chess_moves_for_piece_in_cell(
+                &white,
+                ChessPiece::Pawn,
+                Cell::C2,
+                &black,
+                Some(Cell::B3)
+            ),
+            BitBoard::from_cells(&[Cell::C3, Cell::C4])
+        );
+        // The King cannot step on the duck
+        assert!(!duck_chess_moves_for_piece_in_cell(
+            &white,
+            ChessPiece::King,
+            Cell::E1,
+            &black,
+            Some(Cell::E2)
+        )
+        .cell_is_active(Cell::E2));
+    }
+}
@@ -0,0 +1,31 @@
+use abbadingo::epd::load_test_suite;
+use abbadingo::error::AbbaDingoError;
+
+// ------------------------------------------------------------
+#[test]
+fn itest_load_test_suite_from_file() {
+    let path = std::env::temp_dir().join("abbadingo_epd_itest.epd");
+    std::fs::write(
+        &path,
+        "# Win At Chess, first positions\n\
+         2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";\n\
+         8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - bm Rxb2; id \"WAC.002\";\n",
+    )
+    .unwrap();
+    let suite = load_test_suite(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(suite.len(), 2);
+    for tp in &suite {
+        println!("{:?}: {} (best moves: {:?})", tp.id, tp.fen, tp.best_moves);
+    }
+    assert_eq!(suite[1].id.as_deref(), Some("WAC.002"));
+    assert_eq!(suite[1].best_moves, vec!["Rxb2"]);
+}
+
+#[test]
+fn itest_load_missing_test_suite() {
+    assert_eq!(
+        load_test_suite("/this/file/does/not/exist.epd"),
+        Err(AbbaDingoError::CannotReadFile)
+    );
+}
@@ -0,0 +1,19 @@
+use abbadingo::fenrecord::FenRecord;
+use abbadingo::perft::perft;
+use abbadingo::testpositions::ALL_PERFT_POSITIONS;
+use std::convert::TryFrom;
+
+// Node counts above this limit take too long in debug builds
+const MAX_NODES: u64 = 250_000;
+
+// ------------------------------------------------------------
+#[test]
+fn itest_perft_of_catalog_positions() {
+    for p in ALL_PERFT_POSITIONS {
+        let board = FenRecord::try_from(p.fen).unwrap().board();
+        for &(depth, nodes) in p.perft.iter().filter(|&&(_, n)| n <= MAX_NODES) {
+            println!("{} at depth {}: {} nodes", p.name, depth, nodes);
+            assert_eq!(perft(&board, depth), nodes, "{} at depth {}", p.name, depth);
+        }
+    }
+}